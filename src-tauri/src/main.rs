@@ -3,8 +3,12 @@
 
 use git2::{Repository, Oid};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::fs;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime};
 use anyhow::Result; 
 use std::io::Write;
 use tauri::{Manager, SystemTray, SystemTrayEvent, SystemTrayMenu, SystemTrayMenuItem, CustomMenuItem, GlobalWindowEvent};
@@ -24,29 +28,85 @@ pub struct BranchInfo {
     pub name: String,
     pub is_current: bool,
     pub is_remote: bool,
+    pub worktree: Option<String>, // 已在其他工作树中检出时为该工作树路径
+}
+
+// 文件变更类型，序列化为小写字符串
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileStatus {
+    Added,
+    Modified,
+    Deleted,
+    Renamed,
+    Copied,
+    TypeChange, // 文件类型改变，如普通文件与符号链接互换
+    Unknown,
+}
+
+impl FileStatus {
+    fn from_delta(delta: git2::Delta) -> Self {
+        match delta {
+            git2::Delta::Added | git2::Delta::Untracked => FileStatus::Added,
+            git2::Delta::Modified => FileStatus::Modified,
+            git2::Delta::Deleted => FileStatus::Deleted,
+            git2::Delta::Renamed => FileStatus::Renamed,
+            git2::Delta::Copied => FileStatus::Copied,
+            git2::Delta::Typechange => FileStatus::TypeChange,
+            _ => FileStatus::Unknown,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FileChange {
     pub path: String,
-    pub status: String, // "added", "modified", "deleted", "renamed"
+    pub old_path: Option<String>, // 重命名 / 复制前的路径
+    pub status: FileStatus,
     pub additions: i32,
     pub deletions: i32,
 }
 
+// 冲突类型，对应 git status 的 both modified / deleted by us 等
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictKind {
+    BothModified,
+    BothAdded,
+    BothDeleted,
+    AddedByUs,
+    AddedByThem,
+    DeletedByUs,
+    DeletedByThem,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConflictedFile {
+    pub path: String,
+    pub kind: ConflictKind,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WorkspaceStatus {
     pub staged_files: Vec<FileChange>,
     pub unstaged_files: Vec<FileChange>,
     pub untracked_files: Vec<String>,
+    pub conflicted_files: Vec<ConflictedFile>,
+    pub ignored_files: Vec<String>, // 仅在 include_ignored 时填充，被忽略的目录以 / 结尾
+    pub submodules: Vec<SubmoduleChange>,
+    pub lfs_files: Vec<String>, // 由 Git LFS 管理的变更文件
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct StashInfo {
     pub id: String,
+    pub index: usize,
+    pub selector: String,      // stash@{n}
     pub message: String,
-    pub timestamp: String,
-    pub branch: String,
+    pub timestamp: String,     // RFC 3339
+    pub branch: String,        // 创建贮藏时所在的分支
+    pub base_commit: String,
+    pub file_count: usize,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -80,26 +140,20 @@ pub struct RepoInfo {
     pub commits: Vec<CommitInfo>,
     pub ahead: u32,   // 本地比远端超前的提交数（待推送）
     pub behind: u32,  // 本地比远端落后的提交数（待拉取）
-    pub remote_url: Option<String>, // 远程仓库URL
+    pub remote_url: Option<String>, // 远程仓库URL（当前分支使用的远程）
+    pub remote_name: Option<String>,
+    pub remotes: Vec<RemoteInfo>,
+    pub is_worktree: bool,          // 当前路径是否为关联工作树
+    pub worktrees: Vec<WorktreeInfo>,
 }
 
-// 判断某路径是否在 HEAD（上一次提交）中被追踪
-fn path_tracked_in_head(repo: &Repository, file_path: &str) -> bool {
-    if let Ok(head) = repo.head() {
-        if let Ok(tree) = head.peel_to_tree() {
-            if let Ok(path) = std::path::Path::new(file_path).strip_prefix("./") {
-                // normalize leading ./ if any
-                if tree.get_path(path).is_ok() {
-                    return true;
-                }
-            }
-            // try original path
-            if tree.get_path(Path::new(file_path)).is_ok() {
-                return true;
-            }
-        }
-    }
-    false
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RemoteInfo {
+    pub name: String,
+    pub url: Option<String>,
+    pub push_url: Option<String>,   // 单独配置的推送地址
+    pub fetch_refspecs: Vec<String>,
+    pub push_refspecs: Vec<String>,
 }
 
 // 获取最近打开的仓库列表
@@ -123,6 +177,10 @@ async fn get_recent_repos() -> Result<Vec<RecentRepo>, String> {
 // 保存最近打开的仓库
 #[tauri::command]
 async fn save_recent_repo(path: String) -> Result<(), String> {
+    add_recent_repo(path)
+}
+
+fn add_recent_repo(path: String) -> Result<(), String> {
     let config_dir = get_config_dir();
     fs::create_dir_all(&config_dir)
         .map_err(|e| format!("Failed to create config directory: {}", e))?;
@@ -181,6 +239,10 @@ fn get_config_dir() -> std::path::PathBuf {
 // 获取代理配置
 #[tauri::command]
 async fn get_proxy_config() -> Result<(ProxyConfig, bool), String> {
+    load_proxy_config()
+}
+
+fn load_proxy_config() -> Result<(ProxyConfig, bool), String> {
     let config_dir = get_config_dir();
     let config_file = config_dir.join("proxy_config.json");
     
@@ -630,21 +692,125 @@ async fn open_external_url(url: String) -> Result<(), String> {
     Ok(())
 }
 
-// 打开 Git 仓库
-#[tauri::command]
-async fn open_repository(path: String) -> Result<RepoInfo, String> {
-    let repo = Repository::open(&path)
-        .map_err(|e| format!("无法打开仓库：{}", e))?;
-    
-    // 清理错误的代理配置（https://...）
-    if let Ok(cleaned_keys) = cleanup_invalid_proxy_config(&repo) {
-        if !cleaned_keys.is_empty() {
-            log_message("INFO", &format!("已清理错误的代理配置: {:?}", cleaned_keys));
+// 已打开仓库的缓存（Tauri 托管状态）：按路径复用 Repository，避免每个命令重新读取配置和索引。
+// .git 下的 HEAD / index / config / packed-refs 变化时重新打开。
+#[derive(Clone, Default)]
+pub struct RepoCache {
+    repos: Arc<Mutex<HashMap<PathBuf, CachedRepo>>>,
+}
+
+struct CachedRepo {
+    repo: Arc<Mutex<Repository>>,
+    fingerprint: Vec<Option<(SystemTime, u64)>>,
+    last_used: Instant,
+}
+
+// 最多缓存的仓库数量，超出时淘汰最久未使用的
+const MAX_CACHED_REPOS: usize = 8;
+
+fn repo_fingerprint(repo: &Repository) -> Vec<Option<(SystemTime, u64)>> {
+    let git_dir = repo.path();
+    let common_dir = repo.commondir();
+    [
+        git_dir.join("HEAD"),
+        git_dir.join("index"),
+        common_dir.join("config"),
+        common_dir.join("packed-refs"),
+    ]
+    .iter()
+    .map(|p| fs::metadata(p).ok().and_then(|m| Some((m.modified().ok()?, m.len()))))
+    .collect()
+}
+
+impl RepoCache {
+    fn get(&self, repo_path: &str) -> Result<Arc<Mutex<Repository>>, git2::Error> {
+        let key = fs::canonicalize(repo_path).unwrap_or_else(|_| PathBuf::from(repo_path));
+        // 只在查表和插入时持有表锁，检查指纹与打开仓库都在锁外进行，不阻塞其他仓库
+        let cached = self.repos.lock().unwrap_or_else(|e| e.into_inner())
+            .get(&key)
+            .map(|cached| (cached.repo.clone(), cached.fingerprint.clone()));
+        if let Some((repo, fingerprint)) = cached {
+            // 仓库正被其他命令使用时无法检查，直接复用
+            let fresh = match repo.try_lock() {
+                Ok(guard) => repo_fingerprint(&guard) == fingerprint,
+                Err(_) => true,
+            };
+            if fresh {
+                if let Some(cached) = self.repos.lock().unwrap_or_else(|e| e.into_inner()).get_mut(&key) {
+                    cached.last_used = Instant::now();
+                }
+                return Ok(repo);
+            }
+        }
+
+        let repo = Repository::open(repo_path)?;
+        let fingerprint = repo_fingerprint(&repo);
+        let repo = Arc::new(Mutex::new(repo));
+        let mut repos = self.repos.lock().unwrap_or_else(|e| e.into_inner());
+        repos.insert(key, CachedRepo { repo: repo.clone(), fingerprint, last_used: Instant::now() });
+        if repos.len() > MAX_CACHED_REPOS {
+            if let Some(oldest) = repos.iter().min_by_key(|(_, c)| c.last_used).map(|(k, _)| k.clone()) {
+                repos.remove(&oldest);
+            }
         }
+        Ok(repo)
     }
-    
-    let repo_info = get_repository_info(&repo, &path)
-        .map_err(|e| format!("无法读取仓库信息：{}", e))?;
+
+    // 使用缓存的仓库执行 f；该仓库正被其他命令占用时另外打开一个，不排队等待
+    fn with<T>(&self, repo_path: &str, f: impl FnOnce(&mut Repository) -> Result<T, String>) -> Result<T, String> {
+        let shared = self.get(repo_path)
+            .map_err(|e| format!("Failed to open repository: {}", e))?;
+        let guard = shared.try_lock();
+        match guard {
+            Ok(mut repo) => f(&mut repo),
+            Err(_) => {
+                let mut repo = Repository::open(repo_path)
+                    .map_err(|e| format!("Failed to open repository: {}", e))?;
+                f(&mut repo)
+            }
+        }
+    }
+}
+
+// 在阻塞线程池中执行 git 操作，不占用异步运行时的工作线程
+async fn run_blocking<T, F>(f: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, String> + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("Background task failed: {}", e))?
+}
+
+// 在阻塞线程池中使用缓存的仓库执行 f
+async fn with_repo<T, F>(cache: &RepoCache, repo_path: String, f: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut Repository) -> Result<T, String> + Send + 'static,
+{
+    let cache = cache.clone();
+    run_blocking(move || cache.with(&repo_path, f)).await
+}
+
+// 打开 Git 仓库
+#[tauri::command]
+async fn open_repository(path: String, cache: tauri::State<'_, RepoCache>) -> Result<RepoInfo, String> {
+    let cache = cache.inner().clone();
+    let repo_path = path.clone();
+    let repo_info = run_blocking(move || {
+        cache.with(&repo_path, |repo| {
+            // 清理错误的代理配置（https://...）
+            if let Ok(cleaned_keys) = cleanup_invalid_proxy_config(repo) {
+                if !cleaned_keys.is_empty() {
+                    log_message("INFO", &format!("已清理错误的代理配置: {:?}", cleaned_keys));
+                }
+            }
+            
+            get_repository_info(repo, &repo_path)
+                .map_err(|e| format!("无法读取仓库信息：{}", e))
+        })
+    }).await?;
     
     // 保存到最近打开的仓库列表
     if let Err(e) = save_recent_repo(path).await {
@@ -656,10 +822,21 @@ async fn open_repository(path: String) -> Result<RepoInfo, String> {
 
 // 获取仓库信息
 fn get_repository_info(repo: &Repository, path: &str) -> Result<RepoInfo> {
-    // 获取当前分支
-    let head = repo.head().map_err(|e| anyhow::anyhow!("Failed to get HEAD: {}", e))?;
-    let current_branch = head.shorthand().unwrap_or("detached").to_string();
+    // 获取当前分支（新初始化、尚无提交的仓库 HEAD 指向未出生的分支）
+    let unborn = matches!(repo.head(), Err(ref e) if e.code() == git2::ErrorCode::UnbornBranch);
+    let current_branch = if unborn {
+        repo.find_reference("HEAD")
+            .ok()
+            .and_then(|r| r.symbolic_target().map(|t| t.trim_start_matches("refs/heads/").to_string()))
+            .ok_or_else(|| anyhow::anyhow!("Failed to get HEAD"))?
+    } else {
+        let head = repo.head().map_err(|e| anyhow::anyhow!("Failed to get HEAD: {}", e))?;
+        head.shorthand().unwrap_or("detached").to_string()
+    };
     
+    // 工作树（用于标记在其他工作树中检出的分支）
+    let worktrees = list_worktree_infos(repo).unwrap_or_default();
+
     // 获取分支列表
     let mut branches = Vec::new();
     let branch_iter = repo.branches(Some(git2::BranchType::Local))
@@ -675,16 +852,20 @@ fn get_repository_info(repo: &Repository, path: &str) -> Result<RepoInfo> {
             .to_string();
         
         let is_current = branch_name == current_branch;
+        let worktree = worktrees.iter()
+            .find(|w| !w.is_current && !w.prunable && w.branch.as_deref() == Some(branch_name.as_str()))
+            .map(|w| w.path.clone());
         
         branches.push(BranchInfo {
             name: branch_name,
             is_current,
             is_remote: false,
+            worktree,
         });
     }
     
     // 获取提交历史
-    let commits = get_commit_history(repo)?;
+    let commits = if unborn { Vec::new() } else { get_commit_history(repo)? };
 
     // 计算当前分支与上游的 ahead/behind
     let mut ahead: u32 = 0;
@@ -693,7 +874,7 @@ fn get_repository_info(repo: &Repository, path: &str) -> Result<RepoInfo> {
     if let Ok(branch) = repo.find_branch(&current_branch, git2::BranchType::Local) {
         // 本地提交
         let local_oid_opt = branch.get().target();
-        // 上游跟踪分支（origin/<branch>）
+        // 上游跟踪分支（<remote>/<branch>）
         let upstream_oid_opt = branch.upstream().ok().and_then(|up| up.get().target());
         if let (Some(local_oid), Some(upstream_oid)) = (local_oid_opt, upstream_oid_opt) {
             if let Ok((a, b)) = repo.graph_ahead_behind(local_oid, upstream_oid) {
@@ -704,9 +885,11 @@ fn get_repository_info(repo: &Repository, path: &str) -> Result<RepoInfo> {
     }
     
     // 获取远程仓库URL
-    let remote_url = repo.find_remote("origin")
-        .ok()
-        .and_then(|remote| remote.url().map(|url| url.to_string()));
+    let remotes = list_remote_infos(repo);
+    let remote_name = resolve_remote_name(repo, None);
+    let remote_url = remotes.iter()
+        .find(|r| r.name == remote_name)
+        .and_then(|r| r.url.clone());
     
     Ok(RepoInfo {
         path: path.to_string(),
@@ -716,16 +899,25 @@ fn get_repository_info(repo: &Repository, path: &str) -> Result<RepoInfo> {
         ahead,
         behind,
         remote_url,
+        remote_name: remotes.iter().any(|r| r.name == remote_name).then_some(remote_name),
+        remotes,
+        is_worktree: repo.is_worktree(),
+        worktrees,
     })
 }
 
 // 获取提交历史（初始加载，只获取前50个）
 fn get_commit_history(repo: &Repository) -> Result<Vec<CommitInfo>> {
-    get_commit_history_paginated(repo, Some(50), Some(0))
+    get_commit_history_paginated(repo, Some(50), Some(0), None)
 }
 
 // 获取分页提交历史
-fn get_commit_history_paginated(repo: &Repository, limit: Option<usize>, offset: Option<usize>) -> Result<Vec<CommitInfo>> {
+fn get_commit_history_paginated(
+    repo: &Repository,
+    limit: Option<usize>,
+    offset: Option<usize>,
+    operation: Option<&RunningOperation>,
+) -> Result<Vec<CommitInfo>> {
     let mut revwalk = repo.revwalk()
         .map_err(|e| anyhow::anyhow!("Failed to create revwalk: {}", e))?;
     
@@ -739,6 +931,9 @@ fn get_commit_history_paginated(repo: &Repository, limit: Option<usize>, offset:
     let mut skipped = 0;
     
     for oid_result in revwalk {
+        if operation.is_some_and(|o| o.is_cancelled()) {
+            return Err(anyhow::anyhow!(OPERATION_CANCELLED));
+        }
         if skipped < offset {
             skipped += 1;
             continue;
@@ -778,18 +973,30 @@ fn get_commit_history_paginated(repo: &Repository, limit: Option<usize>, offset:
 
 // 获取分页提交历史
 #[tauri::command]
-async fn get_commits_paginated(repo_path: String, limit: Option<usize>, offset: Option<usize>) -> Result<Vec<CommitInfo>, String> {
-    let repo = Repository::open(&repo_path)
-        .map_err(|e| format!("Failed to open repository: {}", e))?;
-    
-    let commits = get_commit_history_paginated(&repo, limit, offset)
-        .map_err(|e| format!("Failed to get commit history: {}", e))?;
-    
-    Ok(commits)
+async fn get_commits_paginated(
+    repo_path: String,
+    limit: Option<usize>,
+    offset: Option<usize>,
+    operation_id: Option<String>,
+    cache: tauri::State<'_, RepoCache>,
+) -> Result<Vec<CommitInfo>, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        let operation = RunningOperation::begin("history", operation_id);
+    
+        let commits = get_commit_history_paginated(repo, limit, offset, Some(&operation))
+            .map_err(|e| operation.or_cancelled(format!("Failed to get commit history: {}", e)))?;
+    
+        Ok(commits)
+    }).await
 }
 
 // 全仓库历史搜索：按关键词匹配 message / author / short_id，返回最多 limit 条
-fn get_commit_history_search(repo: &Repository, query: &str, limit: usize) -> Result<Vec<CommitInfo>> {
+fn get_commit_history_search(
+    repo: &Repository,
+    query: &str,
+    limit: usize,
+    operation: Option<&RunningOperation>,
+) -> Result<Vec<CommitInfo>> {
     let query_lower = query.to_lowercase();
     if query_lower.trim().is_empty() {
         return Ok(Vec::new());
@@ -800,6 +1007,9 @@ fn get_commit_history_search(repo: &Repository, query: &str, limit: usize) -> Re
         .map_err(|e| anyhow::anyhow!("Failed to push HEAD: {}", e))?;
     let mut commits = Vec::new();
     for oid_result in revwalk {
+        if operation.is_some_and(|o| o.is_cancelled()) {
+            return Err(anyhow::anyhow!(OPERATION_CANCELLED));
+        }
         if commits.len() >= limit {
             break;
         }
@@ -835,132 +1045,142 @@ fn get_commit_history_search(repo: &Repository, query: &str, limit: usize) -> Re
 }
 
 #[tauri::command]
-async fn search_commits(repo_path: String, query: String, limit: Option<usize>) -> Result<Vec<CommitInfo>, String> {
-    let limit = limit.unwrap_or(500);
-    let repo = Repository::open(&repo_path)
-        .map_err(|e| format!("Failed to open repository: {}", e))?;
-    let commits = get_commit_history_search(&repo, query.trim(), limit)
-        .map_err(|e| format!("Search failed: {}", e))?;
-    Ok(commits)
+async fn search_commits(
+    repo_path: String,
+    query: String,
+    limit: Option<usize>,
+    operation_id: Option<String>,
+    cache: tauri::State<'_, RepoCache>,
+) -> Result<Vec<CommitInfo>, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        let operation = RunningOperation::begin("search", operation_id);
+        let limit = limit.unwrap_or(500);
+        let commits = get_commit_history_search(repo, query.trim(), limit, Some(&operation))
+            .map_err(|e| operation.or_cancelled(format!("Search failed: {}", e)))?;
+        Ok(commits)
+    }).await
 }
 
 // 切换分支
 #[tauri::command]
-async fn checkout_branch(repo_path: String, branch_name: String) -> Result<String, String> {
-    let repo = Repository::open(&repo_path)
-        .map_err(|e| format!("无法打开仓库: {}", e))?;
+async fn checkout_branch(repo_path: String, branch_name: String, cache: tauri::State<'_, RepoCache>) -> Result<String, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        let (object, reference) = repo.revparse_ext(&branch_name)
+            .map_err(|e| {
+                let msg = e.message();
+                if msg.contains("reference") || msg.contains("unknown") {
+                    format!("未找到分支「{}」，请检查分支名或先拉取远程分支", branch_name)
+                } else {
+                    format!("无法解析分支: {}", msg)
+                }
+            })?;
 
-    let (object, reference) = repo.revparse_ext(&branch_name)
-        .map_err(|e| {
+        // 同一分支不能同时在两个工作树中检出
+        if let Some(branch) = reference.as_ref().filter(|r| r.is_branch()).and_then(|r| r.shorthand()) {
+            if let Some(other) = branch_checked_out_elsewhere(repo, branch) {
+                return Err(format!("分支「{}」已在工作树 {} 中检出，请先在该工作树中切换到其他分支或打开该工作树", branch, other));
+            }
+        }
+
+        if let Err(e) = repo.checkout_tree(&object, None) {
             let msg = e.message();
-            if msg.contains("reference") || msg.contains("unknown") {
-                format!("未找到分支「{}」，请检查分支名或先拉取远程分支", branch_name)
+            return Err(if msg.contains("overwrite") || msg.contains("would be overwritten") || msg.contains("conflict") {
+                "有未提交的修改，无法切换分支。请先提交或暂存后再切换。".to_string()
             } else {
-                format!("无法解析分支: {}", msg)
-            }
-        })?;
+                format!("检出失败: {}", msg)
+            });
+        }
 
-    if let Err(e) = repo.checkout_tree(&object, None) {
-        let msg = e.message();
-        return Err(if msg.contains("overwrite") || msg.contains("would be overwritten") || msg.contains("conflict") {
-            "有未提交的修改，无法切换分支。请先提交或暂存后再切换。".to_string()
+        if let Some(reference) = reference {
+            let ref_name = reference.name().unwrap_or("refs/heads/unknown");
+            repo.set_head(ref_name)
+                .map_err(|e| format!("设置当前分支失败: {}", e.message()))?;
         } else {
-            format!("检出失败: {}", msg)
-        });
-    }
-
-    if let Some(reference) = reference {
-        let ref_name = reference.name().unwrap_or("refs/heads/unknown");
-        repo.set_head(ref_name)
-            .map_err(|e| format!("设置当前分支失败: {}", e.message()))?;
-    } else {
-        repo.set_head_detached(object.id())
-            .map_err(|e| format!("设置分离头指针失败: {}", e.message()))?;
-    }
+            repo.set_head_detached(object.id())
+                .map_err(|e| format!("设置分离头指针失败: {}", e.message()))?;
+        }
 
-    Ok(format!("已切换到 {}", branch_name))
+        Ok(format!("已切换到 {}", branch_name))
+    }).await
 }
 
 // 获取提交的文件列表
 #[tauri::command]
-async fn get_commit_files(repo_path: String, commit_id: String) -> Result<Vec<FileChange>, String> {
-    let repo = Repository::open(&repo_path)
-        .map_err(|e| format!("Failed to open repository: {}", e))?;
-    
-    let oid = Oid::from_str(&commit_id)
-        .map_err(|e| format!("Invalid commit ID: {}", e))?;
+async fn get_commit_files(repo_path: String, commit_id: String, cache: tauri::State<'_, RepoCache>) -> Result<Vec<FileChange>, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        let oid = Oid::from_str(&commit_id)
+            .map_err(|e| format!("Invalid commit ID: {}", e))?;
     
-    let commit = repo.find_commit(oid)
-        .map_err(|e| format!("Failed to find commit: {}", e))?;
+        let commit = repo.find_commit(oid)
+            .map_err(|e| format!("Failed to find commit: {}", e))?;
     
-    let tree = commit.tree()
-        .map_err(|e| format!("Failed to get commit tree: {}", e))?;
+        let tree = commit.tree()
+            .map_err(|e| format!("Failed to get commit tree: {}", e))?;
     
-    let parent = if commit.parent_count() > 0 {
-        Some(commit.parent(0)
-            .map_err(|e| format!("Failed to get parent commit: {}", e))?
-            .tree()
-            .map_err(|e| format!("Failed to get parent tree: {}", e))?)
-    } else {
-        None
-    };
+        let parent = if commit.parent_count() > 0 {
+            Some(commit.parent(0)
+                .map_err(|e| format!("Failed to get parent commit: {}", e))?
+                .tree()
+                .map_err(|e| format!("Failed to get parent tree: {}", e))?)
+        } else {
+            None
+        };
     
-    let diff = repo.diff_tree_to_tree(parent.as_ref(), Some(&tree), None)
-        .map_err(|e| format!("Failed to create diff: {}", e))?;
+        let diff = repo.diff_tree_to_tree(parent.as_ref(), Some(&tree), None)
+            .map_err(|e| format!("Failed to create diff: {}", e))?;
     
-    let mut files = Vec::new();
+        let mut files = Vec::new();
     
-    diff.foreach(
-        &mut |delta, _progress| {
-            let old_path = delta.old_file().path().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
-            let new_path = delta.new_file().path().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
+        diff.foreach(
+            &mut |delta, _progress| {
+                let old_path = delta.old_file().path().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
+                let new_path = delta.new_file().path().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
             
-            let status = match delta.status() {
-                git2::Delta::Added => "added",
-                git2::Delta::Modified => "modified", 
-                git2::Delta::Deleted => "deleted",
-                git2::Delta::Renamed => "renamed",
-                git2::Delta::Copied => "copied",
-                _ => "unknown",
-            };
+                let status = FileStatus::from_delta(delta.status());
             
-            // 获取正确的文件路径
-            let file_path = if new_path.is_empty() { old_path } else { new_path };
+                // 获取正确的文件路径
+                let moved = matches!(status, FileStatus::Renamed | FileStatus::Copied);
+                let (file_path, previous_path) = if new_path.is_empty() {
+                    (old_path, None)
+                } else {
+                    (new_path, Some(old_path).filter(|_| moved))
+                };
             
-            // 简化的统计方法 - 先确保文件被检测到
-            let additions = match status {
-                "added" => 1, // 新增文件至少算1行
-                "deleted" => 0,
-                _ => 1, // 其他情况先算1行
-            };
+                // 简化的统计方法 - 先确保文件被检测到
+                let additions = match status {
+                    FileStatus::Added => 1, // 新增文件至少算1行
+                    FileStatus::Deleted => 0,
+                    _ => 1, // 其他情况先算1行
+                };
             
-            let deletions = match status {
-                "deleted" => 1, // 删除文件至少算1行
-                "added" => 0,
-                _ => 0, // 其他情况先算0行
-            };
+                let deletions = match status {
+                    FileStatus::Deleted => 1, // 删除文件至少算1行
+                    _ => 0, // 其他情况先算0行
+                };
             
-            files.push(FileChange {
-                path: file_path,
-                status: status.to_string(),
-                additions,
-                deletions,
-            });
+                files.push(FileChange {
+                    path: file_path,
+                    old_path: previous_path,
+                    status,
+                    additions,
+                    deletions,
+                });
             
-            true
-        },
-        None,
-        None,
-        None,
-    ).map_err(|e| format!("Failed to iterate diff: {}", e))?;
+                true
+            },
+            None,
+            None,
+            None,
+        ).map_err(|e| format!("Failed to iterate diff: {}", e))?;
     
-    Ok(files)
+        Ok(files)
+    }).await
 }
 
 // 获取单个文件的差异
 #[tauri::command]
-async fn get_single_file_diff(repo_path: String, commit_id: String, file_path: String) -> Result<String, String> {
-    use git2::{Repository, Oid, DiffOptions, DiffFormat};
+async fn get_single_file_diff(repo_path: String, commit_id: String, file_path: String, cache: tauri::State<'_, RepoCache>) -> Result<String, String> {
+    use git2::{Oid, DiffOptions, DiffFormat};
 use std::fs::{OpenOptions, create_dir_all};
 use std::io::Write;
 use std::path::Path;
@@ -977,1974 +1197,6753 @@ fn append_repo_log(repo_path: &str, message: &str) {
     }
 }
 
-    let repo = Repository::open(&repo_path)
-        .map_err(|e| format!("Failed to open repository: {}", e))?;
+    with_repo(&cache, repo_path.clone(), move |repo| {
+        let oid = Oid::from_str(&commit_id)
+            .map_err(|e| format!("Invalid commit ID: {}", e))?;
 
-    let oid = Oid::from_str(&commit_id)
-        .map_err(|e| format!("Invalid commit ID: {}", e))?;
+        let commit = repo.find_commit(oid)
+            .map_err(|e| format!("Failed to find commit: {}", e))?;
 
-    let commit = repo.find_commit(oid)
-        .map_err(|e| format!("Failed to find commit: {}", e))?;
+        let tree = commit.tree()
+            .map_err(|e| format!("Failed to get commit tree: {}", e))?;
 
-    let tree = commit.tree()
-        .map_err(|e| format!("Failed to get commit tree: {}", e))?;
+        let parent = if commit.parent_count() > 0 {
+            Some(commit.parent(0)
+                .map_err(|e| format!("Failed to get parent commit: {}", e))?
+                .tree()
+                .map_err(|e| format!("Failed to get parent tree: {}", e))?)
+        } else {
+            None
+        };
 
-    let parent = if commit.parent_count() > 0 {
-        Some(commit.parent(0)
-            .map_err(|e| format!("Failed to get parent commit: {}", e))?
-            .tree()
-            .map_err(|e| format!("Failed to get parent tree: {}", e))?)
-    } else {
-        None
-    };
+        // 仅对目标文件生成差异，并输出完整 Patch（包含 diff header/hunk/行前缀）
+        let mut opts = DiffOptions::new();
+        opts.pathspec(&file_path);
+        let diff = repo
+            .diff_tree_to_tree(parent.as_ref(), Some(&tree), Some(&mut opts))
+            .map_err(|e| format!("Failed to create diff: {}", e))?;
 
-    // 仅对目标文件生成差异，并输出完整 Patch（包含 diff header/hunk/行前缀）
-    let mut opts = DiffOptions::new();
-    opts.pathspec(&file_path);
-    let diff = repo
-        .diff_tree_to_tree(parent.as_ref(), Some(&tree), Some(&mut opts))
-        .map_err(|e| format!("Failed to create diff: {}", e))?;
+        // LFS 文件显示为对象 oid 与大小
+        if let Some(text) = diff.deltas().next().and_then(|delta| lfs_delta_text(repo, &delta)) {
+            return Ok(text);
+        }
 
-    let mut text = String::new();
-    diff.print(DiffFormat::Patch, |_delta, _hunk, line| {
-        text.push_str(&format!("{}\n", std::str::from_utf8(line.content()).unwrap_or("")));
-        true
-    }).map_err(|e| format!("Failed to print diff: {}", e))?;
-    // 调试：打印 git2 生成的 patch 概要
-    {
-        let preview: String = text.chars().take(400).collect();
-        let lines_total = text.lines().count();
-        let plus_cnt = text.lines().filter(|l| l.starts_with('+')).count();
-        let minus_cnt = text.lines().filter(|l| l.starts_with('-')).count();
-        let space_cnt = text.lines().filter(|l| l.starts_with(' ')).count();
-        let at_cnt = text.lines().filter(|l| l.starts_with("@@")).count();
-        let msg = format!(
-            "[git2 to_buf] bytes: {}, lines: {}, +: {}, -: {}, space: {}, @@: {}, preview: {}",
-            text.len(), lines_total, plus_cnt, minus_cnt, space_cnt, at_cnt, preview
-        );
-        append_repo_log(&repo_path, &msg);
-    }
-
-    // Fallback: 如果含有 hunk 但几乎没有 +/- 行，尝试调用 git 原生命令生成统一补丁
-    let plus = text.lines().filter(|l| l.starts_with('+')).count();
-    let minus = text.lines().filter(|l| l.starts_with('-')).count();
-    let has_hunk = text.lines().any(|l| l.starts_with("@@"));
-    if has_hunk && (plus + minus) < 3 {
-        if commit.parent_count() > 0 {
-            use std::process::Command;
-            let parent_id = commit.parent_id(0).ok();
-            if let Some(pid) = parent_id {
-                let output = Command::new("git")
-                    .arg("-C").arg(&repo_path)
-                    .arg("diff")
-                    .arg(format!("{}", pid))
-                    .arg(format!("{}", commit_id))
-                    .arg("--")
-                    .arg(&file_path)
-                    .output();
-                if let Ok(out) = output {
-                    if out.status.success() {
-                        let t = String::from_utf8_lossy(&out.stdout).to_string();
-                        if !t.trim().is_empty() {
-                            // 调试：打印 git 原生命令生成的文本概要
-                            let lines_total = t.lines().count();
-                            let plus_cnt = t.lines().filter(|l| l.starts_with('+')).count();
-                            let minus_cnt = t.lines().filter(|l| l.starts_with('-')).count();
-                            let space_cnt = t.lines().filter(|l| l.starts_with(' ')).count();
-                            let at_cnt = t.lines().filter(|l| l.starts_with("@@")).count();
-                            let preview: String = t.chars().take(400).collect();
-                            let msg = format!(
-                                "[git diff fallback] bytes: {}, lines: {}, +: {}, -: {}, space: {}, @@: {}, preview: {}",
-                                t.len(), lines_total, plus_cnt, minus_cnt, space_cnt, at_cnt, preview
-                            );
-                            append_repo_log(&repo_path, &msg);
-                            text = t;
+        let mut text = String::new();
+        diff.print(DiffFormat::Patch, |_delta, _hunk, line| {
+            text.push_str(&format!("{}\n", std::str::from_utf8(line.content()).unwrap_or("")));
+            true
+        }).map_err(|e| format!("Failed to print diff: {}", e))?;
+        // 调试：打印 git2 生成的 patch 概要
+        {
+            let preview: String = text.chars().take(400).collect();
+            let lines_total = text.lines().count();
+            let plus_cnt = text.lines().filter(|l| l.starts_with('+')).count();
+            let minus_cnt = text.lines().filter(|l| l.starts_with('-')).count();
+            let space_cnt = text.lines().filter(|l| l.starts_with(' ')).count();
+            let at_cnt = text.lines().filter(|l| l.starts_with("@@")).count();
+            let msg = format!(
+                "[git2 to_buf] bytes: {}, lines: {}, +: {}, -: {}, space: {}, @@: {}, preview: {}",
+                text.len(), lines_total, plus_cnt, minus_cnt, space_cnt, at_cnt, preview
+            );
+            append_repo_log(&repo_path, &msg);
+        }
+
+        // Fallback: 如果含有 hunk 但几乎没有 +/- 行，尝试调用 git 原生命令生成统一补丁
+        let plus = text.lines().filter(|l| l.starts_with('+')).count();
+        let minus = text.lines().filter(|l| l.starts_with('-')).count();
+        let has_hunk = text.lines().any(|l| l.starts_with("@@"));
+        if has_hunk && (plus + minus) < 3 {
+            if commit.parent_count() > 0 {
+                use std::process::Command;
+                let parent_id = commit.parent_id(0).ok();
+                if let Some(pid) = parent_id {
+                    let output = Command::new("git")
+                        .arg("-C").arg(&repo_path)
+                        .arg("diff")
+                        .arg(format!("{}", pid))
+                        .arg(format!("{}", commit_id))
+                        .arg("--")
+                        .arg(&file_path)
+                        .output();
+                    if let Ok(out) = output {
+                        if out.status.success() {
+                            let t = String::from_utf8_lossy(&out.stdout).to_string();
+                            if !t.trim().is_empty() {
+                                // 调试：打印 git 原生命令生成的文本概要
+                                let lines_total = t.lines().count();
+                                let plus_cnt = t.lines().filter(|l| l.starts_with('+')).count();
+                                let minus_cnt = t.lines().filter(|l| l.starts_with('-')).count();
+                                let space_cnt = t.lines().filter(|l| l.starts_with(' ')).count();
+                                let at_cnt = t.lines().filter(|l| l.starts_with("@@")).count();
+                                let preview: String = t.chars().take(400).collect();
+                                let msg = format!(
+                                    "[git diff fallback] bytes: {}, lines: {}, +: {}, -: {}, space: {}, @@: {}, preview: {}",
+                                    t.len(), lines_total, plus_cnt, minus_cnt, space_cnt, at_cnt, preview
+                                );
+                                append_repo_log(&repo_path, &msg);
+                                text = t;
+                            }
                         }
                     }
                 }
             }
         }
-    }
 
-    Ok(text)
+        Ok(text)
+    }).await
 }
 
 // 获取文件差异（保持向后兼容）
 #[tauri::command]
-async fn get_file_diff(repo_path: String, commit_id: String) -> Result<String, String> {
-    let repo = Repository::open(&repo_path)
-        .map_err(|e| format!("Failed to open repository: {}", e))?;
-    
-    let oid = Oid::from_str(&commit_id)
-        .map_err(|e| format!("Invalid commit ID: {}", e))?;
+async fn get_file_diff(repo_path: String, commit_id: String, cache: tauri::State<'_, RepoCache>) -> Result<String, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        let oid = Oid::from_str(&commit_id)
+            .map_err(|e| format!("Invalid commit ID: {}", e))?;
     
-    let commit = repo.find_commit(oid)
-        .map_err(|e| format!("Failed to find commit: {}", e))?;
+        let commit = repo.find_commit(oid)
+            .map_err(|e| format!("Failed to find commit: {}", e))?;
     
-    let tree = commit.tree()
-        .map_err(|e| format!("Failed to get commit tree: {}", e))?;
+        let tree = commit.tree()
+            .map_err(|e| format!("Failed to get commit tree: {}", e))?;
     
-    let parent = if commit.parent_count() > 0 {
-        Some(commit.parent(0)
-            .map_err(|e| format!("Failed to get parent commit: {}", e))?
-            .tree()
-            .map_err(|e| format!("Failed to get parent tree: {}", e))?)
-    } else {
-        None
-    };
+        let parent = if commit.parent_count() > 0 {
+            Some(commit.parent(0)
+                .map_err(|e| format!("Failed to get parent commit: {}", e))?
+                .tree()
+                .map_err(|e| format!("Failed to get parent tree: {}", e))?)
+        } else {
+            None
+        };
     
-    let diff = repo.diff_tree_to_tree(parent.as_ref(), Some(&tree), None)
-        .map_err(|e| format!("Failed to create diff: {}", e))?;
+        let diff = repo.diff_tree_to_tree(parent.as_ref(), Some(&tree), None)
+            .map_err(|e| format!("Failed to create diff: {}", e))?;
     
-    let mut diff_text = String::new();
-    diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
-        diff_text.push_str(&format!("{}\n", std::str::from_utf8(line.content()).unwrap_or("")));
-        true
-    }).map_err(|e| format!("Failed to print diff: {}", e))?;
+        let lfs_texts = lfs_diff_texts(repo, &diff);
+        let mut diff_text = String::new();
+        diff.print(git2::DiffFormat::Patch, |delta, _hunk, line| {
+            let current_file = delta.new_file().path()
+                .or_else(|| delta.old_file().path())
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default();
+            if lfs_texts.contains_key(&current_file) {
+                return true;
+            }
+            diff_text.push_str(&format!("{}\n", std::str::from_utf8(line.content()).unwrap_or("")));
+            true
+        }).map_err(|e| format!("Failed to print diff: {}", e))?;
+        let mut lfs_texts: Vec<_> = lfs_texts.into_values().collect();
+        lfs_texts.sort();
+        diff_text.extend(lfs_texts);
     
-    Ok(diff_text)
+        Ok(diff_text)
+    }).await
+}
+
+// 工作区状态查询选项
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkspaceStatusOptions {
+    pub pathspecs: Vec<String>,        // 只统计匹配的路径，为空时统计整个工作区
+    pub collapse_untracked_dirs: bool, // 未跟踪目录只报告目录本身（以 / 结尾），不展开其中的文件
+    pub include_ignored: bool,         // 同时列出被忽略的文件
+    pub detect_workdir_renames: bool,  // 将未跟踪文件与已删除文件配对为工作区重命名；大仓库中很慢，git status 默认也不做
+    pub scan_submodule_content: bool,  // 检查子模块内部的修改与未跟踪文件；默认只比较子模块指向的提交
 }
 
 // 获取工作区状态
 #[tauri::command]
-async fn get_workspace_status(repo_path: String) -> Result<WorkspaceStatus, String> {
-    let repo = Repository::open(&repo_path)
-        .map_err(|e| format!("Failed to open repository: {}", e))?;
-    
+async fn get_workspace_status(repo_path: String, options: Option<WorkspaceStatusOptions>, cache: tauri::State<'_, RepoCache>) -> Result<WorkspaceStatus, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        collect_workspace_status(repo, &options.unwrap_or_default())
+    }).await
+}
+
+// 一条状态记录，重命名时带上对应一侧的原路径
+struct StatusEntry {
+    path: String,
+    status: git2::Status,
+    index_old_path: Option<String>,   // HEAD → 索引 的重命名
+    workdir_old_path: Option<String>, // 索引 → 工作区 的重命名
+}
+
+fn collect_workspace_status(repo: &Repository, options: &WorkspaceStatusOptions) -> Result<WorkspaceStatus, String> {
     let mut staged_files = Vec::new();
     let mut unstaged_files = Vec::new();
     let mut untracked_files = Vec::new();
-    
-    
-    // 使用 git status 来获取更准确的状态信息
-    let mut status_options = git2::StatusOptions::new();
-    status_options.include_untracked(true);
-    status_options.include_ignored(false);
-    status_options.include_unmodified(false);
-    
-    let statuses = repo.statuses(Some(&mut status_options))
-        .map_err(|e| format!("Failed to get statuses: {}", e))?;
-    
-    for entry in statuses.iter() {
-        let file_path = entry.path().unwrap_or("").to_string();
-        let status = entry.status();
-        
-        // 优先处理暂存状态，如果文件在暂存区，就不处理工作区状态
-        if status.contains(git2::Status::INDEX_NEW) {
-            staged_files.push(FileChange {
-                path: file_path.clone(),
-                status: "added".to_string(),
-                additions: 1,
-                deletions: 0,
-            });
-        } else if status.contains(git2::Status::INDEX_MODIFIED) {
-            staged_files.push(FileChange {
-                path: file_path.clone(),
-                status: "modified".to_string(),
-                additions: 1,
-                deletions: 0,
-            });
-        } else if status.contains(git2::Status::INDEX_DELETED) {
-            // 与 git status 保持一致：即便工作区有 WT_NEW，也要在暂存区显示 deleted
-            staged_files.push(FileChange {
-                path: file_path.clone(),
-                status: "deleted".to_string(),
-                additions: 0,
-                deletions: 1,
-            });
-        } else if status.contains(git2::Status::INDEX_RENAMED) {
-            staged_files.push(FileChange {
-                path: file_path.clone(),
-                status: "renamed".to_string(),
-                additions: 1,
-                deletions: 0,
-            });
-        }
-        
-        // 处理工作区状态（无论文件是否在暂存区）
-        if status.contains(git2::Status::WT_NEW) {
-            // 与 git status 对齐：若该路径在 HEAD 存在且索引为 deleted，则工作区提示应为 Untracked
-            if path_tracked_in_head(&repo, &file_path) && status.contains(git2::Status::INDEX_DELETED) {
-                if !untracked_files.contains(&file_path) {
-                    untracked_files.push(file_path);
-                }
-            } else if path_tracked_in_head(&repo, &file_path) {
-                // HEAD 有且索引未删除，才视为修改
-                if !unstaged_files.iter().any(|f: &FileChange| f.path == file_path) {
-                    unstaged_files.push(FileChange {
-                        path: file_path.clone(),
-                        status: "modified".to_string(),
-                        additions: 1,
-                        deletions: 0,
-                    });
-                }
-            } else if !untracked_files.contains(&file_path) {
-                untracked_files.push(file_path);
-            }
-        } else if status.contains(git2::Status::WT_MODIFIED) {
-            // 如果文件在工作区被修改但没有暂存，添加到未暂存列表
-            if !status.contains(git2::Status::INDEX_MODIFIED) {
-                unstaged_files.push(FileChange {
-                    path: file_path.clone(),
-                    status: "modified".to_string(),
-                    additions: 1,
-                    deletions: 0,
-                });
-            }
-        } else if status.contains(git2::Status::WT_DELETED) {
-            // 如果文件在工作区被删除但没有暂存，添加到未暂存列表
-            if !status.contains(git2::Status::INDEX_DELETED) {
-                unstaged_files.push(FileChange {
-                    path: file_path.clone(),
-                    status: "deleted".to_string(),
-                    additions: 0,
-                    deletions: 1,
-                });
+    let mut conflicted_files = Vec::new();
+    let mut ignored_files = Vec::new();
+    // 子模块变更单独报告，不混入普通文件列表
+    let submodule_paths = submodule_paths(repo);
+    let submodules = collect_submodule_changes(repo, &submodule_paths, options);
+
+    // 启用了 fsmonitor 或 untracked cache 时交给 git 命令行，libgit2 不支持这两项加速
+    let entries = if status_uses_git_accelerators(repo) {
+        match git_cli_statuses(repo, options) {
+            Ok(entries) => entries,
+            Err(e) => {
+                log_message("WARN", &format!("git status 失败，改用 libgit2: {}", e));
+                libgit2_statuses(repo, options)?
             }
-        } else if status.contains(git2::Status::WT_TYPECHANGE) {
-            // 文件类型改变
-            unstaged_files.push(FileChange {
-                path: file_path.clone(),
-                status: "modified".to_string(),
-                additions: 1,
-                deletions: 0,
-            });
         }
+    } else {
+        libgit2_statuses(repo, options)?
+    };
+
+    let conflict_kinds = if entries.iter().any(|e| e.status.is_conflicted()) {
+        index_conflict_kinds(repo)?
+    } else {
+        HashMap::new()
+    };
+
+    // 一次遍历同时得到暂存区和工作区两侧的变化
+    for entry in entries {
+        let StatusEntry { path: file_path, status, index_old_path, workdir_old_path } = entry;
+        if submodule_paths.contains_key(file_path.trim_end_matches('/')) {
+            continue;
+        }
+        if status.is_ignored() {
+            ignored_files.push(file_path);
+            continue;
+        }
+        if status.is_conflicted() {
+            let kind = conflict_kinds.get(&file_path).copied().unwrap_or(ConflictKind::BothModified);
+            conflicted_files.push(ConflictedFile { path: file_path, kind });
+            continue;
+        }
+
+        let staged = if status.is_index_new() {
+            Some(FileStatus::Added)
+        } else if status.is_index_renamed() {
+            Some(FileStatus::Renamed)
+        } else if status.is_index_deleted() {
+            // 与 git status 保持一致：即便工作区有 WT_NEW，也要在暂存区显示 deleted
+            Some(FileStatus::Deleted)
+        } else if status.is_index_typechange() {
+            Some(FileStatus::TypeChange)
+        } else if status.is_index_modified() {
+            Some(FileStatus::Modified)
+        } else {
+            None
+        };
+        if let Some(kind) = staged {
+            // 工作区中又被重命名时，暂存区一侧使用索引中的路径
+            let path = workdir_old_path.clone().unwrap_or_else(|| file_path.clone());
+            staged_files.push(file_change(path, index_old_path.filter(|_| kind == FileStatus::Renamed), kind));
+        }
+
+        // 同一文件可以同时有暂存和未暂存的修改；HEAD 中存在但已从索引删除的文件在工作区显示为未跟踪
+        let unstaged = if status.is_wt_new() {
+            untracked_files.push(file_path);
+            continue;
+        } else if status.is_wt_renamed() {
+            FileStatus::Renamed
+        } else if status.is_wt_deleted() {
+            FileStatus::Deleted
+        } else if status.is_wt_typechange() {
+            FileStatus::TypeChange
+        } else if status.is_wt_modified() {
+            FileStatus::Modified
+        } else {
+            continue;
+        };
+        unstaged_files.push(file_change(file_path, workdir_old_path.filter(|_| unstaged == FileStatus::Renamed), unstaged));
     }
-    
-    // 使用 index 到 workdir 的差异更可靠地获取"未暂存"
-    let index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
-    let mut diff_opts = git2::DiffOptions::new();
-    diff_opts.include_untracked(true).recurse_untracked_dirs(true);
-    let diff = repo.diff_index_to_workdir(Some(&index), Some(&mut diff_opts))
-        .map_err(|e| format!("Failed to create index->workdir diff: {}", e))?;
-    
-     
-    let mut diff_count = 0;
-    diff.foreach(
-        &mut |delta, _| {
-            diff_count += 1;
-            let file_path = delta.new_file().path()
-                .or_else(|| delta.old_file().path())
-                .map(|p| p.to_string_lossy().replace('\\', "/"))
-                .unwrap_or_default();
-            let _delta_status = format!("{:?}", delta.status());
-            // 注意：同一文件可以同时有暂存和未暂存的修改，所以不跳过
-            // 识别类型
-            let status = match delta.status() {
-                git2::Delta::Added => "added",
-                git2::Delta::Modified => "modified",
-                git2::Delta::Deleted => "deleted",
-                git2::Delta::Renamed => "renamed",
-                git2::Delta::Untracked => {
-                    if !untracked_files.contains(&file_path) {
-                        untracked_files.push(file_path.clone());
-                    }
-                    return true;
-                },
-                _ => "modified",
-            };
-            if !unstaged_files.iter().any(|f| f.path == file_path) {
-                unstaged_files.push(FileChange {
-                    path: file_path.clone(),
-                    status: status.to_string(),
-                    additions: 1,
-                    deletions: 0,
-                });
-            }
-            true
-        },
-        None,
-        None,
-        None,
-    ).map_err(|e| format!("Failed to iterate index->workdir diff: {}", e))?;
-    
+
+    let changed_paths = staged_files.iter().chain(unstaged_files.iter())
+        .map(|f| &f.path)
+        .chain(untracked_files.iter().filter(|p| !p.ends_with('/')));
+    let mut lfs_files = lfs_tracked_paths(repo, changed_paths);
+    lfs_files.sort();
+    lfs_files.dedup();
+
     Ok(WorkspaceStatus {
         staged_files,
         unstaged_files,
         untracked_files,
+        conflicted_files,
+        ignored_files,
+        submodules,
+        lfs_files,
     })
 }
 
-// 暂存文件
-#[tauri::command]
-async fn stage_file(repo_path: String, file_path: String) -> Result<String, String> {
-    log_message("INFO", &format!("stage_file: attempt start | path={} file={}", repo_path, file_path));
-    
-    let repo = Repository::open(&repo_path)
-        .map_err(|e| {
-            let error_msg = format!("Failed to open repository: {}", e);
-            log_message("ERROR", &format!("stage_file: {} | path={}", error_msg, repo_path));
-            error_msg
-        })?;
-    
-    let mut index = repo.index()
-        .map_err(|e| {
-            let error_msg = format!("Failed to get index: {}", e);
-            log_message("ERROR", &format!("stage_file: {} | path={} file={}", error_msg, repo_path, file_path));
-            error_msg
-        })?;
-    
-    // 检查文件是否在工作区中存在
-    let full_path = Path::new(&repo_path).join(&file_path);
-    let file_exists = full_path.exists();
-    
-    if file_exists {
-        // 文件存在，使用 add_path 暂存
-        index.add_path(Path::new(&file_path))
-            .map_err(|e| {
-                let error_msg = format!("Failed to add file to index: {}", e);
-                log_message("ERROR", &format!("stage_file: {} | path={} file={}", error_msg, repo_path, file_path));
-                error_msg
-            })?;
-        log_message("DEBUG", &format!("stage_file: file exists, using add_path | path={} file={}", repo_path, file_path));
-    } else {
-        // 文件不存在，使用 remove_path 暂存删除
-        index.remove_path(Path::new(&file_path))
-            .map_err(|e| {
-                let error_msg = format!("Failed to remove file from index: {}", e);
-                log_message("ERROR", &format!("stage_file: {} | path={} file={}", error_msg, repo_path, file_path));
-                error_msg
-            })?;
-        log_message("DEBUG", &format!("stage_file: file deleted, using remove_path | path={} file={}", repo_path, file_path));
+// 工作区状态不统计行数，只区分增删
+fn file_change(path: String, old_path: Option<String>, status: FileStatus) -> FileChange {
+    let deleted = status == FileStatus::Deleted;
+    FileChange {
+        path,
+        old_path,
+        status,
+        additions: if deleted { 0 } else { 1 },
+        deletions: if deleted { 1 } else { 0 },
     }
-    
-    index.write()
-        .map_err(|e| {
-            let error_msg = format!("Failed to write index: {}", e);
-            log_message("ERROR", &format!("stage_file: {} | path={} file={}", error_msg, repo_path, file_path));
-            error_msg
-        })?;
-    
-    log_message("INFO", &format!("stage_file: success | path={} file={}", repo_path, file_path));
-    Ok(format!("Successfully staged {}", file_path))
 }
 
-// 取消暂存文件
-#[tauri::command]
-async fn unstage_file(repo_path: String, file_path: String) -> Result<String, String> {
-    log_message("INFO", &format!("unstage_file: attempt start | path={} file={}", repo_path, file_path));
-    
-    let repo = Repository::open(&repo_path)
-        .map_err(|e| {
-            let error_msg = format!("Failed to open repository: {}", e);
-            log_message("ERROR", &format!("unstage_file: {} | path={}", error_msg, repo_path));
-            error_msg
-        })?;
+// 索引中各冲突路径的冲突类型
+fn index_conflict_kinds(repo: &Repository) -> Result<HashMap<String, ConflictKind>, String> {
+    let index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
+    let conflicts = index.conflicts()
+        .map_err(|e| format!("Failed to read conflicts: {}", e))?;
+    Ok(conflicts.flatten()
+        .filter_map(|conflict| {
+            let entry = conflict.our.as_ref().or(conflict.their.as_ref()).or(conflict.ancestor.as_ref())?;
+            Some((String::from_utf8_lossy(&entry.path).to_string(), conflict_kind(&conflict)))
+        })
+        .collect())
+}
 
-    // 获取 HEAD 对象
-    let head_obj = repo.revparse_single("HEAD")
-        .map_err(|e| {
-            let error_msg = format!("Failed to get HEAD object: {}", e);
-            log_message("ERROR", &format!("unstage_file: {} | path={} file={}", error_msg, repo_path, file_path));
-            error_msg
-        })?;
+fn libgit2_statuses(repo: &Repository, options: &WorkspaceStatusOptions) -> Result<Vec<StatusEntry>, String> {
+    let mut status_options = git2::StatusOptions::new();
+    status_options
+        .include_untracked(true)
+        .recurse_untracked_dirs(!options.collapse_untracked_dirs)
+        .include_ignored(options.include_ignored)
+        .recurse_ignored_dirs(false)
+        .include_unmodified(false)
+        .renames_head_to_index(true)
+        .renames_index_to_workdir(options.detect_workdir_renames)
+        // 子模块由 collect_submodule_changes 单独检查，不必进入其工作区
+        .exclude_submodules(true);
+    for pathspec in &options.pathspecs {
+        status_options.pathspec(pathspec);
+    }
 
-    // 使用 reset_default 方法取消暂存指定文件
-    // 这等价于 git reset HEAD <file>，会将文件从暂存区移除但不会标记为删除
-    repo.reset_default(Some(&head_obj), &[Path::new(&file_path)])
-        .map_err(|e| {
-            let error_msg = format!("Failed to unstage file: {}", e);
-            log_message("ERROR", &format!("unstage_file: {} | path={} file={}", error_msg, repo_path, file_path));
-            error_msg
-        })?;
-    
-    log_message("INFO", &format!("unstage_file: success | path={} file={}", repo_path, file_path));
-    Ok(format!("Successfully unstaged {}", file_path))
+    let statuses = repo.statuses(Some(&mut status_options))
+        .map_err(|e| format!("Failed to get statuses: {}", e))?;
+    let path_of = |file: git2::DiffFile| file.path().map(|p| p.to_string_lossy().replace('\\', "/"));
+    Ok(statuses.iter()
+        .map(|entry| {
+            let status = entry.status();
+            let head_to_index = entry.head_to_index();
+            let index_to_workdir = entry.index_to_workdir();
+            // entry.path() 在重命名时返回原路径，这里取最新一侧的路径
+            let path = index_to_workdir.as_ref().and_then(|d| path_of(d.new_file()))
+                .or_else(|| head_to_index.as_ref().and_then(|d| path_of(d.new_file())))
+                .unwrap_or_else(|| entry.path().unwrap_or("").to_string());
+            StatusEntry {
+                path,
+                status,
+                index_old_path: head_to_index.filter(|_| status.is_index_renamed()).and_then(|d| path_of(d.old_file())),
+                workdir_old_path: index_to_workdir.filter(|_| status.is_wt_renamed()).and_then(|d| path_of(d.old_file())),
+            }
+        })
+        .collect())
 }
 
-// 提交更改
+// core.fsmonitor 可以是布尔值或钩子路径；core.untrackedCache 为 keep 时不主动启用
+fn status_uses_git_accelerators(repo: &Repository) -> bool {
+    let config = match repo.config() {
+        Ok(config) => config,
+        Err(_) => return false,
+    };
+    let enabled = |key: &str| match config.get_string(key) {
+        Ok(value) => !matches!(value.trim().to_ascii_lowercase().as_str(), "" | "false" | "no" | "off" | "0" | "keep"),
+        Err(_) => false,
+    };
+    enabled("core.fsmonitor") || enabled("core.untrackedCache")
+}
+
+// 调用 git status --porcelain=v2，并把 XY 状态码换算成 git2::Status，与 libgit2 结果统一处理
+fn git_cli_statuses(repo: &Repository, options: &WorkspaceStatusOptions) -> Result<Vec<StatusEntry>, String> {
+    let workdir = repo.workdir().ok_or("Bare repository has no working directory")?;
+    let untracked = if options.collapse_untracked_dirs { "--untracked-files=normal" } else { "--untracked-files=all" };
+    let ignored = if options.include_ignored { "--ignored=matching" } else { "--ignored=no" };
+    let output = std::process::Command::new("git")
+        .arg("-C").arg(workdir)
+        .args(["status", "--porcelain=v2", "-z", "--ignore-submodules=all", untracked, ignored, "--"])
+        .args(&options.pathspecs)
+        .output()
+        .map_err(|e| format!("Failed to run git status: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(parse_porcelain_v2_status(&output.stdout))
+}
+
+// 解析 git status --porcelain=v2 -z 的输出
+fn parse_porcelain_v2_status(stdout: &[u8]) -> Vec<StatusEntry> {
+    let index_flag = |c: u8| match c {
+        b'M' => git2::Status::INDEX_MODIFIED,
+        b'T' => git2::Status::INDEX_TYPECHANGE,
+        b'A' | b'C' => git2::Status::INDEX_NEW,
+        b'D' => git2::Status::INDEX_DELETED,
+        b'R' => git2::Status::INDEX_RENAMED,
+        _ => git2::Status::empty(),
+    };
+    let workdir_flag = |c: u8| match c {
+        b'M' => git2::Status::WT_MODIFIED,
+        b'T' => git2::Status::WT_TYPECHANGE,
+        b'D' => git2::Status::WT_DELETED,
+        b'A' => git2::Status::WT_NEW,
+        b'R' => git2::Status::WT_RENAMED,
+        _ => git2::Status::empty(),
+    };
+
+    let mut entries = Vec::new();
+    let mut records = stdout.split(|b| *b == 0).filter(|r| !r.is_empty());
+    while let Some(record) = records.next() {
+        let text = String::from_utf8_lossy(record);
+        // 普通条目 8 个字段后是路径，重命名条目多一个相似度字段并在下一条记录给出原路径，冲突条目 10 个字段
+        let (status, path, old_path) = match record[0] {
+            b'?' => (git2::Status::WT_NEW, text.get(2..).map(str::to_string), None),
+            b'!' => (git2::Status::IGNORED, text.get(2..).map(str::to_string), None),
+            b'1' | b'2' | b'u' => {
+                let fields = match record[0] { b'1' => 8, b'2' => 9, _ => 10 };
+                let xy = text.split(' ').nth(1).unwrap_or("").as_bytes();
+                let status = if record[0] == b'u' {
+                    git2::Status::CONFLICTED
+                } else {
+                    index_flag(*xy.first().unwrap_or(&b'.')) | workdir_flag(*xy.get(1).unwrap_or(&b'.'))
+                };
+                let old_path = if record[0] == b'2' {
+                    records.next().map(|r| String::from_utf8_lossy(r).to_string())
+                } else {
+                    None
+                };
+                (status, text.splitn(fields + 1, ' ').nth(fields).map(str::to_string), old_path)
+            }
+            _ => continue,
+        };
+        if let Some(path) = path {
+            entries.push(StatusEntry {
+                path,
+                status,
+                index_old_path: old_path.clone().filter(|_| status.is_index_renamed()),
+                workdir_old_path: old_path.filter(|_| status.is_wt_renamed()),
+            });
+        }
+    }
+    entries
+}
+
+// 暂存文件
 #[tauri::command]
-async fn commit_changes(repo_path: String, message: String) -> Result<String, String> {
-    let repo = Repository::open(&repo_path)
-        .map_err(|e| format!("Failed to open repository: {}", e))?;
-    
-    let mut index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
+async fn stage_file(repo_path: String, file_path: String, cache: tauri::State<'_, RepoCache>) -> Result<String, String> {
+    with_repo(&cache, repo_path.clone(), move |repo| {
+        log_message("INFO", &format!("stage_file: attempt start | path={} file={}", repo_path, file_path));
     
-    // 检查是否有暂存的文件
-    if index.len() == 0 {
-        return Err("No files staged for commit".to_string());
-    }
     
-    let tree_id = index.write_tree().map_err(|e| format!("Failed to write tree: {}", e))?;
-    let tree = repo.find_tree(tree_id).map_err(|e| format!("Failed to find tree: {}", e))?;
+        let mut index = repo.index()
+            .map_err(|e| {
+                let error_msg = format!("Failed to get index: {}", e);
+                log_message("ERROR", &format!("stage_file: {} | path={} file={}", error_msg, repo_path, file_path));
+                error_msg
+            })?;
     
-    let head = repo.head().ok();
-    let parent_commit = if let Some(head) = head {
-        head.peel_to_commit().ok()
-    } else {
-        None
-    };
+        // 检查文件是否在工作区中存在
+        let full_path = Path::new(&repo_path).join(&file_path);
+        let file_exists = full_path.exists();
     
-    let signature = git2::Signature::now("GitLite User", "gitlite@example.com")
-        .map_err(|e| format!("Failed to create signature: {}", e))?;
+        if file_exists {
+            // 文件存在，使用 add_path 暂存
+            index.add_path(Path::new(&file_path))
+                .map_err(|e| {
+                    let error_msg = format!("Failed to add file to index: {}", e);
+                    log_message("ERROR", &format!("stage_file: {} | path={} file={}", error_msg, repo_path, file_path));
+                    error_msg
+                })?;
+            log_message("DEBUG", &format!("stage_file: file exists, using add_path | path={} file={}", repo_path, file_path));
+        } else {
+            // 文件不存在，使用 remove_path 暂存删除
+            index.remove_path(Path::new(&file_path))
+                .map_err(|e| {
+                    let error_msg = format!("Failed to remove file from index: {}", e);
+                    log_message("ERROR", &format!("stage_file: {} | path={} file={}", error_msg, repo_path, file_path));
+                    error_msg
+                })?;
+            log_message("DEBUG", &format!("stage_file: file deleted, using remove_path | path={} file={}", repo_path, file_path));
+        }
     
-    let commit_id = repo.commit(
-        Some("HEAD"),
-        &signature,
-        &signature,
-        &message,
-        &tree,
-        &parent_commit.iter().collect::<Vec<_>>(),
-    ).map_err(|e| format!("Failed to commit: {}", e))?;
+        index.write()
+            .map_err(|e| {
+                let error_msg = format!("Failed to write index: {}", e);
+                log_message("ERROR", &format!("stage_file: {} | path={} file={}", error_msg, repo_path, file_path));
+                error_msg
+            })?;
     
-    Ok(format!("Successfully committed with ID: {}", commit_id))
+        log_message("INFO", &format!("stage_file: success | path={} file={}", repo_path, file_path));
+        Ok(format!("Successfully staged {}", file_path))
+    }).await
 }
 
-// 推送更改（支持认证与自动设置上游）
+// 取消暂存文件
 #[tauri::command]
-async fn push_changes(repo_path: String) -> Result<String, String> {
-    log_message("INFO", &format!("push: attempt start | path={}", repo_path));
-    let repo = match Repository::open(&repo_path) {
-        Ok(r) => r,
-        Err(e) => {
-            log_message("ERROR", &format!("push: open repository failed: {} | path={}", e, repo_path));
-            return Err(format!("Failed to open repository: {}", e));
-        }
-    };
+async fn unstage_file(repo_path: String, file_path: String, cache: tauri::State<'_, RepoCache>) -> Result<String, String> {
+    with_repo(&cache, repo_path.clone(), move |repo| {
+        log_message("INFO", &format!("unstage_file: attempt start | path={} file={}", repo_path, file_path));
+    
 
-    let head = match repo.head() {
-        Ok(h) => h,
-        Err(e) => {
-            log_message("ERROR", &format!("push: get HEAD failed: {}", e));
-            return Err(format!("Failed to get HEAD: {}", e));
-        }
+        // 获取 HEAD 对象
+        let head_obj = repo.revparse_single("HEAD")
+            .map_err(|e| {
+                let error_msg = format!("Failed to get HEAD object: {}", e);
+                log_message("ERROR", &format!("unstage_file: {} | path={} file={}", error_msg, repo_path, file_path));
+                error_msg
+            })?;
+
+        // 使用 reset_default 方法取消暂存指定文件
+        // 这等价于 git reset HEAD <file>，会将文件从暂存区移除但不会标记为删除
+        repo.reset_default(Some(&head_obj), &[Path::new(&file_path)])
+            .map_err(|e| {
+                let error_msg = format!("Failed to unstage file: {}", e);
+                log_message("ERROR", &format!("unstage_file: {} | path={} file={}", error_msg, repo_path, file_path));
+                error_msg
+            })?;
+    
+        log_message("INFO", &format!("unstage_file: success | path={} file={}", repo_path, file_path));
+        Ok(format!("Successfully unstaged {}", file_path))
+    }).await
+}
+
+// 批量暂存请求
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StageRequest {
+    pub paths: Vec<String>,  // 文件、目录或 glob（如 src/*.rs），为空时作用于整个工作区
+    pub tracked_only: bool,  // 只暂存已跟踪文件的修改和删除，相当于 git add -u
+    pub intent_to_add: bool, // 未跟踪文件只登记意图添加（git add -N），内容留待之后暂存
+}
+
+// 意图添加条目：flags 带 EXTENDED 且 flags_extended 带 INTENT_TO_ADD
+fn is_intent_to_add(entry: &git2::IndexEntry) -> bool {
+    git2::IndexEntryFlag::from_bits_truncate(entry.flags).is_extended()
+        && git2::IndexEntryExtendedFlag::from_bits_truncate(entry.flags_extended).is_intent_to_add()
+}
+
+// 将索引写成树对象；libgit2 不认识意图添加标志，会把这些条目当作空文件写入，需先去掉
+fn write_index_tree(repo: &Repository, index: &mut git2::Index) -> Result<Oid, git2::Error> {
+    if !index.iter().any(|entry| is_intent_to_add(&entry)) {
+        return index.write_tree();
+    }
+    let mut filtered = git2::Index::new()?;
+    for entry in index.iter().filter(|entry| !is_intent_to_add(entry)) {
+        filtered.add(&entry)?;
+    }
+    filtered.write_tree_to(repo)
+}
+
+// 为未跟踪文件生成意图添加条目：内容为空 blob，生成提交的树时由 write_index_tree 跳过
+fn intent_to_add_entry(repo: &Repository, path: &str) -> Result<git2::IndexEntry, String> {
+    let workdir = repo.workdir().ok_or("Bare repository has no working directory")?;
+    let metadata = fs::symlink_metadata(workdir.join(path))
+        .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let mode = if metadata.file_type().is_symlink() {
+        0o120000
+    } else if is_executable(&metadata) {
+        0o100755
+    } else {
+        0o100644
     };
-    let branch_name = head.shorthand().unwrap_or("main");
+    let empty = repo.blob(b"").map_err(|e| format!("Failed to write blob: {}", e))?;
+    let zero = git2::IndexTime::new(0, 0);
+    Ok(git2::IndexEntry {
+        ctime: zero,
+        mtime: zero,
+        dev: 0,
+        ino: 0,
+        mode,
+        uid: 0,
+        gid: 0,
+        file_size: 0,
+        id: empty,
+        flags: (path.len().min(0xfff) as u16) | git2::IndexEntryFlag::EXTENDED.bits(),
+        flags_extended: git2::IndexEntryExtendedFlag::INTENT_TO_ADD.bits(),
+        path: path.as_bytes().to_vec(),
+    })
+}
 
-    let mut remote = match repo.find_remote("origin") {
-        Ok(r) => r,
-        Err(e) => {
-            log_message("ERROR", &format!("push: find remote 'origin' failed: {}", e));
-            return Err(format!("Failed to find remote 'origin': {}", e));
-        }
+#[cfg(unix)]
+fn is_executable(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+// 按请求暂存，所有变更写入同一次索引写操作；返回被暂存的路径
+fn stage_paths_in(repo: &Repository, request: &StageRequest) -> Result<Vec<String>, String> {
+    let mut index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
+    let mut staged: Vec<String> = Vec::new();
+    // 没有 pathspec 时 libgit2 给回调传空指针，git2 会直接解引用，因此用 * 表示全部
+    let pathspecs: Vec<&str> = if request.paths.is_empty() {
+        vec!["*"]
+    } else {
+        request.paths.iter().map(String::as_str).collect()
     };
 
-    // 认证与 Push 选项
-    let cfg = repo.config().ok();
-    let mut callbacks = git2::RemoteCallbacks::new();
-    callbacks.credentials(move |url, username_from_url, allowed| {
-        log_message("DEBUG", &format!("push: credential callback | url={} username={:?} allowed={:?}", 
-            url, username_from_url, allowed));
-        
-        if allowed.contains(git2::CredentialType::DEFAULT) {
-            log_message("DEBUG", "push: trying default credentials");
-            return git2::Cred::default();
-        }
-        if allowed.contains(git2::CredentialType::SSH_KEY) {
-            log_message("DEBUG", "push: trying SSH key from agent");
-            return git2::Cred::ssh_key_from_agent(username_from_url.unwrap_or("git"));
+    // 需要意图添加的未跟踪文件不写入内容，先找出来
+    let mut intent_paths = Vec::new();
+    if request.intent_to_add && !request.tracked_only {
+        let mut status_options = git2::StatusOptions::new();
+        status_options.include_untracked(true).recurse_untracked_dirs(true).exclude_submodules(true);
+        for path in &request.paths {
+            status_options.pathspec(path);
         }
-        if allowed.contains(git2::CredentialType::USER_PASS_PLAINTEXT) {
-            log_message("DEBUG", "push: trying credential helper");
-            if let Some(cfg) = cfg.as_ref() {
-                if let Ok(cred) = git2::Cred::credential_helper(cfg, url, username_from_url) {
-                    log_message("DEBUG", "push: credential helper success");
-                    return Ok(cred);
-                } else {
-                    log_message("WARN", "push: credential helper failed");
-                }
+        let statuses = repo.statuses(Some(&mut status_options))
+            .map_err(|e| format!("Failed to get statuses: {}", e))?;
+        intent_paths = statuses.iter()
+            .filter(|entry| entry.status().is_wt_new() && !entry.status().is_index_deleted())
+            .filter_map(|entry| entry.path().map(|p| p.to_string()))
+            .collect();
+    }
+
+    if !request.tracked_only {
+        index.add_all(pathspecs.iter(), git2::IndexAddOption::DEFAULT, Some(&mut |path: &Path, _: &[u8]| {
+            let path = path.to_string_lossy().replace('\\', "/");
+            if intent_paths.contains(&path) {
+                return 1;
             }
-        }
-        log_message("ERROR", "push: no authentication method available");
-        Err(git2::Error::from_str("No authentication method available"))
-    });
+            staged.push(path);
+            0
+        }))
+        .map_err(|e| format!("Failed to add files to index: {}", e))?;
+    }
+    // add_all 不处理已删除的文件，由 update_all 暂存删除和已跟踪文件的修改
+    index.update_all(pathspecs.iter(), Some(&mut |path: &Path, _: &[u8]| {
+        staged.push(path.to_string_lossy().replace('\\', "/"));
+        0
+    }))
+    .map_err(|e| format!("Failed to update index: {}", e))?;
+
+    for path in &intent_paths {
+        index.add(&intent_to_add_entry(repo, path)?)
+            .map_err(|e| format!("Failed to add {} to index: {}", path, e))?;
+        staged.push(path.clone());
+    }
+
+    index.write().map_err(|e| format!("Failed to write index: {}", e))?;
+    staged.sort();
+    staged.dedup();
+    Ok(staged)
+}
 
-    let mut push_opts = git2::PushOptions::new();
-    push_opts.remote_callbacks(callbacks);
+// 按路径或 glob 取消暂存，相当于 git reset -- <paths>；返回被取消暂存的路径
+fn unstage_paths_in(repo: &Repository, paths: &[String]) -> Result<Vec<String>, String> {
+    let head_commit = match repo.head() {
+        Ok(head) => Some(head.peel_to_commit().map_err(|e| format!("Failed to get HEAD commit: {}", e))?),
+        Err(e) if e.code() == git2::ErrorCode::UnbornBranch || e.code() == git2::ErrorCode::NotFound => None,
+        Err(e) => return Err(format!("Failed to get HEAD: {}", e)),
+    };
+    let head_tree = head_commit.as_ref()
+        .map(|commit| commit.tree())
+        .transpose()
+        .map_err(|e| format!("Failed to get HEAD tree: {}", e))?;
 
-    let refspec = format!("refs/heads/{}:refs/heads/{}", branch_name, branch_name);
-    if let Err(e) = remote.push(&[&refspec], Some(&mut push_opts)) {
-        let url = remote.url().unwrap_or("");
-        log_message("ERROR", &format!("push: git push failed: {} | url={} refspec={} branch={}", e, url, refspec, branch_name));
-        let log_path = get_config_dir().join("logs").join("gitlite.log");
-        return Err(format!("Failed to push: {} (see log: {})", e, log_path.display()));
+    let mut index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
+    let mut diff_opts = git2::DiffOptions::new();
+    diff_opts.include_typechange(true);
+    for path in paths {
+        diff_opts.pathspec(path);
+    }
+    let diff = repo.diff_tree_to_index(head_tree.as_ref(), Some(&index), Some(&mut diff_opts))
+        .map_err(|e| format!("Failed to diff HEAD to index: {}", e))?;
+    let mut unstaged: Vec<String> = diff.deltas()
+        .flat_map(|delta| [delta.old_file().path(), delta.new_file().path()])
+        .flatten()
+        .map(|p| p.to_string_lossy().replace('\\', "/"))
+        .collect();
+    unstaged.sort();
+    unstaged.dedup();
+    if unstaged.is_empty() {
+        return Ok(unstaged);
     }
 
-    // 若本地分支没有上游，自动设置到 origin/<branch>
-    if let Ok(mut branch) = repo.find_branch(branch_name, git2::BranchType::Local) {
-        if branch.upstream().is_err() {
-            if let Err(e) = branch.set_upstream(Some(&format!("origin/{}", branch_name))) {
-                log_message("WARN", &format!("push: set upstream failed but push succeeded: {}", e));
+    // reset_default 对全部路径只写一次索引；未提交过的仓库直接移出索引
+    match &head_commit {
+        Some(commit) => {
+            repo.reset_default(Some(commit.as_object()), unstaged.iter())
+                .map_err(|e| format!("Failed to unstage files: {}", e))?;
+        }
+        None => {
+            for path in &unstaged {
+                index.remove_path(Path::new(path))
+                    .map_err(|e| format!("Failed to remove {} from index: {}", path, e))?;
             }
+            index.write().map_err(|e| format!("Failed to write index: {}", e))?;
         }
     }
+    Ok(unstaged)
+}
 
-    log_message("INFO", &format!("push: success | branch={} refspec={}", branch_name, refspec));
-    Ok(format!("Successfully pushed to origin/{}", branch_name))
+// 批量暂存文件、目录或 glob，只写一次索引
+#[tauri::command]
+async fn stage_paths(repo_path: String, request: StageRequest, cache: tauri::State<'_, RepoCache>) -> Result<Vec<String>, String> {
+    with_repo(&cache, repo_path.clone(), move |repo| {
+        let staged = stage_paths_in(repo, &request)?;
+        log_message("INFO", &format!("stage_paths: staged {} file(s) | path={} request={:?}", staged.len(), repo_path, request));
+        Ok(staged)
+    }).await
 }
 
-// 拉取更改
+// 批量取消暂存文件、目录或 glob，只写一次索引
 #[tauri::command]
-async fn pull_changes(repo_path: String) -> Result<String, String> {
-    log_message("INFO", &format!("pull: attempt start | path={}", repo_path));
-    let repo = match Repository::open(&repo_path) {
-        Ok(r) => r,
-        Err(e) => {
-            log_message("ERROR", &format!("pull: open repository failed: {} | path={}", e, repo_path));
-            return Err(format!("Failed to open repository: {}", e));
-        }
-    };
+async fn unstage_paths(repo_path: String, paths: Vec<String>, cache: tauri::State<'_, RepoCache>) -> Result<Vec<String>, String> {
+    with_repo(&cache, repo_path.clone(), move |repo| {
+        let unstaged = unstage_paths_in(repo, &paths)?;
+        log_message("INFO", &format!("unstage_paths: unstaged {} file(s) | path={} paths={:?}", unstaged.len(), repo_path, paths));
+        Ok(unstaged)
+    }).await
+}
 
-    let head = match repo.head() {
-        Ok(h) => h,
-        Err(e) => {
-            log_message("ERROR", &format!("pull: get HEAD failed: {}", e));
-            return Err(format!("Failed to get HEAD: {}", e));
-        }
-    };
-    let branch_name = head.shorthand().unwrap_or("main");
+// 暂存全部变更；tracked_only 时只暂存已跟踪文件（git add -u）
+#[tauri::command]
+async fn stage_all(repo_path: String, tracked_only: Option<bool>, cache: tauri::State<'_, RepoCache>) -> Result<Vec<String>, String> {
+    let request = StageRequest { tracked_only: tracked_only.unwrap_or(false), ..Default::default() };
+    stage_paths(repo_path, request, cache).await
+}
 
-    let mut remote = match repo.find_remote("origin") {
-        Ok(r) => r,
-        Err(e) => {
-            log_message("ERROR", &format!("pull: find remote 'origin' failed: {}", e));
-            return Err(format!("Failed to find remote 'origin': {}", e));
-        }
-    };
+// 取消全部暂存
+#[tauri::command]
+async fn unstage_all(repo_path: String, cache: tauri::State<'_, RepoCache>) -> Result<Vec<String>, String> {
+    unstage_paths(repo_path, Vec::new(), cache).await
+}
 
-    // 认证与 Fetch 选项
-    let cfg = repo.config().ok();
-    let mut callbacks = git2::RemoteCallbacks::new();
-    callbacks.credentials(move |url, username_from_url, allowed| {
-        if allowed.contains(git2::CredentialType::DEFAULT) {
-            return git2::Cred::default();
+// 读取 .git/MERGE_HEAD 中记录的被合并提交
+fn read_merge_heads(repo: &Repository) -> Vec<Oid> {
+    fs::read_to_string(repo.path().join("MERGE_HEAD"))
+        .map(|content| content.lines().filter_map(|line| Oid::from_str(line.trim()).ok()).collect())
+        .unwrap_or_default()
+}
+
+// 提交更改
+#[tauri::command]
+async fn commit_changes(repo_path: String, message: String, cache: tauri::State<'_, RepoCache>) -> Result<String, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        let mut index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
+    
+        // 未填写提交说明时沿用 squash 合并写入的 SQUASH_MSG
+        let squash_msg = repo.path().join("SQUASH_MSG");
+        let message = if message.trim().is_empty() {
+            fs::read_to_string(&squash_msg).unwrap_or(message)
+        } else {
+            message
+        };
+    
+        // 检查是否有暂存的文件
+        if index.len() == 0 {
+            return Err("No files staged for commit".to_string());
         }
-        if allowed.contains(git2::CredentialType::SSH_KEY) {
-            return git2::Cred::ssh_key_from_agent(username_from_url.unwrap_or("git"));
+    
+        // 存在未解决的冲突时不允许提交
+        let conflicts = conflicted_paths(&index);
+        if !conflicts.is_empty() {
+            return Err(format!("Cannot commit: unresolved conflicts in [{}]", conflicts.join(", ")));
         }
-        if allowed.contains(git2::CredentialType::USER_PASS_PLAINTEXT) {
-            if let Some(cfg) = cfg.as_ref() {
-                if let Ok(cred) = git2::Cred::credential_helper(cfg, url, username_from_url) {
-                    return Ok(cred);
-                }
+    
+        let tree_id = write_index_tree(repo, &mut index).map_err(|e| format!("Failed to write tree: {}", e))?;
+        let tree = repo.find_tree(tree_id).map_err(|e| format!("Failed to find tree: {}", e))?;
+    
+        let head = repo.head().ok();
+        let parent_commit = if let Some(head) = head {
+            head.peel_to_commit().ok()
+        } else {
+            None
+        };
+        let mut parents: Vec<git2::Commit> = parent_commit.into_iter().collect();
+    
+        // 合并进行中（MERGE_HEAD 存在）：合并提交需要包含被合并的提交作为父提交
+        let state = repo.state();
+        if state == git2::RepositoryState::Merge {
+            for oid in read_merge_heads(repo) {
+                parents.push(repo.find_commit(oid).map_err(|e| format!("Failed to find merge head: {}", e))?);
             }
         }
-        Err(git2::Error::from_str("No authentication method available"))
-    });
-
-    let mut fetch_opts = git2::FetchOptions::new();
-    fetch_opts.remote_callbacks(callbacks);
+    
+        let signature = git2::Signature::now("GitLite User", "gitlite@example.com")
+            .map_err(|e| format!("Failed to create signature: {}", e))?;
+    
+        let commit_id = repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            &message,
+            &tree,
+            &parents.iter().collect::<Vec<_>>(),
+        ).map_err(|e| format!("Failed to commit: {}", e))?;
+    
+        // 完成合并 / 拣选 / 还原后清理 MERGE_HEAD、CHERRY_PICK_HEAD 等状态文件
+        if matches!(state, git2::RepositoryState::Merge | git2::RepositoryState::CherryPick | git2::RepositoryState::Revert) {
+            repo.cleanup_state()
+                .map_err(|e| format!("Failed to clean up merge state: {}", e))?;
+        }
+        let _ = fs::remove_file(&squash_msg);
+    
+        Ok(format!("Successfully committed with ID: {}", commit_id))
+    }).await
+}
 
-    // 首先执行 fetch
-    let refspec = format!("refs/heads/{}:refs/remotes/origin/{}", branch_name, branch_name);
-    if let Err(e) = remote.fetch(&[&refspec], Some(&mut fetch_opts), None) {
-        let url = remote.url().unwrap_or("");
-        log_message("ERROR", &format!("pull: git fetch failed: {} | url={} refspec={} branch={}", e, url, refspec, branch_name));
-        let log_path = get_config_dir().join("logs").join("gitlite.log");
-        return Err(format!("Failed to fetch: {} (see log: {})", e, log_path.display()));
+// 解析要使用的远程仓库：显式参数优先，其次当前分支的 branch.<name>.remote，再次 origin，最后取第一个远程
+fn resolve_remote_name(repo: &Repository, remote: Option<&str>) -> String {
+    if let Some(name) = remote.filter(|r| !r.trim().is_empty()) {
+        return name.to_string();
     }
-
-    // 获取远程分支引用
-    let remote_branch_ref = format!("refs/remotes/origin/{}", branch_name);
-    let remote_branch_oid = match repo.refname_to_id(&remote_branch_ref) {
-        Ok(oid) => oid,
-        Err(e) => {
-            log_message("ERROR", &format!("pull: get remote branch OID failed: {} | ref={}", e, remote_branch_ref));
-            return Err(format!("Failed to get remote branch reference: {}", e));
+    let head_branch = repo.head().ok()
+        .filter(|h| h.is_branch())
+        .and_then(|h| h.shorthand().map(|s| s.to_string()));
+    if let Some(branch) = head_branch {
+        if let Ok(name) = repo.config().and_then(|c| c.get_string(&format!("branch.{}.remote", branch))) {
+            if name != "." {
+                return name;
+            }
         }
-    };
+    }
+    let remotes = repo.remotes().ok();
+    let names: Vec<String> = remotes.iter()
+        .flat_map(|r| r.iter().flatten().map(|n| n.to_string()))
+        .collect();
+    if names.iter().any(|n| n == "origin") || names.is_empty() {
+        "origin".to_string()
+    } else {
+        names[0].clone()
+    }
+}
 
-    // 获取本地HEAD的OID
-    let local_head_oid = match repo.refname_to_id("HEAD") {
-        Ok(oid) => oid,
-        Err(e) => {
-            log_message("ERROR", &format!("pull: get local HEAD OID failed: {}", e));
-            return Err(format!("Failed to get local HEAD reference: {}", e));
-        }
-    };
+// 推送选项（均可省略，默认推送当前分支到同名远程分支）
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PushRequest {
+    pub branches: Vec<String>,          // 要推送的本地分支，为空时为当前分支
+    pub remote_branch: Option<String>,  // 推送到不同名的远程分支（仅推送单个分支时有效）
+    pub refspecs: Vec<String>,          // 额外的任意 refspec
+    pub tags: bool,                     // 推送所有标签
+    pub delete: Vec<String>,            // 要删除的远程分支
+    pub force_with_lease: bool,         // 远程分支仍为预期提交时才强制推送
+    pub expected: Option<String>,       // force-with-lease 的预期提交，默认为本地的远程跟踪分支
+    pub set_upstream: Option<bool>,     // 默认在本地分支没有上游时自动设置
+}
 
-    // 检查是否需要合并
-    if remote_branch_oid == local_head_oid {
-        log_message("INFO", &format!("pull: already up to date | branch={}", branch_name));
-        return Ok("Already up to date".to_string());
-    }
+// 单个引用的推送结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PushRefResult {
+    pub refname: String,
+    pub status: String,          // "ok" / "rejected"
+    pub message: Option<String>, // 被拒绝的原因
+}
 
-    // 检查工作区是否有未提交的更改
-    let index = match repo.index() {
-        Ok(i) => i,
-        Err(e) => {
-            log_message("ERROR", &format!("pull: get index failed: {}", e));
-            return Err(format!("Failed to get index: {}", e));
-        }
-    };
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PushSummary {
+    pub remote: String,
+    pub refs: Vec<PushRefResult>,
+    pub message: String,
+}
 
-    // 检查是否有未暂存的更改
-    let diff_count = repo.diff_index_to_workdir(Some(&index), None)
-        .map_err(|e| format!("Failed to create diff: {}", e))?
-        .stats()
-        .map_err(|e| format!("Failed to get diff stats: {}", e))?
-        .files_changed();
+// 操作被取消时返回给前端的错误信息
+const OPERATION_CANCELLED: &str = "操作已取消";
 
-    if diff_count > 0 {
-        log_message("WARN", &format!("pull: uncommitted changes detected | files_changed={}", diff_count));
-        return Err("Cannot pull: You have uncommitted changes. Please commit or stash them first.".to_string());
-    }
+// 正在运行的可取消操作：操作 id → 取消标志
+type OperationRegistry = Mutex<HashMap<String, Arc<AtomicBool>>>;
 
-    // 执行合并
-    let remote_commit = match repo.find_commit(remote_branch_oid) {
-        Ok(c) => c,
-        Err(e) => {
-            log_message("ERROR", &format!("pull: find remote commit failed: {}", e));
-            return Err(format!("Failed to find remote commit: {}", e));
-        }
-    };
+fn running_operations() -> &'static OperationRegistry {
+    static RUNNING: OnceLock<OperationRegistry> = OnceLock::new();
+    RUNNING.get_or_init(|| Mutex::new(HashMap::new()))
+}
 
-    let local_commit = match repo.find_commit(local_head_oid) {
-        Ok(c) => c,
-        Err(e) => {
-            log_message("ERROR", &format!("pull: find local commit failed: {}", e));
-            return Err(format!("Failed to find local commit: {}", e));
-        }
-    };
+// 可取消的长时间操作：创建时登记到注册表，drop 时自动注销。
+// 前端可传入自己生成的 operation_id，以便在命令返回前调用 cancel_operation
+struct RunningOperation {
+    id: String,
+    cancelled: Arc<AtomicBool>,
+}
 
-    // 检查是否是快进合并
-    let is_ff = match repo.merge_base(local_head_oid, remote_branch_oid) {
-        Ok(base) => base == local_head_oid,
-        Err(_) => false,
-    };
+impl RunningOperation {
+    fn begin(kind: &str, operation_id: Option<String>) -> Self {
+        let id = operation_id
+            .filter(|id| !id.trim().is_empty())
+            .unwrap_or_else(|| format!("{}-{}", kind, chrono::Local::now().format("%Y%m%d%H%M%S%.6f")));
+        let cancelled = Arc::new(AtomicBool::new(false));
+        if let Ok(mut running) = running_operations().lock() {
+            running.insert(id.clone(), cancelled.clone());
+        }
+        log_message("DEBUG", &format!("operation started | id={} kind={}", id, kind));
+        RunningOperation { id, cancelled }
+    }
 
-    if is_ff {
-        // 快进合并
-        let mut reference = match repo.find_reference("HEAD") {
-            Ok(r) => r,
-            Err(e) => {
-                log_message("ERROR", &format!("pull: find HEAD reference failed: {}", e));
-                return Err(format!("Failed to find HEAD reference: {}", e));
-            }
-        };
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
 
-        if let Err(e) = reference.set_target(remote_branch_oid, "Fast-forward merge") {
-            log_message("ERROR", &format!("pull: fast-forward failed: {}", e));
-            return Err(format!("Failed to fast-forward: {}", e));
+    // 取消后 libgit2 返回的错误（如 "user cancelled"）统一替换为取消提示
+    fn or_cancelled(&self, message: String) -> String {
+        if self.is_cancelled() {
+            OPERATION_CANCELLED.to_string()
+        } else {
+            message
         }
+    }
+}
 
-        log_message("INFO", &format!("pull: fast-forward success | branch={}", branch_name));
-        Ok("Successfully pulled (fast-forward)".to_string())
-    } else {
-        // 需要创建合并提交
-        let mut merge_index = match repo.merge_commits(&local_commit, &remote_commit, None) {
-            Ok(index) => index,
-            Err(e) => {
-                log_message("ERROR", &format!("pull: merge commits failed: {}", e));
-                return Err(format!("Failed to merge: {}", e));
-            }
-        };
+impl Drop for RunningOperation {
+    fn drop(&mut self) {
+        if let Ok(mut running) = running_operations().lock() {
+            running.remove(&self.id);
+        }
+    }
+}
 
-        // 将合并结果写入工作区
-        let merge_tree = repo.find_tree(merge_index.write_tree().map_err(|e| format!("Failed to write merge tree: {}", e))?)
-            .map_err(|e| format!("Failed to find merge tree: {}", e))?;
+// 取消正在运行的操作；返回 false 表示操作不存在或已结束
+#[tauri::command]
+async fn cancel_operation(operation_id: String) -> Result<bool, String> {
+    let running = running_operations()
+        .lock()
+        .map_err(|_| "Operation registry poisoned".to_string())?;
+    match running.get(&operation_id) {
+        Some(cancelled) => {
+            cancelled.store(true, Ordering::Relaxed);
+            log_message("INFO", &format!("operation cancel requested | id={}", operation_id));
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
 
-        // 创建合并提交
-        let signature = git2::Signature::now("GitLite User", "gitlite@example.com")
-            .map_err(|e| format!("Failed to create signature: {}", e))?;
+// 凭据请求：通过 credential-request 事件发给前端，由 submit_credentials 回复
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialRequest {
+    pub id: String,
+    pub kind: String,               // "userpass"（用户名 + 密码 / 令牌）或 "passphrase"（SSH 私钥口令）
+    pub url: String,
+    pub username: Option<String>,
+    pub key_path: Option<String>,
+    pub attempt: u32,
+}
 
-        let merge_commit_id = repo.commit(
-            Some("HEAD"),
-            &signature,
-            &signature,
-            &format!("Merge branch 'origin/{}'", branch_name),
-            &merge_tree,
-            &[&local_commit, &remote_commit],
-        ).map_err(|e| format!("Failed to create merge commit: {}", e))?;
+// 前端对凭据请求的回复
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CredentialResponse {
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub save: bool,     // 认证成功后保存到 git 凭据助手
+    pub cancel: bool,
+}
 
-        log_message("INFO", &format!("pull: merge success | branch={} merge_commit={}", branch_name, merge_commit_id));
-        Ok(format!("Successfully pulled and merged (commit: {})", merge_commit_id))
-    }
+// 单次网络操作中最多调用凭据回调的次数，避免认证失败时无限重试
+const MAX_CREDENTIAL_ATTEMPTS: u32 = 10;
+// 单次网络操作中最多弹出用户名 / 密码输入框的次数
+const MAX_CREDENTIAL_PROMPTS: u32 = 3;
+// 等待用户输入凭据的超时时间
+const CREDENTIAL_PROMPT_TIMEOUT_SECS: u64 = 300;
+
+// 等待前端回复的凭据请求
+fn pending_credential_requests() -> &'static std::sync::Mutex<std::collections::HashMap<String, std::sync::mpsc::Sender<CredentialResponse>>> {
+    static PENDING: std::sync::OnceLock<std::sync::Mutex<std::collections::HashMap<String, std::sync::mpsc::Sender<CredentialResponse>>>> = std::sync::OnceLock::new();
+    PENDING.get_or_init(|| std::sync::Mutex::new(std::collections::HashMap::new()))
 }
 
-// 获取远程更改（不合并）- 带日志流
+// 回复凭据请求（由前端的凭据输入框调用）
 #[tauri::command]
-async fn fetch_changes_with_logs(repo_path: String) -> Result<Vec<(String, String, String)>, String> {
-    let mut logs = Vec::new();
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    
-    logs.push((timestamp, "INFO".to_string(), format!("fetch: attempt start | path={}", repo_path)));
-    
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    logs.push((timestamp, "INFO".to_string(), "正在打开仓库...".to_string()));
-    
-    let repo = match Repository::open(&repo_path) {
-        Ok(r) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "INFO".to_string(), "仓库打开成功".to_string()));
-            r
-        },
-        Err(e) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "ERROR".to_string(), format!("打开仓库失败: {}", e)));
-            return Err(format!("Failed to open repository: {}", e));
-        }
-    };
+async fn submit_credentials(request_id: String, response: CredentialResponse) -> Result<(), String> {
+    let sender = pending_credential_requests()
+        .lock()
+        .map_err(|_| "Credential registry poisoned".to_string())?
+        .remove(&request_id)
+        .ok_or_else(|| format!("Credential request not found or expired: {}", request_id))?;
+    sender.send(response)
+        .map_err(|_| "Credential request is no longer waiting".to_string())
+}
 
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    logs.push((timestamp, "INFO".to_string(), "正在查找远程仓库 origin...".to_string()));
+// 从远程 URL 中取出主机名：git@host:path、ssh://user@host:port/path、https://host/path
+fn url_host(url: &str) -> Option<String> {
+    let rest = match url.split_once("://") {
+        Some((_, rest)) => rest,
+        None => url,
+    };
+    let authority = rest.split(['/', ':']).next()?;
+    let host = authority.rsplit('@').next()?;
+    if host.is_empty() { None } else { Some(host.to_string()) }
+}
 
-    let mut remote = match repo.find_remote("origin") {
-        Ok(r) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "INFO".to_string(), "找到远程仓库 origin".to_string()));
-            r
-        },
-        Err(e) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "ERROR".to_string(), format!("未找到远程仓库 origin: {}", e)));
-            return Err(format!("Failed to find remote 'origin': {}", e));
+// ssh_config 风格的通配符匹配（* 与 ?）
+fn ssh_pattern_matches(pattern: &str, text: &str) -> bool {
+    fn matches(p: &[char], t: &[char]) -> bool {
+        match (p.first(), t.first()) {
+            (None, None) => true,
+            (Some('*'), _) => matches(&p[1..], t) || (!t.is_empty() && matches(p, &t[1..])),
+            (Some('?'), Some(_)) => matches(&p[1..], &t[1..]),
+            (Some(a), Some(b)) if a.eq_ignore_ascii_case(b) => matches(&p[1..], &t[1..]),
+            _ => false,
         }
-    };
+    }
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    matches(&p, &t)
+}
 
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    logs.push((timestamp, "INFO".to_string(), "正在设置认证...".to_string()));
+fn expand_home(path: &str) -> std::path::PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => std::path::PathBuf::from(path),
+    }
+}
 
-    // 认证与 Fetch 选项
-    let cfg = repo.config().ok();
-    let mut callbacks = git2::RemoteCallbacks::new();
-    callbacks.credentials(move |url, username_from_url, allowed| {
-        if allowed.contains(git2::CredentialType::DEFAULT) {
-            return git2::Cred::default();
+// 展开 IdentityFile 中的 ssh_config 占位符：%d 主目录、%h 主机、%r 远程用户、%u 本地用户、%%；
+// 遇到不支持的占位符返回 None
+fn expand_ssh_tokens(value: &str, host: &str, remote_user: &str) -> Option<String> {
+    let mut expanded = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            expanded.push(c);
+            continue;
         }
-        if allowed.contains(git2::CredentialType::SSH_KEY) {
-            return git2::Cred::ssh_key_from_agent(username_from_url.unwrap_or("git"));
+        match chars.next()? {
+            '%' => expanded.push('%'),
+            'd' => expanded.push_str(&dirs::home_dir()?.to_string_lossy()),
+            'h' => expanded.push_str(host),
+            'r' => expanded.push_str(remote_user),
+            'u' => expanded.push_str(&std::env::var("USER").or_else(|_| std::env::var("USERNAME")).ok()?),
+            _ => return None,
         }
-        if allowed.contains(git2::CredentialType::USER_PASS_PLAINTEXT) {
-            if let Some(cfg) = cfg.as_ref() {
-                if let Ok(cred) = git2::Cred::credential_helper(cfg, url, username_from_url) {
-                    return Ok(cred);
+    }
+    Some(expanded)
+}
+
+// 按优先级收集可尝试的 SSH 私钥：core.sshCommand 中的 -i，~/.ssh/config 中匹配主机的 IdentityFile，最后是默认密钥
+fn ssh_key_candidates(config: Option<&git2::Config>, host: Option<&str>, remote_user: &str) -> Vec<std::path::PathBuf> {
+    let mut keys = Vec::new();
+
+    if let Some(command) = config.and_then(|c| c.get_string("core.sshCommand").ok()) {
+        let mut tokens = command.split_whitespace();
+        while let Some(token) = tokens.next() {
+            if token == "-i" {
+                if let Some(path) = tokens.next() {
+                    keys.push(expand_home(path.trim_matches(|c| c == '"' || c == '\'')));
                 }
+            } else if let Some(path) = token.strip_prefix("-i") {
+                keys.push(expand_home(path.trim_matches(|c| c == '"' || c == '\'')));
             }
         }
-        Err(git2::Error::from_str("No authentication method available"))
-    });
+    }
 
-    let mut fetch_opts = git2::FetchOptions::new();
-    fetch_opts.remote_callbacks(callbacks);
-
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    logs.push((timestamp, "INFO".to_string(), "开始获取远程更改...".to_string()));
+    let ssh_dir = dirs::home_dir().map(|h| h.join(".ssh"));
+    if let (Some(host), Some(ssh_dir)) = (host, ssh_dir.as_ref()) {
+        if let Ok(content) = fs::read_to_string(ssh_dir.join("config")) {
+            let mut host_matches = true; // Host 块之前的配置对所有主机生效
+            for line in content.lines() {
+                let line = line.trim();
+                let (key, value) = match line.split_once(|c: char| c.is_whitespace() || c == '=') {
+                    Some((k, v)) => (k.to_lowercase(), v.trim().trim_start_matches('=').trim()),
+                    None => continue,
+                };
+                match key.as_str() {
+                    "host" => {
+                        let patterns: Vec<&str> = value.split_whitespace().collect();
+                        let negated = patterns.iter()
+                            .any(|p| p.strip_prefix('!').map(|p| ssh_pattern_matches(p, host)).unwrap_or(false));
+                        host_matches = !negated && patterns.iter()
+                            .any(|p| !p.starts_with('!') && ssh_pattern_matches(p, host));
+                    }
+                    "match" => host_matches = false,
+                    "identityfile" if host_matches => {
+                        let value = value.trim_matches('"');
+                        match expand_ssh_tokens(value, host, remote_user) {
+                            Some(path) => keys.push(expand_home(&path)),
+                            None => log_message("WARN", &format!("ssh config: skip IdentityFile with unsupported token: {}", value)),
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
 
-    // 执行 fetch 操作
-    match remote.fetch::<&str>(&[], Some(&mut fetch_opts), None) {
-        Ok(_) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "INFO".to_string(), "获取成功！".to_string()));
-            
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "SUCCESS".to_string(), "操作完成 - 已获取远程仓库最新信息".to_string()));
-            
-            Ok(logs)
-        },
-        Err(e) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "ERROR".to_string(), format!("获取失败: {}", e)));
-            
-            let url = remote.url().unwrap_or("");
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "ERROR".to_string(), format!("远程仓库URL: {}", url)));
-            
-            return Err(format!("Failed to fetch: {}", e));
+    if let Some(ssh_dir) = ssh_dir {
+        for name in ["id_ed25519", "id_ecdsa", "id_rsa", "id_dsa"] {
+            keys.push(ssh_dir.join(name));
         }
     }
-}
 
-// 推送更改 - 实时日志流
-#[tauri::command]
-async fn push_changes_with_realtime_logs(
-    repo_path: String,
-    app_handle: tauri::AppHandle
-) -> Result<String, String> {
-    let window = app_handle.get_window("main").unwrap();
-    
-    // 发送开始日志
-    let _ = window.emit("push-log", serde_json::json!({
-        "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-        "level": "INFO",
-        "message": "开始推送操作..."
-    }));
-    
-    let _ = window.emit("push-log", serde_json::json!({
-        "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-        "level": "INFO", 
-        "message": format!("正在打开仓库: {}", repo_path)
-    }));
-    
-    let repo = match Repository::open(&repo_path) {
-        Ok(r) => {
-            let _ = window.emit("push-log", serde_json::json!({
-                "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-                "level": "SUCCESS",
-                "message": "仓库打开成功"
-            }));
-            r
-        },
-        Err(e) => {
-            let _ = window.emit("push-log", serde_json::json!({
-                "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-                "level": "ERROR",
-                "message": format!("打开仓库失败: {}", e)
-            }));
-            return Err(format!("Failed to open repository: {}", e));
+    let mut unique = Vec::new();
+    for key in keys {
+        if key.is_file() && !unique.contains(&key) {
+            unique.push(key);
         }
-    };
+    }
+    unique
+}
 
-    // 应用代理配置
-    let _ = window.emit("push-log", serde_json::json!({
-        "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-        "level": "INFO",
-        "message": "正在应用代理配置..."
-    }));
+#[derive(Default)]
+struct CredentialState {
+    attempts: u32,
+    tried_default: bool,
+    tried_agent: bool,
+    tried_helper: bool,
+    prompts: u32,
+    ssh_keys: Option<Vec<std::path::PathBuf>>,
+    key_index: usize,
+    key_tried_plain: bool,
+    key_prompted: bool,
+    to_save: Option<(String, String, String)>, // (url, username, password)
+}
 
-    let proxy_config = match get_proxy_config().await {
-        Ok((config, is_from_git)) => {
-            // 配置来源
-            let _ = window.emit("push-log", serde_json::json!({
-                "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-                "level": "INFO",
-                "message": if is_from_git { "代理配置来源: Git 全局配置" } else { "代理配置来源: 应用本地配置" }
-            }));
+// 统一的凭据提供者：默认凭据 → SSH agent → SSH 私钥文件（必要时询问口令）→ 凭据助手 → 询问用户，
+// 超过尝试次数后停止，避免认证失败时无限循环
+struct CredentialProvider {
+    op: String,
+    repo_dir: Option<std::path::PathBuf>,
+    config: Option<git2::Config>,
+    app_handle: Option<tauri::AppHandle>,
+    cancelled: Option<Arc<AtomicBool>>,
+    state: std::cell::RefCell<CredentialState>,
+}
 
-            if config.enabled {
-                let _ = window.emit("push-log", serde_json::json!({
-                    "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-                    "level": "INFO",
-                    "message": format!("使用代理: {}://{}:{}", config.protocol, config.host, config.port)
-                }));
-            } else {
-                let _ = window.emit("push-log", serde_json::json!({
-                    "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-                    "level": "INFO",
-                    "message": "未启用代理"
-                }));
-            }
-            config
-        },
-        Err(e) => {
-            let _ = window.emit("push-log", serde_json::json!({
-                "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-                "level": "WARN",
-                "message": format!("获取代理配置失败: {}", e)
-            }));
-            ProxyConfig {
-                enabled: false,
-                host: "127.0.0.1".to_string(),
-                port: 7890,
-                username: None,
-                password: None,
-                protocol: "http".to_string(),
-            }
-        }
-    };
+impl CredentialProvider {
+    fn new(repo: &Repository, op: &str, app_handle: Option<tauri::AppHandle>, operation: Option<&RunningOperation>) -> Self {
+        let repo_dir = repo.workdir().unwrap_or_else(|| repo.path()).to_path_buf();
+        Self::with_config(repo.config().ok(), Some(repo_dir), op, app_handle, operation)
+    }
 
-    // 注意：不再设置代理环境变量或写入 git config
-    // libgit2 会使用系统已有的 Git 配置（用户已设置的代理）
-    // 我们只需要验证代理配置的协议是否正确
-    if proxy_config.enabled {
-        if let Err(e) = validate_proxy_protocol(&proxy_config.protocol) {
-            let _ = window.emit("push-log", serde_json::json!({
-                "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-                "level": "ERROR",
-                "message": e
-            }));
-            return Err(format!("代理配置错误: {}", e));
+    // 没有仓库时（如克隆）使用全局 Git 配置
+    fn with_config(
+        config: Option<git2::Config>,
+        repo_dir: Option<std::path::PathBuf>,
+        op: &str,
+        app_handle: Option<tauri::AppHandle>,
+        operation: Option<&RunningOperation>,
+    ) -> Self {
+        CredentialProvider {
+            op: op.to_string(),
+            repo_dir,
+            config,
+            app_handle,
+            cancelled: operation.map(|o| o.cancelled.clone()),
+            state: std::cell::RefCell::new(CredentialState::default()),
         }
-        let _ = window.emit("push-log", serde_json::json!({
-            "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-            "level": "INFO",
-            "message": format!("注意：GitLite 使用系统 Git 配置中的代理设置，当前代理配置（{}://{}:{}）仅用于参考", 
-                proxy_config.protocol, proxy_config.host, proxy_config.port)
-        }));
     }
 
-    let _ = window.emit("push-log", serde_json::json!({
-        "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-        "level": "INFO",
-        "message": "正在获取HEAD引用..."
-    }));
+    // 创建带凭据回调的 RemoteCallbacks，可在其上继续设置其他回调
+    fn callbacks(&self) -> git2::RemoteCallbacks<'_> {
+        let mut callbacks = git2::RemoteCallbacks::new();
+        callbacks.credentials(move |url, username_from_url, allowed| {
+            self.acquire(url, username_from_url, allowed)
+        });
+        callbacks
+    }
 
-    let head = match repo.head() {
-        Ok(h) => {
-            let _ = window.emit("push-log", serde_json::json!({
-                "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-                "level": "SUCCESS",
-                "message": "获取HEAD引用成功"
-            }));
-            h
-        },
-        Err(e) => {
-            let _ = window.emit("push-log", serde_json::json!({
-                "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-                "level": "ERROR",
-                "message": format!("获取HEAD失败: {}", e)
-            }));
-            return Err(format!("Failed to get HEAD: {}", e));
+    fn acquire(&self, url: &str, username_from_url: Option<&str>, allowed: git2::CredentialType) -> Result<git2::Cred, git2::Error> {
+        let op = self.op.as_str();
+        if self.is_cancelled() {
+            return Err(git2::Error::from_str(OPERATION_CANCELLED));
         }
-    };
-    
-    let branch_name = head.shorthand().unwrap_or("main");
-    let _ = window.emit("push-log", serde_json::json!({
-        "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-        "level": "INFO",
-        "message": format!("当前分支: {}", branch_name)
-    }));
-
-    let _ = window.emit("push-log", serde_json::json!({
-        "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-        "level": "INFO",
-        "message": "正在查找远程仓库 origin..."
-    }));
-
-    let mut remote = match repo.find_remote("origin") {
-        Ok(r) => {
-            let _ = window.emit("push-log", serde_json::json!({
-                "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-                "level": "SUCCESS",
-                "message": "找到远程仓库 origin"
-            }));
-            r
-        },
-        Err(e) => {
-            let _ = window.emit("push-log", serde_json::json!({
-                "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-                "level": "ERROR",
-                "message": format!("未找到远程仓库 origin: {}", e)
-            }));
-            return Err(format!("Failed to find remote 'origin': {}", e));
+        let mut state = self.state.borrow_mut();
+        state.attempts += 1;
+        log_message("DEBUG", &format!("{}: credential callback | url={} username={:?} allowed={:?} attempt={}", 
+            op, url, username_from_url, allowed, state.attempts));
+        if state.attempts > MAX_CREDENTIAL_ATTEMPTS {
+            log_message("ERROR", &format!("{}: too many authentication attempts", op));
+            return Err(git2::Error::from_str(&format!("认证失败：已尝试 {} 次，停止重试", MAX_CREDENTIAL_ATTEMPTS)));
         }
-    };
 
-    let _ = window.emit("push-log", serde_json::json!({
-        "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-        "level": "INFO",
-        "message": "正在设置认证..."
-    }));
-
-    // 认证与 Push 选项
-    let cfg = repo.config().ok();
-    let mut callbacks = git2::RemoteCallbacks::new();
-    callbacks.credentials(move |url, username_from_url, allowed| {
-        if allowed.contains(git2::CredentialType::DEFAULT) {
+        if allowed.contains(git2::CredentialType::DEFAULT) && !state.tried_default {
+            state.tried_default = true;
+            log_message("DEBUG", &format!("{}: trying default credentials", op));
             return git2::Cred::default();
         }
+
+        let ssh_user = username_from_url.unwrap_or("git");
+        if allowed.contains(git2::CredentialType::USERNAME) {
+            return git2::Cred::username(ssh_user);
+        }
+
         if allowed.contains(git2::CredentialType::SSH_KEY) {
-            return git2::Cred::ssh_key_from_agent(username_from_url.unwrap_or("git"));
+            if !state.tried_agent {
+                state.tried_agent = true;
+                log_message("DEBUG", &format!("{}: trying SSH key from agent", op));
+                return git2::Cred::ssh_key_from_agent(ssh_user);
+            }
+            if state.ssh_keys.is_none() {
+                state.ssh_keys = Some(ssh_key_candidates(self.config.as_ref(), url_host(url).as_deref(), ssh_user));
+            }
+            while let Some(key) = state.ssh_keys.as_ref().and_then(|keys| keys.get(state.key_index)).cloned() {
+                let public_key = std::path::PathBuf::from(format!("{}.pub", key.display()));
+                let public_key = if public_key.is_file() { Some(public_key) } else { None };
+                if !state.key_tried_plain {
+                    state.key_tried_plain = true;
+                    log_message("DEBUG", &format!("{}: trying SSH key file {}", op, key.display()));
+                    return git2::Cred::ssh_key(ssh_user, public_key.as_deref(), &key, None);
+                }
+                // 无口令尝试失败，私钥可能已加密，询问一次口令
+                if !state.key_prompted && self.app_handle.is_some() {
+                    state.key_prompted = true;
+                    let attempt = state.attempts;
+                    drop(state);
+                    let response = self.prompt("passphrase", url, Some(ssh_user), Some(&key), attempt)?;
+                    state = self.state.borrow_mut();
+                    if let Some(passphrase) = response.and_then(|r| r.password) {
+                        log_message("DEBUG", &format!("{}: trying SSH key file {} with passphrase", op, key.display()));
+                        return git2::Cred::ssh_key(ssh_user, public_key.as_deref(), &key, Some(&passphrase));
+                    }
+                }
+                state.key_index += 1;
+                state.key_tried_plain = false;
+                state.key_prompted = false;
+            }
         }
+
         if allowed.contains(git2::CredentialType::USER_PASS_PLAINTEXT) {
-            if let Some(cfg) = cfg.as_ref() {
-                if let Ok(cred) = git2::Cred::credential_helper(cfg, url, username_from_url) {
-                    return Ok(cred);
+            if !state.tried_helper {
+                state.tried_helper = true;
+                log_message("DEBUG", &format!("{}: trying credential helper", op));
+                if let Some(cfg) = self.config.as_ref() {
+                    if let Ok(cred) = git2::Cred::credential_helper(cfg, url, username_from_url) {
+                        log_message("DEBUG", &format!("{}: credential helper success", op));
+                        return Ok(cred);
+                    }
+                    log_message("WARN", &format!("{}: credential helper failed", op));
+                }
+            }
+            if state.prompts < MAX_CREDENTIAL_PROMPTS && self.app_handle.is_some() {
+                state.prompts += 1;
+                let attempt = state.attempts;
+                drop(state);
+                let response = self.prompt("userpass", url, username_from_url, None, attempt)?;
+                state = self.state.borrow_mut();
+                if let Some(response) = response {
+                    let username = response.username.unwrap_or_default();
+                    let password = response.password.unwrap_or_default();
+                    state.to_save = if response.save {
+                        Some((url.to_string(), username.clone(), password.clone()))
+                    } else {
+                        None
+                    };
+                    return git2::Cred::userpass_plaintext(&username, &password);
                 }
             }
         }
+
+        log_message("ERROR", &format!("{}: no authentication method available", op));
         Err(git2::Error::from_str("No authentication method available"))
-    });
+    }
 
-    let mut push_opts = git2::PushOptions::new();
-    push_opts.remote_callbacks(callbacks);
+    // 通过事件向前端请求凭据并等待回复；没有可用窗口或超时时返回 None，用户取消时返回错误
+    fn prompt(
+        &self,
+        kind: &str,
+        url: &str,
+        username: Option<&str>,
+        key_path: Option<&Path>,
+        attempt: u32,
+    ) -> Result<Option<CredentialResponse>, git2::Error> {
+        let app_handle = match &self.app_handle {
+            Some(handle) => handle,
+            None => return Ok(None),
+        };
+        let request = CredentialRequest {
+            id: format!("{}-{}", self.op, chrono::Local::now().format("%Y%m%d%H%M%S%.6f")),
+            kind: kind.to_string(),
+            url: url.to_string(),
+            username: username.map(|u| u.to_string()),
+            key_path: key_path.map(|p| p.display().to_string()),
+            attempt,
+        };
+        let (sender, receiver) = std::sync::mpsc::channel();
+        if let Ok(mut pending) = pending_credential_requests().lock() {
+            pending.insert(request.id.clone(), sender);
+        }
+        log_message("INFO", &format!("{}: prompting for credentials | kind={} url={}", self.op, kind, url));
+        if app_handle.emit_all("credential-request", request.clone()).is_err() {
+            pending_credential_requests().lock().ok().map(|mut p| p.remove(&request.id));
+            return Ok(None);
+        }
 
-    let refspec = format!("refs/heads/{}:refs/heads/{}", branch_name, branch_name);
-    let _ = window.emit("push-log", serde_json::json!({
-        "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-        "level": "INFO",
-        "message": format!("开始推送分支 {} 到 origin...", branch_name)
-    }));
+        // 分段等待，以便操作被取消时及时返回
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(CREDENTIAL_PROMPT_TIMEOUT_SECS);
+        let response = loop {
+            match receiver.recv_timeout(std::time::Duration::from_millis(200)) {
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout)
+                    if !self.is_cancelled() && std::time::Instant::now() < deadline => continue,
+                result => break result,
+            }
+        };
+        pending_credential_requests().lock().ok().map(|mut p| p.remove(&request.id));
+        if self.is_cancelled() {
+            return Err(git2::Error::from_str(OPERATION_CANCELLED));
+        }
+        match response {
+            Ok(response) if response.cancel => {
+                log_message("WARN", &format!("{}: credential prompt cancelled", self.op));
+                Err(git2::Error::from_str("用户取消了认证"))
+            }
+            Ok(response) => Ok(Some(response)),
+            Err(_) => {
+                log_message("WARN", &format!("{}: credential prompt timed out", self.op));
+                Ok(None)
+            }
+        }
+    }
 
-    // 执行推送
-    match remote.push(&[&refspec], Some(&mut push_opts)) {
-        Ok(_) => {
-            let _ = window.emit("push-log", serde_json::json!({
-                "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-                "level": "SUCCESS",
-                "message": "推送成功！"
-            }));
-            
-            // 若本地分支没有上游，自动设置到 origin/<branch>
-            let _ = window.emit("push-log", serde_json::json!({
-                "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-                "level": "INFO",
-                "message": "正在检查上游分支设置..."
-            }));
-            
-            if let Ok(mut branch) = repo.find_branch(branch_name, git2::BranchType::Local) {
-                if branch.upstream().is_err() {
-                    if let Err(e) = branch.set_upstream(Some(&format!("origin/{}", branch_name))) {
-                        let _ = window.emit("push-log", serde_json::json!({
-                            "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-                            "level": "WARN",
-                            "message": format!("设置上游分支失败: {}", e)
-                        }));
-                    } else {
-                        let _ = window.emit("push-log", serde_json::json!({
-                            "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-                            "level": "SUCCESS",
-                            "message": format!("已设置上游分支: origin/{}", branch_name)
-                        }));
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.as_ref().map(|c| c.load(Ordering::Relaxed)).unwrap_or(false)
+    }
+
+    // 操作成功后调用：将用户要求保存的凭据写入 git 凭据助手
+    fn finish(&self) {
+        let to_save = self.state.borrow_mut().to_save.take();
+        if let Some((url, username, password)) = to_save {
+            let mut command = std::process::Command::new("git");
+            if let Some(dir) = &self.repo_dir {
+                command.current_dir(dir);
+            }
+            let child = command
+                .args(["credential", "approve"])
+                .stdin(std::process::Stdio::piped())
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .spawn();
+            match child {
+                Ok(mut child) => {
+                    if let Some(mut stdin) = child.stdin.take() {
+                        let _ = write!(stdin, "url={}\nusername={}\npassword={}\n\n", url, username, password);
+                    }
+                    match child.wait() {
+                        Ok(status) if status.success() => log_message("INFO", &format!("{}: credentials saved to credential helper", self.op)),
+                        _ => log_message("WARN", &format!("{}: failed to save credentials", self.op)),
                     }
-                } else {
-                    let _ = window.emit("push-log", serde_json::json!({
-                        "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-                        "level": "INFO",
-                        "message": "上游分支已存在"
-                    }));
                 }
+                Err(e) => log_message("WARN", &format!("{}: failed to run git credential: {}", self.op, e)),
             }
-            
-            let _ = window.emit("push-log", serde_json::json!({
-                "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-                "level": "SUCCESS",
-                "message": format!("操作完成 - 已推送到 origin/{}", branch_name)
-            }));
-            
-            Ok(format!("Successfully pushed to origin/{}", branch_name))
-        },
-        Err(e) => {
-            let url = remote.url().unwrap_or("");
-            let error_msg = format!("推送失败: {}", e);
-            let detailed_msg = format!("详细错误信息: {}", e);
-            let url_msg = format!("远程仓库URL: {}", url);
-            
-            let _ = window.emit("push-log", serde_json::json!({
-                "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-                "level": "ERROR",
-                "message": error_msg
-            }));
-            
-            let _ = window.emit("push-log", serde_json::json!({
-                "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-                "level": "ERROR",
-                "message": detailed_msg
-            }));
-            
-            let _ = window.emit("push-log", serde_json::json!({
-                "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-                "level": "ERROR",
-                "message": url_msg
-            }));
-            
-            // 根据错误类型提供更具体的建议
-            let suggestion = if e.message().contains("authentication") {
-                "建议：检查Git凭据配置，确保有推送权限"
-            } else if e.message().contains("network") || e.message().contains("timeout") {
-                "建议：检查网络连接，或尝试使用代理"
-            } else if e.message().contains("rejected") {
-                "建议：远程仓库可能已更新，请先拉取最新更改"
-            } else {
-                "建议：查看详细错误信息，或尝试使用命令行推送"
-            };
-            
-            let _ = window.emit("push-log", serde_json::json!({
-                "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-                "level": "INFO",
-                "message": suggestion
-            }));
-            
-            return Err(format!("Failed to push: {}", e));
         }
     }
 }
 
-// 推送更改 - 带日志流（保留原函数以兼容性）
-#[tauri::command]
-async fn push_changes_with_logs(repo_path: String) -> Result<Vec<(String, String, String)>, String> {
-    let mut logs = Vec::new();
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    
-    logs.push((timestamp, "INFO".to_string(), format!("push: attempt start | path={}", repo_path)));
-    
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    logs.push((timestamp, "INFO".to_string(), "正在打开仓库...".to_string()));
-    
-    let repo = match Repository::open(&repo_path) {
-        Ok(r) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "INFO".to_string(), "仓库打开成功".to_string()));
-            r
-        },
-        Err(e) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "ERROR".to_string(), format!("打开仓库失败: {}", e)));
-            return Err(format!("Failed to open repository: {}", e));
-        }
-    };
+// 网络操作进度：通过 git-progress 事件发给前端，fetch / pull / push / clone 共用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitProgress {
+    pub operation: String,
+    pub operation_id: Option<String>,   // 可传给 cancel_operation
+    pub remote: String,
+    pub phase: String,              // "receiving" | "resolving" | "packing" | "pushing" | "checkout" | "remote"
+    pub current: usize,             // 当前阶段的进度，用于通用进度条
+    pub total: usize,
+    pub received_objects: usize,
+    pub indexed_objects: usize,
+    pub total_objects: usize,
+    pub indexed_deltas: usize,
+    pub total_deltas: usize,
+    pub bytes: usize,
+    pub message: Option<String>,    // 服务端消息（remote: ...）
+}
 
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    logs.push((timestamp, "INFO".to_string(), "正在获取HEAD引用...".to_string()));
+// 两次进度事件之间的最小间隔，避免大仓库获取时事件过多
+const PROGRESS_EMIT_INTERVAL_MS: u128 = 100;
+
+// 将 transfer_progress / sideband_progress / pack_progress / push_transfer_progress 汇总成 git-progress 事件
+struct ProgressReporter {
+    app_handle: Option<tauri::AppHandle>,
+    operation: String,
+    operation_id: Option<String>,
+    cancelled: Option<Arc<AtomicBool>>,
+    remote: String,
+    last_emit: std::cell::Cell<Option<std::time::Instant>>,
+}
 
-    let head = match repo.head() {
-        Ok(h) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "INFO".to_string(), "获取HEAD引用成功".to_string()));
-            h
-        },
-        Err(e) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "ERROR".to_string(), format!("获取HEAD失败: {}", e)));
-            return Err(format!("Failed to get HEAD: {}", e));
+impl ProgressReporter {
+    fn new(app_handle: Option<tauri::AppHandle>, operation: &str, remote: &str, running: Option<&RunningOperation>) -> Self {
+        ProgressReporter {
+            app_handle,
+            operation: operation.to_string(),
+            operation_id: running.map(|o| o.id.clone()),
+            cancelled: running.map(|o| o.cancelled.clone()),
+            remote: remote.to_string(),
+            last_emit: std::cell::Cell::new(None),
         }
-    };
-    
-    let branch_name = head.shorthand().unwrap_or("main");
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    logs.push((timestamp, "INFO".to_string(), format!("当前分支: {}", branch_name)));
-
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    logs.push((timestamp, "INFO".to_string(), "正在查找远程仓库 origin...".to_string()));
+    }
 
-    let mut remote = match repo.find_remote("origin") {
-        Ok(r) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "INFO".to_string(), "找到远程仓库 origin".to_string()));
-            r
-        },
-        Err(e) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "ERROR".to_string(), format!("未找到远程仓库 origin: {}", e)));
-            return Err(format!("Failed to find remote 'origin': {}", e));
-        }
-    };
+    // 在已有的 RemoteCallbacks（通常来自 CredentialProvider::callbacks）上挂载进度回调
+    fn attach<'a>(&'a self, callbacks: &mut git2::RemoteCallbacks<'a>) {
+        callbacks.transfer_progress(move |stats| {
+            let resolving = stats.received_objects() == stats.total_objects() && stats.total_deltas() > 0;
+            let (phase, current, total) = if resolving {
+                ("resolving", stats.indexed_deltas(), stats.total_deltas())
+            } else {
+                ("receiving", stats.received_objects(), stats.total_objects())
+            };
+            let mut progress = self.progress(phase, current, total);
+            progress.received_objects = stats.received_objects();
+            progress.indexed_objects = stats.indexed_objects();
+            progress.total_objects = stats.total_objects();
+            progress.indexed_deltas = stats.indexed_deltas();
+            progress.total_deltas = stats.total_deltas();
+            progress.bytes = stats.received_bytes();
+            self.emit(progress);
+            !self.is_cancelled()
+        });
+        callbacks.sideband_progress(move |data| {
+            let text = String::from_utf8_lossy(data);
+            for line in text.split(['\r', '\n']).map(|l| l.trim()).filter(|l| !l.is_empty()) {
+                log_message("DEBUG", &format!("{}: remote: {}", self.operation, line));
+                let mut progress = self.progress("remote", 0, 0);
+                progress.message = Some(line.to_string());
+                self.send(progress);
+            }
+            !self.is_cancelled()
+        });
+        callbacks.pack_progress(move |_stage, current, total| {
+            self.emit(self.progress("packing", current, total));
+        });
+        callbacks.push_transfer_progress(move |current, total, bytes| {
+            let mut progress = self.progress("pushing", current, total);
+            progress.bytes = bytes;
+            self.emit(progress);
+        });
+        // 推送的传输回调无法中止，在协商阶段检查取消
+        callbacks.push_negotiation(move |_updates| {
+            if self.is_cancelled() {
+                Err(git2::Error::from_str(OPERATION_CANCELLED))
+            } else {
+                Ok(())
+            }
+        });
+    }
 
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    logs.push((timestamp, "INFO".to_string(), "正在设置认证...".to_string()));
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.as_ref().map(|c| c.load(Ordering::Relaxed)).unwrap_or(false)
+    }
 
-    // 认证与 Push 选项
-    let cfg = repo.config().ok();
-    let mut callbacks = git2::RemoteCallbacks::new();
-    callbacks.credentials(move |url, username_from_url, allowed| {
-        if allowed.contains(git2::CredentialType::DEFAULT) {
-            return git2::Cred::default();
-        }
-        if allowed.contains(git2::CredentialType::SSH_KEY) {
-            return git2::Cred::ssh_key_from_agent(username_from_url.unwrap_or("git"));
-        }
-        if allowed.contains(git2::CredentialType::USER_PASS_PLAINTEXT) {
-            if let Some(cfg) = cfg.as_ref() {
-                if let Ok(cred) = git2::Cred::credential_helper(cfg, url, username_from_url) {
-                    return Ok(cred);
-                }
-            }
+    fn progress(&self, phase: &str, current: usize, total: usize) -> GitProgress {
+        GitProgress {
+            operation: self.operation.clone(),
+            operation_id: self.operation_id.clone(),
+            remote: self.remote.clone(),
+            phase: phase.to_string(),
+            current,
+            total,
+            received_objects: 0,
+            indexed_objects: 0,
+            total_objects: 0,
+            indexed_deltas: 0,
+            total_deltas: 0,
+            bytes: 0,
+            message: None,
         }
-        Err(git2::Error::from_str("No authentication method available"))
-    });
-
-    let mut push_opts = git2::PushOptions::new();
-    push_opts.remote_callbacks(callbacks);
+    }
 
-    let refspec = format!("refs/heads/{}:refs/heads/{}", branch_name, branch_name);
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    logs.push((timestamp, "INFO".to_string(), format!("开始推送分支 {} 到 origin...", branch_name)));
+    // 按间隔节流发送；阶段完成时总是发送
+    fn emit(&self, progress: GitProgress) {
+        let now = std::time::Instant::now();
+        let due = match self.last_emit.get() {
+            Some(last) => now.duration_since(last).as_millis() >= PROGRESS_EMIT_INTERVAL_MS,
+            None => true,
+        };
+        if due || progress.current >= progress.total {
+            self.last_emit.set(Some(now));
+            self.send(progress);
+        }
+    }
 
-    // 执行推送
-    match remote.push(&[&refspec], Some(&mut push_opts)) {
-        Ok(_) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "INFO".to_string(), "推送成功！".to_string()));
-            
-            // 若本地分支没有上游，自动设置到 origin/<branch>
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "INFO".to_string(), "正在检查上游分支设置...".to_string()));
-            
-            if let Ok(mut branch) = repo.find_branch(branch_name, git2::BranchType::Local) {
-                if branch.upstream().is_err() {
-                    if let Err(e) = branch.set_upstream(Some(&format!("origin/{}", branch_name))) {
-                        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-                        logs.push((timestamp, "WARN".to_string(), format!("设置上游分支失败: {}", e)));
-                    } else {
-                        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-                        logs.push((timestamp, "INFO".to_string(), format!("已设置上游分支: origin/{}", branch_name)));
-                    }
-                } else {
-                    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-                    logs.push((timestamp, "INFO".to_string(), "上游分支已存在".to_string()));
-                }
-            }
-            
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "SUCCESS".to_string(), format!("操作完成 - 已推送到 origin/{}", branch_name)));
-            
-            Ok(logs)
-        },
-        Err(e) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "ERROR".to_string(), format!("推送失败: {}", e)));
-            
-            let url = remote.url().unwrap_or("");
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "ERROR".to_string(), format!("远程仓库URL: {}", url)));
-            
-            return Err(format!("Failed to push: {}", e));
+    fn send(&self, progress: GitProgress) {
+        if let Some(app_handle) = &self.app_handle {
+            let _ = app_handle.emit_all("git-progress", progress);
         }
     }
 }
 
-// Git诊断功能
-#[tauri::command]
-async fn git_diagnostics(repo_path: String) -> Result<Vec<(String, String, String)>, String> {
-    let mut logs = Vec::new();
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    
-    logs.push((timestamp, "INFO".to_string(), "开始Git诊断...".to_string()));
-    
-    // 检查仓库状态
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    logs.push((timestamp, "INFO".to_string(), "检查仓库状态...".to_string()));
-    
-    let repo = match Repository::open(&repo_path) {
-        Ok(r) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "SUCCESS".to_string(), "仓库打开成功".to_string()));
-            r
-        },
-        Err(e) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "ERROR".to_string(), format!("仓库打开失败: {}", e)));
-            return Err(format!("Failed to open repository: {}", e));
-        }
-    };
-    
-    // 检查远程仓库
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    logs.push((timestamp, "INFO".to_string(), "检查远程仓库配置...".to_string()));
-    
-    match repo.find_remote("origin") {
-        Ok(remote) => {
-            let url = remote.url().unwrap_or("未设置");
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "SUCCESS".to_string(), format!("远程仓库URL: {}", url)));
-        },
-        Err(e) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "ERROR".to_string(), format!("未找到远程仓库 origin: {}", e)));
+// 补全远程分支引用名：main → refs/heads/main
+fn full_branch_ref(name: &str) -> String {
+    if name.starts_with("refs/") {
+        name.to_string()
+    } else {
+        format!("refs/heads/{}", name)
+    }
+}
+
+// 逐个引用记录推送结果，保留被拒绝的原因
+fn record_push_results<'a>(callbacks: &mut git2::RemoteCallbacks<'a>, results: &'a std::cell::RefCell<Vec<PushRefResult>>) {
+    callbacks.push_update_reference(move |refname, status| {
+        results.borrow_mut().push(PushRefResult {
+            refname: refname.to_string(),
+            status: if status.is_some() { "rejected" } else { "ok" }.to_string(),
+            message: status.map(|s| s.to_string()),
+        });
+        Ok(())
+    });
+}
+
+// 被远程拒绝的引用及原因，全部成功时为 None
+fn push_rejections(results: &[PushRefResult]) -> Option<String> {
+    let failures: Vec<String> = results.iter()
+        .filter(|r| r.status != "ok")
+        .map(|r| format!("{} ({})", r.refname, r.message.as_deref().unwrap_or("rejected")))
+        .collect();
+    if failures.is_empty() {
+        None
+    } else {
+        Some(failures.join("; "))
+    }
+}
+
+// 推送更改（支持认证与自动设置上游）
+#[tauri::command]
+async fn push_changes(
+    repo_path: String,
+    remote: Option<String>,
+    options: Option<PushRequest>,
+    operation_id: Option<String>,
+    cache: tauri::State<'_, RepoCache>,
+    app_handle: tauri::AppHandle,
+) -> Result<PushSummary, String> {
+    let cache = cache.inner().clone();
+    run_blocking(move || {
+        let operation = RunningOperation::begin("push", operation_id);
+        let options = options.unwrap_or_default();
+        log_message("INFO", &format!("push: attempt start | path={} options={:?}", repo_path, options));
+        cache.with(&repo_path, |repo| {
+            let head = match repo.head() {
+                Ok(h) => h,
+                Err(e) => {
+                    log_message("ERROR", &format!("push: get HEAD failed: {}", e));
+                    return Err(format!("Failed to get HEAD: {}", e));
+                }
+            };
+            let current_branch = head.shorthand().unwrap_or("main").to_string();
+            let remote_name = resolve_remote_name(repo, remote.as_deref());
+            let credentials = CredentialProvider::new(repo, "push", Some(app_handle.clone()), Some(&operation));
+
+            let mut remote = match repo.find_remote(&remote_name) {
+                Ok(r) => r,
+                Err(e) => {
+                    log_message("ERROR", &format!("push: find remote '{}' failed: {}", remote_name, e));
+                    return Err(format!("Failed to find remote '{}': {}", remote_name, e));
+                }
+            };
+
+            // (本地分支, 远程引用) 列表
+            // 未指定分支时推送当前分支；仅推送标签 / 删除 / 自定义 refspec 时除外
+            let only_other_refs = !options.delete.is_empty() || !options.refspecs.is_empty() || options.tags;
+            let branches = if options.branches.is_empty() && (options.remote_branch.is_some() || !only_other_refs) {
+                vec![current_branch.clone()]
+            } else {
+                options.branches.clone()
+            };
+            if options.remote_branch.is_some() && branches.len() != 1 {
+                return Err("指定远程分支名时只能推送一个分支".to_string());
+            }
+            let targets: Vec<(String, String)> = branches.iter()
+                .map(|b| {
+                    let dst = options.remote_branch.as_deref().unwrap_or(b);
+                    (b.clone(), full_branch_ref(dst))
+                })
+                .collect();
+
+            let mut rejected: Vec<PushRefResult> = Vec::new();
+            let mut refspecs: Vec<String> = Vec::new();
+
+            // force-with-lease：推送前确认远程分支仍指向预期的提交
+            // （libgit2 不支持原子的 lease 检查，检查与推送之间仍存在极小的竞争窗口）
+            if options.force_with_lease {
+                if options.expected.is_some() && targets.len() != 1 {
+                    return Err("指定预期提交时只能推送一个分支".to_string());
+                }
+                remote.connect_auth(git2::Direction::Push, Some(credentials.callbacks()), None)
+                    .map_err(|e| operation.or_cancelled(format!("Failed to connect to remote '{}': {}", remote_name, e)))?;
+                let remote_tips: Vec<(String, Oid)> = remote.list()
+                    .map_err(|e| format!("Failed to list remote refs: {}", e))?
+                    .iter()
+                    .map(|head| (head.name().to_string(), head.oid()))
+                    .collect();
+                remote.disconnect().ok();
+
+                for (branch, dst) in &targets {
+                    let expected = match &options.expected {
+                        Some(rev) => Some(repo.revparse_single(rev)
+                            .map_err(|e| format!("Failed to resolve expected commit '{}': {}", rev, e))?
+                            .id()),
+                        None => {
+                            let short = dst.strip_prefix("refs/heads/").unwrap_or(dst);
+                            repo.refname_to_id(&format!("refs/remotes/{}/{}", remote_name, short)).ok()
+                        }
+                    };
+                    let actual = remote_tips.iter().find(|(name, _)| name == dst).map(|(_, oid)| *oid);
+                    if actual != expected {
+                        log_message("WARN", &format!("push: lease check failed | ref={} expected={:?} actual={:?}", dst, expected, actual));
+                        rejected.push(PushRefResult {
+                            refname: dst.clone(),
+                            status: "rejected".to_string(),
+                            message: Some(format!(
+                                "stale info: remote is at {}, expected {}",
+                                actual.map(|o| o.to_string()).unwrap_or_else(|| "(none)".to_string()),
+                                expected.map(|o| o.to_string()).unwrap_or_else(|| "(none)".to_string())
+                            )),
+                        });
+                        continue;
+                    }
+                    refspecs.push(format!("+{}:{}", full_branch_ref(branch), dst));
+                }
+            } else {
+                for (branch, dst) in &targets {
+                    refspecs.push(format!("{}:{}", full_branch_ref(branch), dst));
+                }
+            }
+            if options.tags {
+                // libgit2 推送不支持通配 refspec，逐个展开标签
+                let tag_names = repo.tag_names(None)
+                    .map_err(|e| format!("Failed to list tags: {}", e))?;
+                for tag in tag_names.iter().flatten() {
+                    refspecs.push(format!("refs/tags/{}:refs/tags/{}", tag, tag));
+                }
+            }
+            for name in &options.delete {
+                refspecs.push(format!(":{}", full_branch_ref(name)));
+            }
+            refspecs.extend(options.refspecs.iter().cloned());
+
+            let ref_results = std::cell::RefCell::new(Vec::new());
+            if !refspecs.is_empty() {
+                let progress = ProgressReporter::new(Some(app_handle.clone()), "push", &remote_name, Some(&operation));
+                let mut callbacks = credentials.callbacks();
+                progress.attach(&mut callbacks);
+                record_push_results(&mut callbacks, &ref_results);
+
+                let mut push_opts = git2::PushOptions::new();
+                push_opts.remote_callbacks(callbacks);
+
+                let push_result = remote.push(&refspecs, Some(&mut push_opts));
+                drop(push_opts);
+                if let Err(e) = push_result {
+                    let url = remote.url().unwrap_or("");
+                    log_message("ERROR", &format!("push: git push failed: {} | url={} refspecs={:?}", e, url, refspecs));
+                    let log_path = get_config_dir().join("logs").join("gitlite.log");
+                    return Err(operation.or_cancelled(format!("Failed to push: {} (see log: {})", e, log_path.display())));
+                }
+                credentials.finish();
+            }
+            let mut refs = ref_results.into_inner();
+            refs.extend(rejected);
+
+            // 若本地分支没有上游，自动设置到 <remote>/<branch>
+            for (branch, dst) in &targets {
+                let pushed = refs.iter().any(|r| &r.refname == dst && r.status == "ok");
+                if !pushed {
+                    continue;
+                }
+                if let Ok(mut local) = repo.find_branch(branch, git2::BranchType::Local) {
+                    let wanted = options.set_upstream.unwrap_or_else(|| local.upstream().is_err());
+                    if wanted {
+                        let upstream = format!("{}/{}", remote_name, dst.strip_prefix("refs/heads/").unwrap_or(dst));
+                        if let Err(e) = local.set_upstream(Some(&upstream)) {
+                            log_message("WARN", &format!("push: set upstream failed but push succeeded: {}", e));
+                        }
+                    }
+                }
+            }
+
+            // 部分引用被拒绝不视为命令失败，由前端根据 refs 展示每个引用的结果
+            let message = match push_rejections(&refs) {
+                None => {
+                    log_message("INFO", &format!("push: success | remote={} refspecs={:?}", remote_name, refspecs));
+                    format!("Successfully pushed {} ref(s) to {}", refs.len(), remote_name)
+                }
+                Some(rejections) => {
+                    let ok = refs.iter().filter(|r| r.status == "ok").count();
+                    log_message("WARN", &format!("push: rejected | remote={} refs={}", remote_name, rejections));
+                    format!("Pushed {} of {} ref(s) to {}, rejected: {}", ok, refs.len(), remote_name, rejections)
+                }
+            };
+            Ok(PushSummary {
+                message,
+                remote: remote_name,
+                refs,
+            })
+        })
+    }).await
+}
+
+// 解析拉取模式：显式参数优先，其次 branch.<name>.rebase / pull.rebase，再次 pull.ff=only，默认 merge
+fn resolve_pull_mode(repo: &Repository, branch_name: &str, mode: Option<&str>) -> Result<String, String> {
+    if let Some(mode) = mode {
+        return match mode {
+            "merge" | "rebase" | "ff-only" => Ok(mode.to_string()),
+            other => Err(format!("不支持的拉取模式: {}，只允许 merge / rebase / ff-only", other)),
+        };
+    }
+
+    let config = repo.config()
+        .map_err(|e| format!("Failed to get repository config: {}", e))?;
+    let rebase_setting = config.get_string(&format!("branch.{}.rebase", branch_name))
+        .or_else(|_| config.get_string("pull.rebase"))
+        .ok();
+    if let Some(value) = rebase_setting {
+        match value.to_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => return Ok("rebase".to_string()),
+            // 保留合并提交与交互式变基无法用普通变基代替，否则会改写历史中的合并提交
+            "merges" | "m" | "preserve" | "p" | "interactive" | "i" => {
+                return Err(format!(
+                    "不支持的 pull.rebase 配置: {}，请在拉取时显式指定 merge / rebase / ff-only",
+                    value
+                ));
+            }
+            _ => {}
         }
     }
-    
-    // 检查Git配置
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    logs.push((timestamp, "INFO".to_string(), "检查Git配置...".to_string()));
-    
-    if let Ok(config) = repo.config() {
-        // 检查用户配置
-        if let Ok(name) = config.get_string("user.name") {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "SUCCESS".to_string(), format!("用户名: {}", name)));
+    if config.get_string("pull.ff").map(|v| v == "only").unwrap_or(false) {
+        return Ok("ff-only".to_string());
+    }
+    Ok("merge".to_string())
+}
+
+// 未显式指定时，按 rebase.autoStash / merge.autoStash 配置决定是否自动贮藏
+fn config_autostash(repo: &Repository, mode: &str) -> bool {
+    let key = if mode == "rebase" { "rebase.autoStash" } else { "merge.autoStash" };
+    repo.config()
+        .and_then(|c| c.get_bool(key))
+        .unwrap_or(false)
+}
+
+// 是否存在未提交的已跟踪文件改动（暂存区或工作区，不含未跟踪文件）
+fn has_local_changes(repo: &Repository) -> Result<bool, String> {
+    let mut status_options = git2::StatusOptions::new();
+    status_options.include_untracked(false).include_ignored(false);
+    let statuses = repo.statuses(Some(&mut status_options))
+        .map_err(|e| format!("Failed to get statuses: {}", e))?;
+    Ok(statuses.iter().any(|entry| entry.status() != git2::Status::CURRENT))
+}
+
+// 自动贮藏本地改动，返回贮藏提交
+fn save_autostash(repo_dir: &Path) -> Result<Oid, String> {
+    let mut repo = Repository::open(repo_dir)
+        .map_err(|e| format!("Failed to open repository: {}", e))?;
+    let signature = get_signature(&repo)?;
+    repo.stash_save(&signature, "autostash", None)
+        .map_err(|e| format!("Failed to autostash: {}", e))
+}
+
+// 恢复自动贮藏：成功则删除该贮藏，失败（如冲突）时保留在贮藏列表中
+fn pop_autostash(repo_dir: &Path, stash_oid: Oid) -> Result<String, String> {
+    let mut repo = Repository::open(repo_dir)
+        .map_err(|e| format!("Failed to open repository: {}", e))?;
+    let mut stash_index = None;
+    repo.stash_foreach(|index, _message, oid| {
+        if *oid == stash_oid {
+            stash_index = Some(index);
+            false
         } else {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "WARN".to_string(), "未设置用户名".to_string()));
+            true
         }
-        
-        if let Ok(email) = config.get_string("user.email") {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "SUCCESS".to_string(), format!("邮箱: {}", email)));
-        } else {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "WARN".to_string(), "未设置邮箱".to_string()));
+    }).map_err(|e| format!("Failed to find stash: {}", e))?;
+
+    let index = stash_index.ok_or_else(|| format!("Autostash {} not found", stash_oid))?;
+    let mut options = git2::StashApplyOptions::new();
+    options.reinstantiate_index();
+    repo.stash_pop(index, Some(&mut options))
+        .map_err(|e| format!("自动贮藏恢复失败，已保留在贮藏列表中（{}）: {}", stash_oid, e))?;
+    Ok(format!("已恢复自动贮藏 {}", stash_oid))
+}
+
+// 将已获取的上游分支整合到当前分支（mode: merge / rebase / ff-only），返回结果摘要
+fn integrate_pulled_changes(
+    repo: &Repository,
+    branch_name: &str,
+    upstream_ref: &str,
+    mode: &str,
+    autostash: bool,
+    log: &mut dyn FnMut(&str, &str),
+) -> Result<String, String> {
+    let upstream_oid = repo.refname_to_id(upstream_ref)
+        .map_err(|e| format!("Failed to get remote branch reference: {}", e))?;
+    let (analysis, _) = {
+        let annotated = repo.find_annotated_commit(upstream_oid)
+            .map_err(|e| format!("Failed to find remote commit: {}", e))?;
+        repo.merge_analysis(&[&annotated])
+            .map_err(|e| format!("Failed to analyze merge: {}", e))?
+    };
+
+    if analysis.is_up_to_date() {
+        log("INFO", "本地分支已是最新状态");
+        return Ok("Already up to date".to_string());
+    }
+    if mode == "ff-only" && !analysis.is_fast_forward() {
+        log("ERROR", "本地与远程分支已分叉，ff-only 模式无法快进");
+        return Err("Cannot fast-forward: local and remote branches have diverged (ff-only)".to_string());
+    }
+
+    // 检查本地改动，必要时自动贮藏
+    let repo_dir = repo.path().to_path_buf();
+    let mut stash_oid = None;
+    if has_local_changes(repo)? {
+        if !autostash {
+            log("ERROR", "无法拉取：存在未提交的更改，请先提交或贮藏");
+            return Err("Cannot pull: You have uncommitted changes. Please commit or stash them first.".to_string());
         }
-        
-        // 检查凭据配置
-        if let Ok(helper) = config.get_string("credential.helper") {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "SUCCESS".to_string(), format!("凭据助手: {}", helper)));
-        } else {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "WARN".to_string(), "未配置凭据助手".to_string()));
+        let oid = save_autostash(&repo_dir)?;
+        log("INFO", &format!("已自动贮藏本地更改: {}", oid));
+        stash_oid = Some(oid);
+    }
+
+    let result = if analysis.is_fast_forward() {
+        log("INFO", "检测到快进合并，执行快进操作...");
+        fast_forward_branch(repo, branch_name, upstream_oid)
+            .map(|_| "Successfully pulled (fast-forward)".to_string())
+    } else if mode == "rebase" {
+        log("INFO", "正在将本地提交变基到远程分支...");
+        match begin_rebase(repo, upstream_ref, None, None, stash_oid.map(|o| o.to_string())) {
+            Ok(status) if status.in_progress => {
+                // 自动贮藏已记录在变基状态中，变基完成或中止后再恢复
+                log("WARN", &status.message);
+                return Err(format!(
+                    "Rebase stopped: {}. Conflicted files: [{}]. Resolve them and continue the rebase.",
+                    status.message,
+                    status.conflicted_files.join(", ")
+                ));
+            }
+            Ok(_) => {
+                // 变基完成时 run_rebase_steps 已恢复自动贮藏
+                stash_oid = None;
+                Ok("Successfully pulled and rebased".to_string())
+            }
+            // 变基未能开始：自动贮藏仍在贮藏列表中，由下方统一报告
+            Err(e) => Err(e),
         }
     } else {
-        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-        logs.push((timestamp, "ERROR".to_string(), "无法读取Git配置".to_string()));
+        log("INFO", "检测到需要合并提交，开始合并操作...");
+        let upstream_name = upstream_ref.strip_prefix("refs/remotes/").unwrap_or(upstream_ref);
+        merge_upstream_commit(repo, upstream_name, upstream_oid, log)
+    };
+
+    match result {
+        Ok(summary) => {
+            if let Some(oid) = stash_oid {
+                match pop_autostash(&repo_dir, oid) {
+                    Ok(msg) => log("INFO", &msg),
+                    Err(e) => {
+                        log("WARN", &e);
+                        return Ok(format!("{} ({})", summary, e));
+                    }
+                }
+            }
+            log("INFO", &summary);
+            Ok(summary)
+        }
+        Err(e) => match stash_oid {
+            Some(oid) => {
+                let notice = format!("本地更改仍保存在自动贮藏 {} 中", oid);
+                log("WARN", &notice);
+                Err(format!("{} ({})", e, notice))
+            }
+            None => Err(e),
+        },
+    }
+}
+
+// 快进当前分支到目标提交，并同步工作区
+fn fast_forward_branch(repo: &Repository, branch_name: &str, target_oid: Oid) -> Result<(), String> {
+    let target = repo.find_object(target_oid, None)
+        .map_err(|e| format!("Failed to find target commit: {}", e))?;
+    let mut checkout = git2::build::CheckoutBuilder::new();
+    checkout.safe();
+    repo.checkout_tree(&target, Some(&mut checkout))
+        .map_err(|e| format!("Failed to checkout fast-forward target: {}", e))?;
+
+    let mut reference = repo.find_reference(&format!("refs/heads/{}", branch_name))
+        .map_err(|e| format!("Failed to find branch reference: {}", e))?;
+    reference.set_target(target_oid, "pull: Fast-forward")
+        .map_err(|e| format!("Failed to fast-forward: {}", e))?;
+    Ok(())
+}
+
+// 将上游提交合并到当前分支；有冲突时保留 MERGE_HEAD，由用户解决后提交
+fn merge_upstream_commit(
+    repo: &Repository,
+    upstream_name: &str,
+    upstream_oid: Oid,
+    log: &mut dyn FnMut(&str, &str),
+) -> Result<String, String> {
+    let annotated = repo.find_annotated_commit(upstream_oid)
+        .map_err(|e| format!("Failed to find remote commit: {}", e))?;
+    let mut checkout = git2::build::CheckoutBuilder::new();
+    checkout.safe().allow_conflicts(true).conflict_style_merge(true);
+    repo.merge(&[&annotated], None, Some(&mut checkout))
+        .map_err(|e| format!("Failed to merge: {}", e))?;
+
+    let mut index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
+    let conflicts = conflicted_paths(&index);
+    if !conflicts.is_empty() {
+        log("ERROR", &format!("合并冲突: {}", conflicts.join(", ")));
+        return Err(format!(
+            "Merge conflicts in: [{}]. Resolve them and commit to complete the merge.",
+            conflicts.join(", ")
+        ));
     }
+
+    let merge_commit_id = commit_merge_result(repo, &mut index, upstream_oid, &format!("Merge branch '{}'", upstream_name))?;
+    Ok(format!("Successfully pulled and merged (commit: {})", merge_commit_id))
+}
+
+// 用合并后的暂存区创建合并提交（父提交为 HEAD 与被合并的提交），并清理合并状态
+fn commit_merge_result(repo: &Repository, index: &mut git2::Index, theirs_oid: Oid, message: &str) -> Result<Oid, String> {
+    let tree_id = write_index_tree(repo, index).map_err(|e| format!("Failed to write merge tree: {}", e))?;
+    let merge_tree = repo.find_tree(tree_id)
+        .map_err(|e| format!("Failed to find merge tree: {}", e))?;
+    let local_commit = repo.head()
+        .and_then(|h| h.peel_to_commit())
+        .map_err(|e| format!("Failed to find local commit: {}", e))?;
+    let their_commit = repo.find_commit(theirs_oid)
+        .map_err(|e| format!("Failed to find merged commit: {}", e))?;
+    let signature = get_signature(repo)?;
+
+    let merge_commit_id = repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        message,
+        &merge_tree,
+        &[&local_commit, &their_commit],
+    ).map_err(|e| format!("Failed to create merge commit: {}", e))?;
+    repo.cleanup_state()
+        .map_err(|e| format!("Failed to clean up merge state: {}", e))?;
+    Ok(merge_commit_id)
+}
+
+// 拉取更改
+#[tauri::command]
+async fn pull_changes(
+    repo_path: String,
+    remote: Option<String>,
+    mode: Option<String>,
+    autostash: Option<bool>,
+    operation_id: Option<String>,
+    cache: tauri::State<'_, RepoCache>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    let cache = cache.inner().clone();
+    run_blocking(move || {
+        let operation = RunningOperation::begin("pull", operation_id);
+        log_message("INFO", &format!("pull: attempt start | path={}", repo_path));
+        cache.with(&repo_path, |repo| {
+            let head = match repo.head() {
+                Ok(h) => h,
+                Err(e) => {
+                    log_message("ERROR", &format!("pull: get HEAD failed: {}", e));
+                    return Err(format!("Failed to get HEAD: {}", e));
+                }
+            };
+            let branch_name = head.shorthand().unwrap_or("main");
+            let remote_name = resolve_remote_name(repo, remote.as_deref());
+            let mode = resolve_pull_mode(repo, branch_name, mode.as_deref())?;
+            let autostash = autostash.unwrap_or_else(|| config_autostash(repo, &mode));
+            log_message("INFO", &format!("pull: mode={} autostash={} | branch={}", mode, autostash, branch_name));
+
+            let mut remote = match repo.find_remote(&remote_name) {
+                Ok(r) => r,
+                Err(e) => {
+                    log_message("ERROR", &format!("pull: find remote '{}' failed: {}", remote_name, e));
+                    return Err(format!("Failed to find remote '{}': {}", remote_name, e));
+                }
+            };
+
+            // 认证与 Fetch 选项
+            let credentials = CredentialProvider::new(repo, "pull", Some(app_handle.clone()), Some(&operation));
+            let progress = ProgressReporter::new(Some(app_handle.clone()), "pull", &remote_name, Some(&operation));
+            let mut callbacks = credentials.callbacks();
+            progress.attach(&mut callbacks);
+
+            let mut fetch_opts = git2::FetchOptions::new();
+            fetch_opts.remote_callbacks(callbacks);
+
+            // 首先执行 fetch
+            let refspec = format!("refs/heads/{}:refs/remotes/{}/{}", branch_name, remote_name, branch_name);
+            if let Err(e) = remote.fetch(&[&refspec], Some(&mut fetch_opts), None) {
+                let url = remote.url().unwrap_or("");
+                log_message("ERROR", &format!("pull: git fetch failed: {} | url={} refspec={} branch={}", e, url, refspec, branch_name));
+                let log_path = get_config_dir().join("logs").join("gitlite.log");
+                return Err(operation.or_cancelled(format!("Failed to fetch: {} (see log: {})", e, log_path.display())));
+            }
+            credentials.finish();
+
+            // 按拉取模式整合远程更改
+            let remote_branch_ref = format!("refs/remotes/{}/{}", remote_name, branch_name);
+            let mut log = |level: &str, message: &str| {
+                log_message(level, &format!("pull: {} | branch={}", message, branch_name));
+            };
+            integrate_pulled_changes(repo, branch_name, &remote_branch_ref, &mode, autostash, &mut log)
+        })
+    }).await
+}
+
+// 获取（fetch）结果中的单个引用变化
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RefUpdate {
+    pub remote: String,
+    pub refname: String,
+    pub old_id: Option<String>,
+    pub new_id: Option<String>,
+    pub kind: String, // "new", "updated", "forced", "pruned"
+}
+
+// 获取操作的结构化结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FetchSummary {
+    pub remotes: Vec<String>,
+    pub failed_remotes: Vec<String>,
+    pub updated: Vec<RefUpdate>,
+    pub logs: Vec<(String, String, String)>,
+}
+
+// 获取选项（均可省略，默认获取当前分支的远程）
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FetchRequest {
+    pub all_remotes: bool,     // 获取所有远程
+    pub prune: bool,           // 删除远端已不存在的远程跟踪分支
+    pub tags: Option<String>,  // "auto"（默认，跟随获取到的提交）/ "all" / "none"
+    pub depth: Option<i32>,    // 浅获取深度
+    pub unshallow: bool,       // 转为完整历史
+}
+
+// 获取远程更改（不合并）- 带日志流
+#[tauri::command]
+async fn fetch_changes_with_logs(
+    repo_path: String,
+    remote: Option<String>,
+    options: Option<FetchRequest>,
+    operation_id: Option<String>,
+    cache: tauri::State<'_, RepoCache>,
+    app_handle: tauri::AppHandle,
+) -> Result<FetchSummary, String> {
+    let cache = cache.inner().clone();
+    run_blocking(move || {
+        let operation = RunningOperation::begin("fetch", operation_id);
+        let options = options.unwrap_or_default();
+        let mut logs = Vec::new();
+        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+    
+        logs.push((timestamp, "INFO".to_string(), format!("fetch: attempt start | path={}", repo_path)));
+    
+        let download_tags = match options.tags.as_deref().unwrap_or("auto") {
+            "auto" => git2::AutotagOption::Auto,
+            "all" => git2::AutotagOption::All,
+            "none" => git2::AutotagOption::None,
+            other => return Err(format!("不支持的标签获取方式: {}，只允许 auto / all / none", other)),
+        };
     
-    // 检查当前分支
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    logs.push((timestamp, "INFO".to_string(), "检查当前分支...".to_string()));
+        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+        logs.push((timestamp, "INFO".to_string(), "正在打开仓库...".to_string()));
     
-    match repo.head() {
-        Ok(head) => {
-            let branch_name = head.shorthand().unwrap_or("未知");
+        cache.with(&repo_path, |repo| {
             let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "SUCCESS".to_string(), format!("当前分支: {}", branch_name)));
-            
-            // 检查上游分支
-            if let Ok(branch) = repo.find_branch(branch_name, git2::BranchType::Local) {
-                match branch.upstream() {
-                    Ok(upstream) => {
-                        let upstream_name = upstream.name().unwrap_or(Some("未知")).unwrap_or("未知");
+            logs.push((timestamp, "INFO".to_string(), "仓库打开成功".to_string()));
+
+            let remote_names: Vec<String> = if options.all_remotes {
+                repo.remotes()
+                    .map_err(|e| format!("Failed to list remotes: {}", e))?
+                    .iter()
+                    .flatten()
+                    .map(|n| n.to_string())
+                    .collect()
+            } else {
+                vec![resolve_remote_name(repo, remote.as_deref())]
+            };
+            if remote_names.is_empty() {
+                return Err("仓库没有配置任何远程仓库".to_string());
+            }
+
+            let mut summary = FetchSummary {
+                remotes: remote_names.clone(),
+                failed_remotes: Vec::new(),
+                updated: Vec::new(),
+                logs: Vec::new(),
+            };
+
+            for remote_name in &remote_names {
+                let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                logs.push((timestamp, "INFO".to_string(), format!("正在查找远程仓库 {}...", remote_name)));
+
+                let mut remote = match repo.find_remote(remote_name) {
+                    Ok(r) => {
+                        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                        logs.push((timestamp, "INFO".to_string(), format!("找到远程仓库 {}", remote_name)));
+                        r
+                    },
+                    Err(e) => {
+                        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                        logs.push((timestamp, "ERROR".to_string(), format!("未找到远程仓库 {}: {}", remote_name, e)));
+                        if remote_names.len() == 1 {
+                            return Err(format!("Failed to find remote '{}': {}", remote_name, e));
+                        }
+                        summary.failed_remotes.push(remote_name.clone());
+                        continue;
+                    }
+                };
+
+                let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                logs.push((timestamp, "INFO".to_string(), "正在设置认证...".to_string()));
+
+                // 认证与 Fetch 选项
+                let credentials = CredentialProvider::new(repo, "fetch", Some(app_handle.clone()), Some(&operation));
+                let ref_changes = std::cell::RefCell::new(Vec::new());
+                let progress = ProgressReporter::new(Some(app_handle.clone()), "fetch", remote_name, Some(&operation));
+                let mut callbacks = credentials.callbacks();
+                progress.attach(&mut callbacks);
+                // 记录每个被更新 / 新建 / 删除的引用
+                callbacks.update_tips(|refname, old, new| {
+                    ref_changes.borrow_mut().push((refname.to_string(), old, new));
+                    true
+                });
+
+                let mut fetch_opts = git2::FetchOptions::new();
+                fetch_opts.remote_callbacks(callbacks);
+                fetch_opts.download_tags(download_tags);
+                if options.prune {
+                    fetch_opts.prune(git2::FetchPrune::On);
+                }
+                if options.unshallow {
+                    fetch_opts.depth(i32::MAX);
+                } else if let Some(depth) = options.depth.filter(|d| *d > 0) {
+                    fetch_opts.depth(depth);
+                }
+
+                let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                logs.push((timestamp, "INFO".to_string(), format!("开始获取远程更改: {}...", remote_name)));
+
+                // 执行 fetch 操作（使用远程配置的默认 refspec）
+                let result = remote.fetch::<&str>(&[], Some(&mut fetch_opts), None);
+                drop(fetch_opts);
+                match result {
+                    Ok(_) => {
+                        credentials.finish();
+                        for (refname, old, new) in ref_changes.into_inner() {
+                            let kind = if old.is_zero() {
+                                "new"
+                            } else if new.is_zero() {
+                                "pruned"
+                            } else if repo.graph_descendant_of(new, old).unwrap_or(false) {
+                                "updated"
+                            } else {
+                                "forced"
+                            };
+                            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                            logs.push((timestamp, "INFO".to_string(), format!("{} {} {:.7} -> {:.7}", kind, refname, old, new)));
+                            summary.updated.push(RefUpdate {
+                                remote: remote_name.clone(),
+                                refname,
+                                old_id: (!old.is_zero()).then(|| old.to_string()),
+                                new_id: (!new.is_zero()).then(|| new.to_string()),
+                                kind: kind.to_string(),
+                            });
+                        }
                         let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-                        logs.push((timestamp, "SUCCESS".to_string(), format!("上游分支: {}", upstream_name)));
+                        logs.push((timestamp, "INFO".to_string(), format!("获取 {} 成功！", remote_name)));
                     },
-                    Err(_) => {
+                    Err(e) => {
                         let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-                        logs.push((timestamp, "WARN".to_string(), "未设置上游分支".to_string()));
+                        logs.push((timestamp, "ERROR".to_string(), format!("获取失败: {}", e)));
+                
+                        let url = remote.url().unwrap_or("");
+                        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                        logs.push((timestamp, "ERROR".to_string(), format!("远程仓库URL: {}", url)));
+                
+                        if remote_names.len() == 1 || operation.is_cancelled() {
+                            return Err(operation.or_cancelled(format!("Failed to fetch: {}", e)));
+                        }
+                        summary.failed_remotes.push(remote_name.clone());
                     }
                 }
             }
-        },
-        Err(e) => {
+
             let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "ERROR".to_string(), format!("获取HEAD失败: {}", e)));
+            if summary.failed_remotes.is_empty() {
+                logs.push((timestamp, "SUCCESS".to_string(), format!("操作完成 - 已获取远程仓库最新信息（{} 个引用有变化）", summary.updated.len())));
+            } else {
+                logs.push((timestamp, "WARN".to_string(), format!("部分远程获取失败: {}", summary.failed_remotes.join(", "))));
+            }
+            summary.logs = logs;
+            Ok(summary)
+        })
+    }).await
+}
+
+// 推送更改 - 实时日志流
+#[tauri::command]
+async fn push_changes_with_realtime_logs(
+    repo_path: String,
+    remote: Option<String>,
+    operation_id: Option<String>,
+    cache: tauri::State<'_, RepoCache>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    let cache = cache.inner().clone();
+    run_blocking(move || {
+        let operation = RunningOperation::begin("push", operation_id);
+        let window = app_handle.get_window("main").unwrap();
+    
+        // 发送开始日志
+        let _ = window.emit("push-log", serde_json::json!({
+            "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+            "level": "INFO",
+            "message": "开始推送操作..."
+        }));
+    
+        let _ = window.emit("push-log", serde_json::json!({
+            "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+            "level": "INFO", 
+            "message": format!("正在打开仓库: {}", repo_path)
+        }));
+    
+        cache.with(&repo_path, |repo| {
+            let _ = window.emit("push-log", serde_json::json!({
+                "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                "level": "SUCCESS",
+                "message": "仓库打开成功"
+            }));
+
+            // 应用代理配置
+            let _ = window.emit("push-log", serde_json::json!({
+                "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                "level": "INFO",
+                "message": "正在应用代理配置..."
+            }));
+
+            let proxy_config = match load_proxy_config() {
+                Ok((config, is_from_git)) => {
+                    // 配置来源
+                    let _ = window.emit("push-log", serde_json::json!({
+                        "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                        "level": "INFO",
+                        "message": if is_from_git { "代理配置来源: Git 全局配置" } else { "代理配置来源: 应用本地配置" }
+                    }));
+
+                    if config.enabled {
+                        let _ = window.emit("push-log", serde_json::json!({
+                            "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                            "level": "INFO",
+                            "message": format!("使用代理: {}://{}:{}", config.protocol, config.host, config.port)
+                        }));
+                    } else {
+                        let _ = window.emit("push-log", serde_json::json!({
+                            "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                            "level": "INFO",
+                            "message": "未启用代理"
+                        }));
+                    }
+                    config
+                },
+                Err(e) => {
+                    let _ = window.emit("push-log", serde_json::json!({
+                        "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                        "level": "WARN",
+                        "message": format!("获取代理配置失败: {}", e)
+                    }));
+                    ProxyConfig {
+                        enabled: false,
+                        host: "127.0.0.1".to_string(),
+                        port: 7890,
+                        username: None,
+                        password: None,
+                        protocol: "http".to_string(),
+                    }
+                }
+            };
+
+            // 注意：不再设置代理环境变量或写入 git config
+            // libgit2 会使用系统已有的 Git 配置（用户已设置的代理）
+            // 我们只需要验证代理配置的协议是否正确
+            if proxy_config.enabled {
+                if let Err(e) = validate_proxy_protocol(&proxy_config.protocol) {
+                    let _ = window.emit("push-log", serde_json::json!({
+                        "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                        "level": "ERROR",
+                        "message": e
+                    }));
+                    return Err(format!("代理配置错误: {}", e));
+                }
+                let _ = window.emit("push-log", serde_json::json!({
+                    "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                    "level": "INFO",
+                    "message": format!("注意：GitLite 使用系统 Git 配置中的代理设置，当前代理配置（{}://{}:{}）仅用于参考", 
+                        proxy_config.protocol, proxy_config.host, proxy_config.port)
+                }));
+            }
+
+            let _ = window.emit("push-log", serde_json::json!({
+                "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                "level": "INFO",
+                "message": "正在获取HEAD引用..."
+            }));
+
+            let head = match repo.head() {
+                Ok(h) => {
+                    let _ = window.emit("push-log", serde_json::json!({
+                        "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                        "level": "SUCCESS",
+                        "message": "获取HEAD引用成功"
+                    }));
+                    h
+                },
+                Err(e) => {
+                    let _ = window.emit("push-log", serde_json::json!({
+                        "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                        "level": "ERROR",
+                        "message": format!("获取HEAD失败: {}", e)
+                    }));
+                    return Err(format!("Failed to get HEAD: {}", e));
+                }
+            };
+    
+            let branch_name = head.shorthand().unwrap_or("main");
+            let remote_name = resolve_remote_name(repo, remote.as_deref());
+            let _ = window.emit("push-log", serde_json::json!({
+                "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                "level": "INFO",
+                "message": format!("当前分支: {}", branch_name)
+            }));
+
+            let _ = window.emit("push-log", serde_json::json!({
+                "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                "level": "INFO",
+                "message": format!("正在查找远程仓库 {}...", remote_name)
+            }));
+
+            let mut remote = match repo.find_remote(&remote_name) {
+                Ok(r) => {
+                    let _ = window.emit("push-log", serde_json::json!({
+                        "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                        "level": "SUCCESS",
+                        "message": format!("找到远程仓库 {}", remote_name)
+                    }));
+                    r
+                },
+                Err(e) => {
+                    let _ = window.emit("push-log", serde_json::json!({
+                        "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                        "level": "ERROR",
+                        "message": format!("未找到远程仓库 {}: {}", remote_name, e)
+                    }));
+                    return Err(format!("Failed to find remote '{}': {}", remote_name, e));
+                }
+            };
+
+            let _ = window.emit("push-log", serde_json::json!({
+                "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                "level": "INFO",
+                "message": "正在设置认证..."
+            }));
+
+            // 认证与 Push 选项
+            let credentials = CredentialProvider::new(repo, "push", Some(app_handle.clone()), Some(&operation));
+            let progress = ProgressReporter::new(Some(app_handle.clone()), "push", &remote_name, Some(&operation));
+            let ref_results = std::cell::RefCell::new(Vec::new());
+            let mut callbacks = credentials.callbacks();
+            progress.attach(&mut callbacks);
+            record_push_results(&mut callbacks, &ref_results);
+
+            let mut push_opts = git2::PushOptions::new();
+            push_opts.remote_callbacks(callbacks);
+
+            let refspec = format!("refs/heads/{}:refs/heads/{}", branch_name, branch_name);
+            let _ = window.emit("push-log", serde_json::json!({
+                "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                "level": "INFO",
+                "message": format!("开始推送分支 {} 到 {}...", branch_name, remote_name)
+            }));
+
+            // 执行推送
+            match remote.push(&[&refspec], Some(&mut push_opts)) {
+                Ok(_) => {
+                    credentials.finish();
+                    // 连接成功但引用被远程拒绝（非快进、受保护分支等）
+                    if let Some(rejections) = push_rejections(&ref_results.borrow()) {
+                        let _ = window.emit("push-log", serde_json::json!({
+                            "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                            "level": "ERROR",
+                            "message": format!("推送被远程拒绝: {}", rejections)
+                        }));
+                        let _ = window.emit("push-log", serde_json::json!({
+                            "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                            "level": "INFO",
+                            "message": "建议：远程仓库可能已更新，请先拉取最新更改"
+                        }));
+                        log_message("WARN", &format!("push: rejected | remote={} refs={}", remote_name, rejections));
+                        return Err(format!("Push rejected: {}", rejections));
+                    }
+                    let _ = window.emit("push-log", serde_json::json!({
+                        "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                        "level": "SUCCESS",
+                        "message": "推送成功！"
+                    }));
+            
+                    // 若本地分支没有上游，自动设置到 <remote>/<branch>
+                    let _ = window.emit("push-log", serde_json::json!({
+                        "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                        "level": "INFO",
+                        "message": "正在检查上游分支设置..."
+                    }));
+            
+                    if let Ok(mut branch) = repo.find_branch(branch_name, git2::BranchType::Local) {
+                        if branch.upstream().is_err() {
+                            if let Err(e) = branch.set_upstream(Some(&format!("{}/{}", remote_name, branch_name))) {
+                                let _ = window.emit("push-log", serde_json::json!({
+                                    "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                                    "level": "WARN",
+                                    "message": format!("设置上游分支失败: {}", e)
+                                }));
+                            } else {
+                                let _ = window.emit("push-log", serde_json::json!({
+                                    "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                                    "level": "SUCCESS",
+                                    "message": format!("已设置上游分支: {}/{}", remote_name, branch_name)
+                                }));
+                            }
+                        } else {
+                            let _ = window.emit("push-log", serde_json::json!({
+                                "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                                "level": "INFO",
+                                "message": "上游分支已存在"
+                            }));
+                        }
+                    }
+            
+                    let _ = window.emit("push-log", serde_json::json!({
+                        "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                        "level": "SUCCESS",
+                        "message": format!("操作完成 - 已推送到 {}/{}", remote_name, branch_name)
+                    }));
+            
+                    Ok(format!("Successfully pushed to {}/{}", remote_name, branch_name))
+                },
+                Err(e) => {
+                    let url = remote.url().unwrap_or("");
+                    let error_msg = format!("推送失败: {}", e);
+                    let detailed_msg = format!("详细错误信息: {}", e);
+                    let url_msg = format!("远程仓库URL: {}", url);
+            
+                    let _ = window.emit("push-log", serde_json::json!({
+                        "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                        "level": "ERROR",
+                        "message": error_msg
+                    }));
+            
+                    let _ = window.emit("push-log", serde_json::json!({
+                        "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                        "level": "ERROR",
+                        "message": detailed_msg
+                    }));
+            
+                    let _ = window.emit("push-log", serde_json::json!({
+                        "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                        "level": "ERROR",
+                        "message": url_msg
+                    }));
+            
+                    // 根据错误类型提供更具体的建议
+                    let suggestion = if e.message().contains("authentication") {
+                        "建议：检查Git凭据配置，确保有推送权限"
+                    } else if e.message().contains("network") || e.message().contains("timeout") {
+                        "建议：检查网络连接，或尝试使用代理"
+                    } else if e.message().contains("rejected") {
+                        "建议：远程仓库可能已更新，请先拉取最新更改"
+                    } else {
+                        "建议：查看详细错误信息，或尝试使用命令行推送"
+                    };
+            
+                    let _ = window.emit("push-log", serde_json::json!({
+                        "timestamp": chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+                        "level": "INFO",
+                        "message": suggestion
+                    }));
+            
+                    return Err(operation.or_cancelled(format!("Failed to push: {}", e)));
+                }
+            }
+        })
+    }).await
+}
+
+// 推送更改 - 带日志流（保留原函数以兼容性）
+#[tauri::command]
+async fn push_changes_with_logs(
+    repo_path: String,
+    remote: Option<String>,
+    operation_id: Option<String>,
+    cache: tauri::State<'_, RepoCache>,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<(String, String, String)>, String> {
+    let cache = cache.inner().clone();
+    run_blocking(move || {
+        let operation = RunningOperation::begin("push", operation_id);
+        let mut logs = Vec::new();
+        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+    
+        logs.push((timestamp, "INFO".to_string(), format!("push: attempt start | path={}", repo_path)));
+    
+        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+        logs.push((timestamp, "INFO".to_string(), "正在打开仓库...".to_string()));
+    
+        cache.with(&repo_path, |repo| {
+            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+            logs.push((timestamp, "INFO".to_string(), "仓库打开成功".to_string()));
+
+            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+            logs.push((timestamp, "INFO".to_string(), "正在获取HEAD引用...".to_string()));
+
+            let head = match repo.head() {
+                Ok(h) => {
+                    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                    logs.push((timestamp, "INFO".to_string(), "获取HEAD引用成功".to_string()));
+                    h
+                },
+                Err(e) => {
+                    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                    logs.push((timestamp, "ERROR".to_string(), format!("获取HEAD失败: {}", e)));
+                    return Err(format!("Failed to get HEAD: {}", e));
+                }
+            };
+    
+            let branch_name = head.shorthand().unwrap_or("main");
+            let remote_name = resolve_remote_name(repo, remote.as_deref());
+            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+            logs.push((timestamp, "INFO".to_string(), format!("当前分支: {}", branch_name)));
+
+            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+            logs.push((timestamp, "INFO".to_string(), format!("正在查找远程仓库 {}...", remote_name)));
+
+            let mut remote = match repo.find_remote(&remote_name) {
+                Ok(r) => {
+                    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                    logs.push((timestamp, "INFO".to_string(), format!("找到远程仓库 {}", remote_name)));
+                    r
+                },
+                Err(e) => {
+                    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                    logs.push((timestamp, "ERROR".to_string(), format!("未找到远程仓库 {}: {}", remote_name, e)));
+                    return Err(format!("Failed to find remote '{}': {}", remote_name, e));
+                }
+            };
+
+            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+            logs.push((timestamp, "INFO".to_string(), "正在设置认证...".to_string()));
+
+            // 认证与 Push 选项
+            let credentials = CredentialProvider::new(repo, "push", Some(app_handle.clone()), Some(&operation));
+            let progress = ProgressReporter::new(Some(app_handle.clone()), "push", &remote_name, Some(&operation));
+            let ref_results = std::cell::RefCell::new(Vec::new());
+            let mut callbacks = credentials.callbacks();
+            progress.attach(&mut callbacks);
+            record_push_results(&mut callbacks, &ref_results);
+
+            let mut push_opts = git2::PushOptions::new();
+            push_opts.remote_callbacks(callbacks);
+
+            let refspec = format!("refs/heads/{}:refs/heads/{}", branch_name, branch_name);
+            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+            logs.push((timestamp, "INFO".to_string(), format!("开始推送分支 {} 到 {}...", branch_name, remote_name)));
+
+            // 执行推送
+            match remote.push(&[&refspec], Some(&mut push_opts)) {
+                Ok(_) => {
+                    credentials.finish();
+                    // 连接成功但引用被远程拒绝（非快进、受保护分支等）
+                    if let Some(rejections) = push_rejections(&ref_results.borrow()) {
+                        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                        logs.push((timestamp, "ERROR".to_string(), format!("推送被远程拒绝: {}", rejections)));
+                        log_message("WARN", &format!("push: rejected | remote={} refs={}", remote_name, rejections));
+                        return Err(format!("Push rejected: {}", rejections));
+                    }
+                    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                    logs.push((timestamp, "INFO".to_string(), "推送成功！".to_string()));
+            
+                    // 若本地分支没有上游，自动设置到 <remote>/<branch>
+                    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                    logs.push((timestamp, "INFO".to_string(), "正在检查上游分支设置...".to_string()));
+            
+                    if let Ok(mut branch) = repo.find_branch(branch_name, git2::BranchType::Local) {
+                        if branch.upstream().is_err() {
+                            if let Err(e) = branch.set_upstream(Some(&format!("{}/{}", remote_name, branch_name))) {
+                                let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                                logs.push((timestamp, "WARN".to_string(), format!("设置上游分支失败: {}", e)));
+                            } else {
+                                let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                                logs.push((timestamp, "INFO".to_string(), format!("已设置上游分支: {}/{}", remote_name, branch_name)));
+                            }
+                        } else {
+                            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                            logs.push((timestamp, "INFO".to_string(), "上游分支已存在".to_string()));
+                        }
+                    }
+            
+                    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                    logs.push((timestamp, "SUCCESS".to_string(), format!("操作完成 - 已推送到 {}/{}", remote_name, branch_name)));
+            
+                    Ok(logs)
+                },
+                Err(e) => {
+                    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                    logs.push((timestamp, "ERROR".to_string(), format!("推送失败: {}", e)));
+            
+                    let url = remote.url().unwrap_or("");
+                    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                    logs.push((timestamp, "ERROR".to_string(), format!("远程仓库URL: {}", url)));
+            
+                    return Err(operation.or_cancelled(format!("Failed to push: {}", e)));
+                }
+            }
+        })
+    }).await
+}
+
+// Git诊断功能
+#[tauri::command]
+async fn git_diagnostics(repo_path: String, remote: Option<String>, cache: tauri::State<'_, RepoCache>) -> Result<Vec<(String, String, String)>, String> {
+    let cache = cache.inner().clone();
+    run_blocking(move || {
+        let mut logs = Vec::new();
+        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+    
+        logs.push((timestamp, "INFO".to_string(), "开始Git诊断...".to_string()));
+    
+        // 检查仓库状态
+        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+        logs.push((timestamp, "INFO".to_string(), "检查仓库状态...".to_string()));
+    
+        cache.with(&repo_path, |repo| {
+            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+            logs.push((timestamp, "SUCCESS".to_string(), "仓库打开成功".to_string()));
+    
+            // 检查远程仓库
+            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+            logs.push((timestamp, "INFO".to_string(), "检查远程仓库配置...".to_string()));
+    
+            let remote_name = resolve_remote_name(repo, remote.as_deref());
+            match repo.find_remote(&remote_name) {
+                Ok(remote) => {
+                    let url = remote.url().unwrap_or("未设置");
+                    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                    logs.push((timestamp, "SUCCESS".to_string(), format!("远程仓库URL: {}", url)));
+                },
+                Err(e) => {
+                    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                    logs.push((timestamp, "ERROR".to_string(), format!("未找到远程仓库 {}: {}", remote_name, e)));
+                }
+            }
+    
+            // 检查Git配置
+            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+            logs.push((timestamp, "INFO".to_string(), "检查Git配置...".to_string()));
+    
+            if let Ok(config) = repo.config() {
+                // 检查用户配置
+                if let Ok(name) = config.get_string("user.name") {
+                    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                    logs.push((timestamp, "SUCCESS".to_string(), format!("用户名: {}", name)));
+                } else {
+                    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                    logs.push((timestamp, "WARN".to_string(), "未设置用户名".to_string()));
+                }
+        
+                if let Ok(email) = config.get_string("user.email") {
+                    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                    logs.push((timestamp, "SUCCESS".to_string(), format!("邮箱: {}", email)));
+                } else {
+                    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                    logs.push((timestamp, "WARN".to_string(), "未设置邮箱".to_string()));
+                }
+        
+                // 检查凭据配置
+                if let Ok(helper) = config.get_string("credential.helper") {
+                    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                    logs.push((timestamp, "SUCCESS".to_string(), format!("凭据助手: {}", helper)));
+                } else {
+                    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                    logs.push((timestamp, "WARN".to_string(), "未配置凭据助手".to_string()));
+                }
+            } else {
+                let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                logs.push((timestamp, "ERROR".to_string(), "无法读取Git配置".to_string()));
+            }
+    
+            // 检查当前分支
+            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+            logs.push((timestamp, "INFO".to_string(), "检查当前分支...".to_string()));
+    
+            match repo.head() {
+                Ok(head) => {
+                    let branch_name = head.shorthand().unwrap_or("未知");
+                    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                    logs.push((timestamp, "SUCCESS".to_string(), format!("当前分支: {}", branch_name)));
+            
+                    // 检查上游分支
+                    if let Ok(branch) = repo.find_branch(branch_name, git2::BranchType::Local) {
+                        match branch.upstream() {
+                            Ok(upstream) => {
+                                let upstream_name = upstream.name().unwrap_or(Some("未知")).unwrap_or("未知");
+                                let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                                logs.push((timestamp, "SUCCESS".to_string(), format!("上游分支: {}", upstream_name)));
+                            },
+                            Err(_) => {
+                                let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                                logs.push((timestamp, "WARN".to_string(), "未设置上游分支".to_string()));
+                            }
+                        }
+                    }
+                },
+                Err(e) => {
+                    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                    logs.push((timestamp, "ERROR".to_string(), format!("获取HEAD失败: {}", e)));
+                }
+            }
+    
+            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+            logs.push((timestamp, "SUCCESS".to_string(), "Git诊断完成".to_string()));
+    
+            Ok(logs)
+        })
+    }).await
+}
+
+// 拉取更改 - 带日志流
+#[tauri::command]
+async fn pull_changes_with_logs(
+    repo_path: String,
+    remote: Option<String>,
+    mode: Option<String>,
+    autostash: Option<bool>,
+    operation_id: Option<String>,
+    cache: tauri::State<'_, RepoCache>,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<(String, String, String)>, String> {
+    let cache = cache.inner().clone();
+    run_blocking(move || {
+        let operation = RunningOperation::begin("pull", operation_id);
+        let mut logs = Vec::new();
+        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+    
+        logs.push((timestamp, "INFO".to_string(), format!("pull: attempt start | path={}", repo_path)));
+    
+        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+        logs.push((timestamp, "INFO".to_string(), "正在打开仓库...".to_string()));
+    
+        cache.with(&repo_path, |repo| {
+            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+            logs.push((timestamp, "INFO".to_string(), "仓库打开成功".to_string()));
+
+            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+            logs.push((timestamp, "INFO".to_string(), "正在获取HEAD引用...".to_string()));
+
+            let head = match repo.head() {
+                Ok(h) => {
+                    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                    logs.push((timestamp, "INFO".to_string(), "获取HEAD引用成功".to_string()));
+                    h
+                },
+                Err(e) => {
+                    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                    logs.push((timestamp, "ERROR".to_string(), format!("获取HEAD失败: {}", e)));
+                    return Err(format!("Failed to get HEAD: {}", e));
+                }
+            };
+    
+            let branch_name = head.shorthand().unwrap_or("main");
+            let remote_name = resolve_remote_name(repo, remote.as_deref());
+            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+            logs.push((timestamp, "INFO".to_string(), format!("当前分支: {}", branch_name)));
+
+            let mode = resolve_pull_mode(repo, branch_name, mode.as_deref())?;
+            let autostash = autostash.unwrap_or_else(|| config_autostash(repo, &mode));
+            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+            logs.push((timestamp, "INFO".to_string(), format!("拉取模式: {}{}", mode, if autostash { "（自动贮藏）" } else { "" })));
+
+            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+            logs.push((timestamp, "INFO".to_string(), format!("正在查找远程仓库 {}...", remote_name)));
+
+            let mut remote = match repo.find_remote(&remote_name) {
+                Ok(r) => {
+                    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                    logs.push((timestamp, "INFO".to_string(), format!("找到远程仓库 {}", remote_name)));
+                    r
+                },
+                Err(e) => {
+                    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                    logs.push((timestamp, "ERROR".to_string(), format!("未找到远程仓库 {}: {}", remote_name, e)));
+                    return Err(format!("Failed to find remote '{}': {}", remote_name, e));
+                }
+            };
+
+            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+            logs.push((timestamp, "INFO".to_string(), "正在设置认证...".to_string()));
+
+            // 认证与 Fetch 选项
+            let credentials = CredentialProvider::new(repo, "pull", Some(app_handle.clone()), Some(&operation));
+            let progress = ProgressReporter::new(Some(app_handle.clone()), "pull", &remote_name, Some(&operation));
+            let mut callbacks = credentials.callbacks();
+            progress.attach(&mut callbacks);
+
+            let mut fetch_opts = git2::FetchOptions::new();
+            fetch_opts.remote_callbacks(callbacks);
+
+            // 首先执行 fetch
+            let refspec = format!("refs/heads/{}:refs/remotes/{}/{}", branch_name, remote_name, branch_name);
+            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+            logs.push((timestamp, "INFO".to_string(), format!("开始获取远程分支 {}...", branch_name)));
+
+            match remote.fetch::<&str>(&[&refspec], Some(&mut fetch_opts), None) {
+                Ok(_) => {
+                    credentials.finish();
+                    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                    logs.push((timestamp, "INFO".to_string(), "获取远程信息成功".to_string()));
+                },
+                Err(e) => {
+                    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                    logs.push((timestamp, "ERROR".to_string(), format!("获取远程信息失败: {}", e)));
+                    return Err(operation.or_cancelled(format!("Failed to fetch: {}", e)));
+                }
+            }
+
+            // 按拉取模式整合远程更改
+            let remote_branch_ref = format!("refs/remotes/{}/{}", remote_name, branch_name);
+            let result = {
+                let mut log = |level: &str, message: &str| {
+                    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+                    logs.push((timestamp, level.to_string(), message.to_string()));
+                };
+                integrate_pulled_changes(repo, branch_name, &remote_branch_ref, &mode, autostash, &mut log)
+            };
+            let summary = result?;
+
+            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+            logs.push((timestamp, "SUCCESS".to_string(), format!("操作完成 - {}", summary)));
+
+            Ok(logs)
+        })
+    }).await
+}
+
+// 获取已暂存文件的差异
+#[tauri::command]
+async fn get_staged_file_diff(repo_path: String, file_path: String, cache: tauri::State<'_, RepoCache>) -> Result<String, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        let head = repo.head()
+            .map_err(|e| format!("Failed to get HEAD: {}", e))?
+            .peel_to_commit()
+            .map_err(|e| format!("Failed to peel to commit: {}", e))?;
+    
+        let head_tree = head.tree()
+            .map_err(|e| format!("Failed to get HEAD tree: {}", e))?;
+    
+        let mut index = repo.index()
+            .map_err(|e| format!("Failed to get index: {}", e))?;
+    
+        let index_tree = repo.find_tree(write_index_tree(repo, &mut index).map_err(|e| format!("Failed to write tree: {}", e))?)
+            .map_err(|e| format!("Failed to find index tree: {}", e))?;
+    
+        let diff = repo.diff_tree_to_tree(Some(&head_tree), Some(&index_tree), None)
+            .map_err(|e| format!("Failed to create diff: {}", e))?;
+    
+        if let Some(text) = lfs_diff_texts(repo, &diff).remove(&file_path) {
+            return Ok(text);
+        }
+    
+        let mut diff_text = String::new();
+        diff.print(git2::DiffFormat::Patch, |delta, _hunk, line| {
+            // 检查是否是目标文件
+            let current_file = delta.new_file().path()
+                .or_else(|| delta.old_file().path())
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default();
+        
+            if current_file == file_path {
+                // 添加diff行前缀
+                let prefix = match line.origin() {
+                    '+' => "+",
+                    '-' => "-",
+                    ' ' => " ",
+                    _ => "",
+                };
+                // 安全地处理 UTF-8 编码
+                let content = std::str::from_utf8(line.content()).unwrap_or("[INVALID UTF-8]");
+                diff_text.push_str(&format!("{}{}\n", prefix, content));
+            }
+            true
+        }).map_err(|e| format!("Failed to print diff: {}", e))?;
+    
+        Ok(diff_text)
+    }).await
+}
+
+// 获取未暂存文件的差异
+#[tauri::command]
+async fn get_unstaged_file_diff(repo_path: String, file_path: String, cache: tauri::State<'_, RepoCache>) -> Result<String, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        let index = repo.index()
+            .map_err(|e| format!("Failed to get index: {}", e))?;
+    
+        let diff = repo.diff_index_to_workdir(Some(&index), None)
+            .map_err(|e| format!("Failed to create diff: {}", e))?;
+    
+        if let Some(text) = lfs_diff_texts(repo, &diff).remove(&file_path) {
+            return Ok(text);
+        }
+    
+        let mut diff_text = String::new();
+        diff.print(git2::DiffFormat::Patch, |delta, _hunk, line| {
+            // 检查是否是目标文件
+            let current_file = delta.new_file().path()
+                .or_else(|| delta.old_file().path())
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default();
+        
+            if current_file == file_path {
+                // 添加diff行前缀
+                let prefix = match line.origin() {
+                    '+' => "+",
+                    '-' => "-",
+                    ' ' => " ",
+                    _ => "",
+                };
+                // 安全地处理 UTF-8 编码
+                let content = std::str::from_utf8(line.content()).unwrap_or("[INVALID UTF-8]");
+                diff_text.push_str(&format!("{}{}\n", prefix, content));
+            }
+            true
+        }).map_err(|e| format!("Failed to print diff: {}", e))?;
+    
+        Ok(diff_text)
+    }).await
+}
+
+// 获取未跟踪文件的内容
+#[tauri::command]
+async fn get_untracked_file_content(repo_path: String, file_path: String, cache: tauri::State<'_, RepoCache>) -> Result<String, String> {
+    let cache = cache.inner().clone();
+    run_blocking(move || {
+        let full_path = Path::new(&repo_path).join(&file_path);
+    
+        if full_path.is_dir() {
+            return Err("Cannot show content of directory".to_string());
+        }
+    
+        // LFS 文件显示为对象描述，不把大文件当作文本读取
+        let lfs_text = cache.with(&repo_path, |repo| {
+            let size = match fs::metadata(&full_path) {
+                Ok(meta) => meta.len(),
+                Err(_) => return Ok(None),
+            };
+            let pointer = if size <= LFS_POINTER_MAX_SIZE {
+                fs::read(&full_path).ok().and_then(|c| parse_lfs_pointer(&c))
+            } else {
+                None
+            };
+            if pointer.is_none() && !is_lfs_tracked(repo, &file_path) {
+                return Ok(None);
+            }
+            Ok(Some(format!(
+                "diff --git a/{0} b/{0}\nnew file mode 100644\n--- /dev/null\n+++ b/{0}\n@@ -0,0 +1,1 @@\n+{1}\n",
+                file_path,
+                describe_lfs_object(repo, pointer.as_ref(), size)
+            )))
+        });
+        if let Ok(Some(text)) = lfs_text {
+            return Ok(text);
+        }
+    
+        let content = fs::read_to_string(&full_path)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+    
+        // 格式化为类似diff的格式，显示为新增文件
+        let lines: Vec<&str> = content.lines().collect();
+        let mut diff_text = format!("diff --git a/{} b/{}\n", file_path, file_path);
+        diff_text.push_str("new file mode 100644\n");
+        diff_text.push_str("index 0000000..0000000\n");
+        diff_text.push_str("--- /dev/null\n");
+        diff_text.push_str(&format!("+++ b/{}\n", file_path));
+    
+        for (i, line) in lines.iter().enumerate() {
+            diff_text.push_str(&format!("@@ -0,0 +{},1 @@\n", i + 1));
+            diff_text.push_str(&format!("+{}\n", line));
+        }
+    
+        Ok(diff_text)
+    }).await
+}
+
+// 获取文件内容
+#[tauri::command]
+async fn get_file_content(repo_path: String, file_path: String, cache: tauri::State<'_, RepoCache>) -> Result<String, String> {
+    let cache = cache.inner().clone();
+    run_blocking(move || {
+        let full_path = Path::new(&repo_path).join(&file_path);
+    
+        if full_path.is_dir() {
+            return Err("Cannot read content of directory".to_string());
+        }
+    
+        if let Ok(Some(content)) = cache.with(&repo_path, |repo| Ok(lfs_workdir_content(repo, &file_path, &full_path))) {
+            return Ok(content);
+        }
+    
+        let content = fs::read_to_string(&full_path)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+    
+        Ok(content)
+    }).await
+}
+
+// 获取贮藏列表
+#[tauri::command]
+async fn get_stash_list(repo_path: String, cache: tauri::State<'_, RepoCache>) -> Result<Vec<StashInfo>, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        // 收集贮藏信息
+        let mut stash_data = Vec::new();
+        repo.stash_foreach(|index, message, oid| {
+            stash_data.push((index, *oid, message.to_string()));
+            true // 继续遍历
+        }).map_err(|e| format!("Failed to iterate stashes: {}", e))?;
+
+        let mut stashes = Vec::new();
+        for (index, oid, stash_message) in stash_data {
+            let commit = match repo.find_commit(oid) {
+                Ok(commit) => commit,
+                Err(_) => continue,
+            };
+            let timestamp = chrono::DateTime::from_timestamp(commit.time().seconds(), 0)
+                .map(|dt| dt.with_timezone(&chrono::Local).to_rfc3339())
+                .unwrap_or_default();
+            let branch = parse_stash_branch(&stash_message)
+                .or_else(|| commit.summary().and_then(parse_stash_branch))
+                .unwrap_or_else(|| "unknown".to_string());
+            let base_commit = commit.parent_id(0).map(|id| id.to_string()).unwrap_or_default();
+            let file_count = stash_changed_files(repo, &commit).map(|files| files.len()).unwrap_or(0);
+
+            stashes.push(StashInfo {
+                id: oid.to_string(),
+                index,
+                selector: format!("stash@{{{}}}", index),
+                message: stash_message,
+                timestamp,
+                branch,
+                base_commit,
+                file_count,
+            });
+        }
+
+        Ok(stashes)
+    }).await
+}
+
+// 从贮藏说明中解析来源分支："WIP on <branch>: ..." 或 "On <branch>: ..."
+fn parse_stash_branch(message: &str) -> Option<String> {
+    let rest = message.strip_prefix("WIP on ").or_else(|| message.strip_prefix("On "))?;
+    rest.split_once(':').map(|(branch, _)| branch.to_string())
+}
+
+// 贮藏中改动的文件（相对基础提交，含贮藏的未跟踪文件）
+fn stash_changed_files(repo: &Repository, stash_commit: &git2::Commit) -> Result<Vec<String>, git2::Error> {
+    let base_tree = stash_commit.parent(0)?.tree()?;
+    let mut diff = repo.diff_tree_to_tree(Some(&base_tree), Some(&stash_commit.tree()?), None)?;
+    if let Ok(untracked) = stash_commit.parent(2) {
+        diff.merge(&repo.diff_tree_to_tree(None, Some(&untracked.tree()?), None)?)?;
+    }
+    Ok(diff.deltas()
+        .filter_map(|delta| delta.new_file().path().or_else(|| delta.old_file().path()))
+        .map(|p| p.to_string_lossy().to_string())
+        .collect())
+}
+
+// 仅贮藏选定的路径。libgit2 在按路径贮藏后仍会重置整个工作区，
+// 因此以 KEEP_ALL 保存后只还原选定的路径；stash_save_ext 不支持自定义说明，保存后改写贮藏的 reflog 说明
+fn stash_selected_paths(
+    repo: &mut Repository,
+    signature: &git2::Signature,
+    message: &str,
+    flags: git2::StashFlags,
+    paths: &[String],
+) -> Result<Oid, git2::Error> {
+    let keep_index = flags.contains(git2::StashFlags::KEEP_INDEX);
+    let include_untracked = flags.contains(git2::StashFlags::INCLUDE_UNTRACKED);
+
+    // 将路径展开为有改动的文件；libgit2 会把路径中的未跟踪文件一并贮藏，未要求时需排除
+    let mut status_options = git2::StatusOptions::new();
+    status_options.include_untracked(include_untracked).recurse_untracked_dirs(true);
+    for path in paths {
+        status_options.pathspec(path);
+    }
+    let mut selected = Vec::new();
+    let mut added = Vec::new();   // 暂存区中新增的文件在还原后会变成未跟踪文件，需要单独删除
+    for entry in repo.statuses(Some(&mut status_options))?.iter() {
+        if let Some(path) = entry.path() {
+            if entry.status().contains(git2::Status::INDEX_NEW) {
+                added.push(path.to_string());
+            }
+            selected.push(path.to_string());
+        }
+    }
+
+    // 暂存的重命名需同时贮藏并还原新旧两个路径，否则原路径的删除会留在暂存区。
+    // 路径过滤后的状态里只能看到选中的一侧，因此在完整的 HEAD → 索引 差异中检测重命名
+    let renames: Vec<(String, String)> = {
+        let head_tree = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+        let mut index_diff = repo.diff_tree_to_index(head_tree.as_ref(), None, None)?;
+        index_diff.find_similar(Some(git2::DiffFindOptions::new().renames(true)))?;
+        let renames = index_diff.deltas()
+            .filter(|d| d.status() == git2::Delta::Renamed)
+            .filter_map(|d| Some((
+                d.old_file().path()?.to_string_lossy().to_string(),
+                d.new_file().path()?.to_string_lossy().to_string(),
+            )))
+            .collect();
+        renames
+    };
+    for (old, new) in renames {
+        if !selected.contains(&old) && !selected.contains(&new) {
+            continue;
+        }
+        if !selected.contains(&old) {
+            selected.push(old);
+        }
+        if !selected.contains(&new) {
+            added.push(new.clone());
+            selected.push(new);
+        }
+    }
+    if selected.is_empty() {
+        return Err(git2::Error::from_str("No changes to stash in the selected paths"));
+    }
+
+    let mut save_options = git2::StashSaveOptions::new(signature.to_owned());
+    save_options.flags(Some(flags | git2::StashFlags::KEEP_ALL));
+    for path in &selected {
+        save_options.pathspec(path.as_str());
+    }
+    let oid = repo.stash_save_ext(Some(&mut save_options))?;
+
+    let head = repo.head()?.peel_to_commit()?;
+    let mut checkout = git2::build::CheckoutBuilder::new();
+    checkout.force().remove_untracked(include_untracked);
+    for path in &selected {
+        checkout.path(path);
+    }
+    if keep_index {
+        repo.checkout_index(None, Some(&mut checkout))?;
+    } else {
+        repo.reset_default(Some(head.as_object()), selected.iter())?;
+        repo.checkout_head(Some(&mut checkout))?;
+        if let Some(workdir) = repo.workdir() {
+            for path in &added {
+                let _ = fs::remove_file(workdir.join(path));
+            }
+        }
+    }
+
+    if !message.is_empty() {
+        let branch = repo.head().ok()
+            .and_then(|h| h.shorthand().map(|s| s.to_string()))
+            .unwrap_or_else(|| "(no branch)".to_string());
+        let mut reflog = repo.reflog("refs/stash")?;
+        reflog.remove(0, false)?;
+        reflog.append(oid, signature, Some(&format!("On {}: {}", branch, message)))?;
+        reflog.write()?;
+    }
+    Ok(oid)
+}
+
+// 创建贮藏
+#[tauri::command]
+async fn create_stash(
+    repo_path: String,
+    message: String,
+    include_untracked: Option<bool>,
+    keep_index: Option<bool>,
+    paths: Option<Vec<String>>,
+    cache: tauri::State<'_, RepoCache>,
+) -> Result<String, String> {
+    with_repo(&cache, repo_path.clone(), move |repo| {
+        let include_untracked = include_untracked.unwrap_or(false);
+        let keep_index = keep_index.unwrap_or(false);
+        let paths = paths.unwrap_or_default();
+        log_message("INFO", &format!(
+            "create_stash: attempt start | path={} message={} include_untracked={} keep_index={} paths={}",
+            repo_path, message, include_untracked, keep_index, paths.len()
+        ));
+    
+
+        // 尝试从仓库获取签名，如果失败则使用默认签名
+        let signature = match repo.signature() {
+            Ok(sig) => {
+                log_message("DEBUG", &format!("create_stash: using repo signature | name={} email={}", 
+                    sig.name().unwrap_or("unknown"), 
+                    sig.email().unwrap_or("unknown")));
+                sig
+            },
+            Err(e) => {
+                log_message("WARN", &format!("create_stash: failed to get repo signature: {}, using default", e));
+                git2::Signature::now("GitLite User", "gitlite@example.com")
+                    .map_err(|e| {
+                        let error_msg = format!("Failed to create default signature: {}", e);
+                        log_message("ERROR", &format!("create_stash: {}", error_msg));
+                        error_msg
+                    })?
+            }
+        };
+
+        log_message("DEBUG", &format!("create_stash: signature obtained | name={} email={}", 
+            signature.name().unwrap_or("unknown"), 
+            signature.email().unwrap_or("unknown")));
+
+        // 检查工作区是否有更改
+        let has_changes = {
+            let statuses = repo.statuses(None)
+                .map_err(|e| {
+                    let error_msg = format!("Failed to get status: {}", e);
+                    log_message("ERROR", &format!("create_stash: {}", error_msg));
+                    error_msg
+                })?;
+        
+            statuses.iter().any(|entry| {
+                let status = entry.status();
+                (include_untracked && status.contains(git2::Status::WT_NEW)) ||
+                status.contains(git2::Status::WT_MODIFIED) ||
+                status.contains(git2::Status::WT_DELETED) ||
+                status.contains(git2::Status::WT_TYPECHANGE) ||
+                status.contains(git2::Status::WT_RENAMED) ||
+                status.contains(git2::Status::INDEX_NEW) ||
+                status.contains(git2::Status::INDEX_MODIFIED) ||
+                status.contains(git2::Status::INDEX_DELETED)
+            })
+        };
+    
+        if !has_changes {
+            log_message("WARN", "create_stash: no changes to stash");
+            return Err("No changes to stash".to_string());
+        }
+    
+        log_message("DEBUG", "create_stash: changes detected, proceeding with stash");
+
+        let mut flags = git2::StashFlags::DEFAULT;
+        if include_untracked {
+            flags |= git2::StashFlags::INCLUDE_UNTRACKED;
+        }
+        if keep_index {
+            flags |= git2::StashFlags::KEEP_INDEX;
+        }
+        let stash_result = if paths.is_empty() {
+            repo.stash_save(&signature, &message, Some(flags))
+        } else {
+            stash_selected_paths(repo, &signature, &message, flags, &paths)
+        };
+
+        let stash_id = stash_result
+            .map_err(|e| {
+                let error_msg = format!("Failed to create stash: {}", e);
+                log_message("ERROR", &format!("create_stash: {}", error_msg));
+                error_msg
+            })?;
+    
+        log_message("INFO", &format!("create_stash: success | stash_id={}", stash_id));
+        Ok(format!("Successfully created stash: {}", stash_id))
+    }).await
+}
+
+// 应用贮藏
+#[tauri::command]
+async fn apply_stash(repo_path: String, stash_id: String, cache: tauri::State<'_, RepoCache>) -> Result<String, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        let (index, oid, message) = find_stash(repo, &stash_id)?;
+        let stash_info = (oid.to_string(), message);
+
+        // 创建贮藏应用选项
+        let mut options = git2::StashApplyOptions::new();
+        options.reinstantiate_index();
+    
+        match repo.stash_apply(index, Some(&mut options)) {
+            Ok(_) => {
+                Ok(format!("Successfully applied stash: {} ({})", stash_info.0, stash_info.1))
+            },
+            Err(e) => {
+                let error_msg = e.message();
+            
+                // 记录详细错误信息
+                eprintln!("Stash apply error for {}: {}", stash_info.0, error_msg);
+            
+                // 检查是否是重复应用的错误
+                if error_msg.contains("already applied") || error_msg.contains("nothing to commit") {
+                    Ok(format!("Stash {} ({}) has already been applied or there are no changes to apply", 
+                              stash_info.0, stash_info.1))
+                } else if error_msg.contains("conflict") {
+                    Err(format!("Failed to apply stash {} ({}): Conflicts detected. Error: {}. Please resolve conflicts manually.", 
+                               stash_info.0, stash_info.1, error_msg))
+                } else {
+                    // 尝试不使用选项
+                    match repo.stash_apply(index, None) {
+                        Ok(_) => {
+                            Ok(format!("Successfully applied stash: {} ({})", stash_info.0, stash_info.1))
+                        },
+                        Err(e2) => {
+                            let error_msg2 = e2.message();
+                            eprintln!("Second stash apply attempt failed for {}: {}", stash_info.0, error_msg2);
+                        
+                            if error_msg2.contains("already applied") || error_msg2.contains("nothing to commit") {
+                                Ok(format!("Stash {} ({}) has already been applied or there are no changes to apply", 
+                                          stash_info.0, stash_info.1))
+                            } else {
+                                Err(format!("Failed to apply stash {} ({}): {}. This may be because the stash has already been applied, there are conflicts, or the working directory is in an unexpected state.", 
+                                           stash_info.0, stash_info.1, error_msg2))
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }).await
+}
+
+// 删除贮藏
+#[tauri::command]
+async fn delete_stash(repo_path: String, stash_id: String, cache: tauri::State<'_, RepoCache>) -> Result<String, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        let (index, oid, _) = find_stash(repo, &stash_id)?;
+
+        repo.stash_drop(index)
+            .map_err(|e| format!("Failed to delete stash: {}", e))?;
+    
+        Ok(format!("Successfully deleted stash: {}", oid))
+    }).await
+}
+
+// 按 OID（支持至少 7 位的唯一前缀）或 stash@{n} 查找贮藏，返回 (索引, OID, 说明)
+fn find_stash(repo: &mut Repository, stash_id: &str) -> Result<(usize, Oid, String), String> {
+    const MIN_PREFIX_LEN: usize = 7;
+    let selector_index = stash_id.strip_prefix("stash@{")
+        .and_then(|rest| rest.strip_suffix('}'))
+        .and_then(|n| n.parse::<usize>().ok());
+    if selector_index.is_none() && stash_id.len() < MIN_PREFIX_LEN {
+        return Err(format!("贮藏 ID 前缀至少需要 {} 位: {}", MIN_PREFIX_LEN, stash_id));
+    }
+    let stash_id_lower = stash_id.to_lowercase();
+    let mut found = Vec::new();
+    let mut available = Vec::new();
+    repo.stash_foreach(|index, message, oid| {
+        let oid_str = oid.to_string();
+        let matched = match selector_index {
+            Some(n) => n == index,
+            None => oid_str.starts_with(&stash_id_lower),
+        };
+        if matched {
+            found.push((index, *oid, message.to_string()));
+        }
+        available.push(format!("{}: {}", oid_str, message));
+        true
+    }).map_err(|e| format!("Failed to find stash: {}", e))?;
+
+    match found.len() {
+        0 => Err(format!("Stash not found: {}. Available stashes: [{}]", stash_id, available.join(", "))),
+        1 => Ok(found.remove(0)),
+        n => Err(format!(
+            "贮藏 ID 前缀 {} 不唯一，匹配到 {} 个贮藏: [{}]",
+            stash_id,
+            n,
+            found.iter().map(|(_, oid, message)| format!("{}: {}", oid, message)).collect::<Vec<_>>().join(", ")
+        )),
+    }
+}
+
+// 弹出贮藏：应用成功后删除该贮藏
+#[tauri::command]
+async fn pop_stash(repo_path: String, stash_id: String, cache: tauri::State<'_, RepoCache>) -> Result<String, String> {
+    with_repo(&cache, repo_path.clone(), move |repo| {
+        log_message("INFO", &format!("pop_stash: attempt start | path={} stash_id={}", repo_path, stash_id));
+        let (index, oid, message) = find_stash(repo, &stash_id)?;
+
+        let mut options = git2::StashApplyOptions::new();
+        options.reinstantiate_index();
+        repo.stash_pop(index, Some(&mut options))
+            .map_err(|e| {
+                log_message("ERROR", &format!("pop_stash: failed | stash_id={} error={}", oid, e));
+                format!("Failed to pop stash {} ({}): {}. The stash has been kept.", oid, message, e)
+            })?;
+
+        log_message("INFO", &format!("pop_stash: success | stash_id={}", oid));
+        Ok(format!("Successfully popped stash: {} ({})", oid, message))
+    }).await
+}
+
+// 从贮藏创建分支：在贮藏的基础提交上新建并切换分支，应用贮藏后将其删除
+#[tauri::command]
+async fn create_branch_from_stash(repo_path: String, stash_id: String, branch_name: String, cache: tauri::State<'_, RepoCache>) -> Result<String, String> {
+    with_repo(&cache, repo_path.clone(), move |repo| {
+        log_message("INFO", &format!("stash_branch: attempt start | path={} stash_id={} branch={}", repo_path, stash_id, branch_name));
+        let (index, oid, message) = find_stash(repo, &stash_id)?;
+
+        {
+            let base = repo.find_commit(oid)
+                .and_then(|stash| stash.parent(0))
+                .map_err(|e| format!("Failed to find stash base commit: {}", e))?;
+            let branch = repo.branch(&branch_name, &base, false)
+                .map_err(|e| format!("Failed to create branch: {}", e))?;
+            let refname = branch.get().name().ok_or("Invalid branch name")?.to_string();
+
+            let mut checkout = git2::build::CheckoutBuilder::new();
+            checkout.safe();
+            repo.checkout_tree(base.as_object(), Some(&mut checkout))
+                .map_err(|e| format!("Failed to checkout branch: {}", e))?;
+            repo.set_head(&refname)
+                .map_err(|e| format!("Failed to switch to branch: {}", e))?;
+        }
+
+        let mut options = git2::StashApplyOptions::new();
+        options.reinstantiate_index();
+        repo.stash_pop(index, Some(&mut options))
+            .map_err(|e| format!("Created branch {} but failed to apply stash {}: {}", branch_name, oid, e))?;
+
+        log_message("INFO", &format!("stash_branch: success | branch={} stash_id={}", branch_name, oid));
+        Ok(format!("Created branch {} from stash: {} ({})", branch_name, oid, message))
+    }).await
+}
+
+// 获取贮藏内容的差异（相对其基础提交，包括贮藏的未跟踪文件），可按文件过滤
+#[tauri::command]
+async fn get_stash_diff(repo_path: String, stash_id: String, file_path: Option<String>, cache: tauri::State<'_, RepoCache>) -> Result<String, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        let (_, oid, _) = find_stash(repo, &stash_id)?;
+
+        let stash_commit = repo.find_commit(oid)
+            .map_err(|e| format!("Failed to find stash commit: {}", e))?;
+        let base_tree = stash_commit.parent(0)
+            .and_then(|c| c.tree())
+            .map_err(|e| format!("Failed to get stash base tree: {}", e))?;
+        let stash_tree = stash_commit.tree()
+            .map_err(|e| format!("Failed to get stash tree: {}", e))?;
+
+        let mut diff = repo.diff_tree_to_tree(Some(&base_tree), Some(&stash_tree), None)
+            .map_err(|e| format!("Failed to create diff: {}", e))?;
+        // 第三个父提交保存了贮藏时的未跟踪文件
+        if let Ok(untracked) = stash_commit.parent(2) {
+            let untracked_tree = untracked.tree()
+                .map_err(|e| format!("Failed to get untracked tree: {}", e))?;
+            let untracked_diff = repo.diff_tree_to_tree(None, Some(&untracked_tree), None)
+                .map_err(|e| format!("Failed to create diff: {}", e))?;
+            diff.merge(&untracked_diff)
+                .map_err(|e| format!("Failed to merge diffs: {}", e))?;
+        }
+
+        let lfs_texts = lfs_diff_texts(repo, &diff);
+        let mut lfs_printed = std::collections::HashSet::new();
+        let mut diff_text = String::new();
+        diff.print(git2::DiffFormat::Patch, |delta, _hunk, line| {
+            let current_file = delta.new_file().path()
+                .or_else(|| delta.old_file().path())
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            if let Some(text) = lfs_texts.get(&current_file) {
+                if file_path.as_ref().map(|f| *f == current_file).unwrap_or(true) && lfs_printed.insert(current_file) {
+                    diff_text.push_str(text);
+                }
+                return true;
+            }
+            if file_path.as_ref().map(|f| *f == current_file).unwrap_or(true) {
+                let prefix = match line.origin() {
+                    '+' => "+",
+                    '-' => "-",
+                    ' ' => " ",
+                    _ => "",
+                };
+                let content = std::str::from_utf8(line.content()).unwrap_or("[INVALID UTF-8]");
+                diff_text.push_str(&format!("{}{}\n", prefix, content));
+            }
+            true
+        }).map_err(|e| format!("Failed to print diff: {}", e))?;
+
+        Ok(diff_text)
+    }).await
+}
+
+// 交互式变基的待办项
+// action: "pick" / "reword" / "edit" / "squash" / "fixup" / "drop"，列表顺序即执行顺序（可重排）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RebaseTodoItem {
+    pub action: String,
+    pub commit_id: String,
+    #[serde(default)]
+    pub short_id: String,
+    #[serde(default)]
+    pub summary: String,
+    pub message: Option<String>, // reword 时必填的新提交信息；squash 时为空则合并两个提交的信息
+}
+
+// 持久化在 .git/gitlite/rebase.json 中的变基进度，应用重启后可继续
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RebaseState {
+    pub head_name: String, // 原分支引用，如 refs/heads/main；分离头指针时为 "detached HEAD"
+    pub onto: String,
+    pub todo: Vec<RebaseTodoItem>,
+    pub current: usize,          // 下一个待执行（或因冲突停下）的步骤
+    pub stopped: Option<String>, // "conflict" / "edit"
+    #[serde(default)]
+    pub autostash: Option<String>, // 拉取时自动贮藏的提交，变基结束后恢复
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RebaseStatus {
+    pub in_progress: bool,
+    pub head_name: String,
+    pub onto: String,
+    pub current: usize,
+    pub total: usize,
+    pub stopped: Option<String>,        // "conflict" / "edit"，为空表示未暂停
+    pub stopped_commit: Option<String>, // 暂停时对应的原提交
+    pub conflicted_files: Vec<String>,
+    pub todo: Vec<RebaseTodoItem>,
+    pub message: String,
+}
+
+// 获取提交签名：优先使用仓库配置的 user.name / user.email
+fn get_signature(repo: &Repository) -> Result<git2::Signature<'static>, String> {
+    match repo.signature() {
+        Ok(sig) => Ok(sig),
+        Err(_) => git2::Signature::now("GitLite User", "gitlite@example.com")
+            .map_err(|e| format!("Failed to create signature: {}", e)),
+    }
+}
+
+// GitLite 自身的操作状态目录（.git/gitlite）
+fn gitlite_state_file(repo: &Repository, name: &str) -> std::path::PathBuf {
+    repo.path().join("gitlite").join(name)
+}
+
+// 收集索引中处于冲突状态的路径
+fn conflicted_paths(index: &git2::Index) -> Vec<String> {
+    let mut paths = Vec::new();
+    if let Ok(conflicts) = index.conflicts() {
+        for conflict in conflicts.flatten() {
+            let entry = conflict.our.or(conflict.their).or(conflict.ancestor);
+            if let Some(entry) = entry {
+                let path = String::from_utf8_lossy(&entry.path).to_string();
+                if !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+        }
+    }
+    paths
+}
+
+// 将某个提交的改动应用到 HEAD 上，写入索引与工作区，返回冲突文件列表
+// 不会写入 CHERRY_PICK_HEAD 等状态文件，供变基等内部流程使用
+fn apply_commit_to_head(repo: &Repository, commit: &git2::Commit, mainline: u32) -> Result<Vec<String>, String> {
+    let head_commit = repo.head()
+        .and_then(|h| h.peel_to_commit())
+        .map_err(|e| format!("Failed to get HEAD commit: {}", e))?;
+
+    let mut merged = repo.cherrypick_commit(commit, &head_commit, mainline, None)
+        .map_err(|e| format!("Failed to apply commit {}: {}", commit.id(), e))?;
+
+    let mut checkout = git2::build::CheckoutBuilder::new();
+    checkout.safe().allow_conflicts(true).conflict_style_merge(true);
+    repo.checkout_index(Some(&mut merged), Some(&mut checkout))
+        .map_err(|e| format!("Failed to update working directory: {}", e))?;
+
+    let index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
+    Ok(conflicted_paths(&index))
+}
+
+// 规范化变基动作名，支持 git 的单字母缩写
+fn normalize_rebase_action(action: &str) -> Result<String, String> {
+    match action.trim().to_lowercase().as_str() {
+        "p" | "pick" => Ok("pick".to_string()),
+        "r" | "reword" => Ok("reword".to_string()),
+        "e" | "edit" => Ok("edit".to_string()),
+        "s" | "squash" => Ok("squash".to_string()),
+        "f" | "fixup" => Ok("fixup".to_string()),
+        "d" | "drop" => Ok("drop".to_string()),
+        other => Err(format!("不支持的变基操作: {}", other)),
+    }
+}
+
+fn load_rebase_state(repo: &Repository) -> Option<RebaseState> {
+    let state_file = gitlite_state_file(repo, "rebase.json");
+    // libgit2 的变基状态已不存在（例如在命令行中被中止），清理残留文件
+    if repo.open_rebase(None).is_err() {
+        let _ = fs::remove_file(&state_file);
+        return None;
+    }
+    let content = fs::read_to_string(&state_file).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_rebase_state(repo: &Repository, state: &RebaseState) -> Result<(), String> {
+    let state_file = gitlite_state_file(repo, "rebase.json");
+    if let Some(dir) = state_file.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create state directory: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(state)
+        .map_err(|e| format!("Failed to serialize rebase state: {}", e))?;
+    fs::write(&state_file, content)
+        .map_err(|e| format!("Failed to write rebase state: {}", e))
+}
+
+fn build_rebase_status(repo: &Repository, state: Option<&RebaseState>, message: &str) -> RebaseStatus {
+    let conflicted_files = repo.index().map(|i| conflicted_paths(&i)).unwrap_or_default();
+    match state {
+        Some(state) => {
+            let stopped_commit = match state.stopped.as_deref() {
+                Some("conflict") => state.todo.get(state.current).map(|t| t.commit_id.clone()),
+                Some("edit") if state.current > 0 => state.todo.get(state.current - 1).map(|t| t.commit_id.clone()),
+                _ => None,
+            };
+            RebaseStatus {
+                in_progress: true,
+                head_name: state.head_name.clone(),
+                onto: state.onto.clone(),
+                current: state.current,
+                total: state.todo.len(),
+                stopped: state.stopped.clone(),
+                stopped_commit,
+                conflicted_files,
+                todo: state.todo.clone(),
+                message: message.to_string(),
+            }
+        }
+        None => RebaseStatus {
+            in_progress: false,
+            head_name: String::new(),
+            onto: String::new(),
+            current: 0,
+            total: 0,
+            stopped: None,
+            stopped_commit: None,
+            conflicted_files,
+            todo: Vec::new(),
+            message: message.to_string(),
+        },
+    }
+}
+
+// 为当前待办项创建提交（索引内容即为提交内容）；返回 false 表示改动为空被跳过
+fn commit_rebase_step(repo: &Repository, item: &RebaseTodoItem) -> Result<bool, String> {
+    let oid = Oid::from_str(&item.commit_id)
+        .map_err(|e| format!("Invalid commit ID: {}", e))?;
+    let original = repo.find_commit(oid)
+        .map_err(|e| format!("Failed to find commit: {}", e))?;
+    let head_commit = repo.head()
+        .and_then(|h| h.peel_to_commit())
+        .map_err(|e| format!("Failed to get HEAD commit: {}", e))?;
+
+    let mut index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
+    if index.has_conflicts() {
+        return Err("Conflicts have not been resolved".to_string());
+    }
+    let tree_id = write_index_tree(repo, &mut index).map_err(|e| format!("Failed to write tree: {}", e))?;
+    let tree = repo.find_tree(tree_id).map_err(|e| format!("Failed to find tree: {}", e))?;
+    let committer = get_signature(repo)?;
+    let original_message = original.message().unwrap_or("").to_string();
+
+    let new_oid = match item.action.as_str() {
+        "squash" | "fixup" => {
+            // 合并到上一个提交：沿用上一个提交的父提交与作者
+            let head_message = head_commit.message().unwrap_or("").to_string();
+            let message = if item.action == "squash" {
+                item.message.clone().unwrap_or_else(|| {
+                    format!("{}\n\n{}", head_message.trim_end(), original_message)
+                })
+            } else {
+                head_message
+            };
+            let parents: Vec<git2::Commit> = head_commit.parents().collect();
+            let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+            repo.commit(None, &head_commit.author(), &committer, &message, &tree, &parent_refs)
+                .map_err(|e| format!("Failed to create commit: {}", e))?
+        }
+        _ => {
+            if tree_id == head_commit.tree_id() {
+                return Ok(false);
+            }
+            let message = if item.action == "reword" {
+                item.message.clone().unwrap_or(original_message)
+            } else {
+                original_message
+            };
+            repo.commit(None, &original.author(), &committer, &message, &tree, &[&head_commit])
+                .map_err(|e| format!("Failed to create commit: {}", e))?
+        }
+    };
+
+    repo.set_head_detached(new_oid)
+        .map_err(|e| format!("Failed to update HEAD: {}", e))?;
+    Ok(true)
+}
+
+// 从 state.current 开始逐步执行待办列表，遇到冲突或 edit 时暂停并保存进度
+fn run_rebase_steps(repo: &Repository, mut state: RebaseState) -> Result<RebaseStatus, String> {
+    while state.current < state.todo.len() {
+        let item = state.todo[state.current].clone();
+        if item.action == "drop" {
+            state.current += 1;
+            continue;
+        }
+
+        let oid = Oid::from_str(&item.commit_id)
+            .map_err(|e| format!("Invalid commit ID: {}", e))?;
+        let commit = repo.find_commit(oid)
+            .map_err(|e| format!("Failed to find commit: {}", e))?;
+
+        let conflicts = apply_commit_to_head(repo, &commit, 0)?;
+        if !conflicts.is_empty() {
+            state.stopped = Some("conflict".to_string());
+            save_rebase_state(repo, &state)?;
+            log_message("WARN", &format!("rebase: stopped on conflict | commit={} files={:?}", item.commit_id, conflicts));
+            let message = format!("应用提交 {} 时发生冲突，请解决冲突后继续", item.short_id);
+            return Ok(build_rebase_status(repo, Some(&state), &message));
+        }
+
+        commit_rebase_step(repo, &item)?;
+        state.current += 1;
+
+        if item.action == "edit" {
+            state.stopped = Some("edit".to_string());
+            save_rebase_state(repo, &state)?;
+            let message = format!("已在提交 {} 处暂停，可修改后继续", item.short_id);
+            return Ok(build_rebase_status(repo, Some(&state), &message));
+        }
+        save_rebase_state(repo, &state)?;
+    }
+
+    // 所有步骤完成，更新原分支并清理 libgit2 的变基状态
+    let signature = get_signature(repo)?;
+    let mut rebase = repo.open_rebase(None)
+        .map_err(|e| format!("Failed to open rebase: {}", e))?;
+    rebase.finish(Some(&signature))
+        .map_err(|e| format!("Failed to finish rebase: {}", e))?;
+    let _ = fs::remove_file(gitlite_state_file(repo, "rebase.json"));
+    log_message("INFO", &format!("rebase: finished | branch={} onto={}", state.head_name, state.onto));
+
+    let mut message = "变基完成".to_string();
+    if let Some(stash_oid) = state.autostash.as_deref().and_then(|id| Oid::from_str(id).ok()) {
+        match pop_autostash(repo.path(), stash_oid) {
+            Ok(msg) => message = format!("{}，{}", message, msg),
+            Err(e) => message = format!("{}，{}", message, e),
+        }
+    }
+    Ok(build_rebase_status(repo, None, &message))
+}
+
+// 生成默认变基待办列表（upstream..HEAD，跳过合并提交，按从旧到新排列）
+fn build_rebase_todo(repo: &Repository, upstream: &str) -> Result<Vec<RebaseTodoItem>, String> {
+    let upstream_commit = repo.revparse_single(upstream)
+        .and_then(|obj| obj.peel_to_commit())
+        .map_err(|e| format!("Failed to resolve upstream '{}': {}", upstream, e))?;
+
+    let mut revwalk = repo.revwalk()
+        .map_err(|e| format!("Failed to create revwalk: {}", e))?;
+    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)
+        .map_err(|e| format!("Failed to set sorting: {}", e))?;
+    revwalk.push_head()
+        .map_err(|e| format!("Failed to push HEAD: {}", e))?;
+    revwalk.hide(upstream_commit.id())
+        .map_err(|e| format!("Failed to hide upstream: {}", e))?;
+
+    let mut todo = Vec::new();
+    for oid_result in revwalk {
+        let oid = oid_result.map_err(|e| format!("Failed to get OID: {}", e))?;
+        let commit = repo.find_commit(oid)
+            .map_err(|e| format!("Failed to find commit: {}", e))?;
+        if commit.parent_count() > 1 {
+            continue;
+        }
+        todo.push(RebaseTodoItem {
+            action: "pick".to_string(),
+            commit_id: oid.to_string(),
+            short_id: format!("{:.7}", oid),
+            summary: commit.summary().unwrap_or("").to_string(),
+            message: None,
+        });
+    }
+    Ok(todo)
+}
+
+// 获取默认的变基待办列表，供前端编辑
+#[tauri::command]
+async fn get_rebase_todo(repo_path: String, upstream: String, cache: tauri::State<'_, RepoCache>) -> Result<Vec<RebaseTodoItem>, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        build_rebase_todo(repo, &upstream)
+    }).await
+}
+
+// 开始变基：todo 为空时使用默认待办列表（全部 pick）
+#[tauri::command]
+async fn start_rebase(
+    repo_path: String,
+    upstream: String,
+    onto: Option<String>,
+    todo: Option<Vec<RebaseTodoItem>>,
+    cache: tauri::State<'_, RepoCache>,
+) -> Result<RebaseStatus, String> {
+    with_repo(&cache, repo_path.clone(), move |repo| {
+        log_message("INFO", &format!("rebase: attempt start | path={} upstream={} onto={:?}", repo_path, upstream, onto));
+        begin_rebase(repo, &upstream, onto.as_deref(), todo, None)
+    }).await
+}
+
+// 初始化变基并开始执行；autostash 为拉取时自动贮藏的提交，变基结束后恢复
+fn begin_rebase(
+    repo: &Repository,
+    upstream: &str,
+    onto: Option<&str>,
+    todo: Option<Vec<RebaseTodoItem>>,
+    autostash: Option<String>,
+) -> Result<RebaseStatus, String> {
+    if repo.state() != git2::RepositoryState::Clean {
+        return Err(format!("仓库当前处于 {:?} 状态，请先完成或中止当前操作", repo.state()));
+    }
+
+    let mut todo = match todo {
+        Some(items) => items,
+        None => build_rebase_todo(repo, upstream)?,
+    };
+    if todo.is_empty() {
+        return Err("没有需要变基的提交".to_string());
+    }
+
+    // 校验并补全待办项
+    for item in todo.iter_mut() {
+        item.action = normalize_rebase_action(&item.action)?;
+        if item.action == "reword" && item.message.as_deref().unwrap_or("").trim().is_empty() {
+            return Err(format!("reword 提交 {} 时必须提供新的提交信息", item.commit_id));
+        }
+        let oid = Oid::from_str(&item.commit_id)
+            .map_err(|e| format!("Invalid commit ID '{}': {}", item.commit_id, e))?;
+        let commit = repo.find_commit(oid)
+            .map_err(|e| format!("Failed to find commit '{}': {}", item.commit_id, e))?;
+        item.commit_id = oid.to_string();
+        item.short_id = format!("{:.7}", oid);
+        if item.summary.is_empty() {
+            item.summary = commit.summary().unwrap_or("").to_string();
+        }
+    }
+    if let Some(first) = todo.iter().find(|t| t.action != "drop") {
+        if first.action == "squash" || first.action == "fixup" {
+            return Err("第一个提交不能使用 squash / fixup，没有可合并的上一个提交".to_string());
+        }
+    }
+
+    let upstream_commit = repo.revparse_single(upstream)
+        .and_then(|obj| obj.peel_to_commit())
+        .map_err(|e| format!("Failed to resolve upstream '{}': {}", upstream, e))?;
+    let upstream_annotated = repo.find_annotated_commit(upstream_commit.id())
+        .map_err(|e| format!("Failed to annotate upstream: {}", e))?;
+
+    let onto_id = match onto {
+        Some(onto) => repo.revparse_single(onto)
+            .and_then(|obj| obj.peel_to_commit())
+            .map_err(|e| format!("Failed to resolve onto '{}': {}", onto, e))?
+            .id(),
+        None => upstream_commit.id(),
+    };
+    let onto_annotated = match onto {
+        Some(_) => Some(repo.find_annotated_commit(onto_id)
+            .map_err(|e| format!("Failed to annotate onto: {}", e))?),
+        None => None,
+    };
+
+    let head = repo.head().map_err(|e| format!("Failed to get HEAD: {}", e))?;
+    let (branch_annotated, head_name) = if repo.head_detached().unwrap_or(false) {
+        let oid = head.target().ok_or("HEAD has no target")?;
+        (repo.find_annotated_commit(oid), "detached HEAD".to_string())
+    } else {
+        (repo.reference_to_annotated_commit(&head), head.name().unwrap_or("HEAD").to_string())
+    };
+    let branch_annotated = branch_annotated
+        .map_err(|e| format!("Failed to annotate HEAD: {}", e))?;
+
+    // libgit2 负责检出 onto 并在 .git/rebase-merge 中记录原分支，以便中止或完成
+    let mut rebase_opts = git2::RebaseOptions::new();
+    repo.rebase(
+        Some(&branch_annotated),
+        Some(&upstream_annotated),
+        onto_annotated.as_ref(),
+        Some(&mut rebase_opts),
+    ).map_err(|e| format!("Failed to start rebase: {}", e))?;
+
+    let state = RebaseState {
+        head_name,
+        onto: onto_id.to_string(),
+        todo,
+        current: 0,
+        stopped: None,
+        autostash,
+    };
+    save_rebase_state(repo, &state)?;
+    run_rebase_steps(repo, state)
+}
+
+// 获取当前变基进度
+#[tauri::command]
+async fn get_rebase_status(repo_path: String, cache: tauri::State<'_, RepoCache>) -> Result<RebaseStatus, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        match load_rebase_state(repo) {
+            Some(state) => Ok(build_rebase_status(repo, Some(&state), "变基进行中")),
+            None if repo.open_rebase(None).is_ok() => {
+                let mut status = build_rebase_status(repo, None, "检测到非 GitLite 发起的变基，仅支持中止");
+                status.in_progress = true;
+                Ok(status)
+            }
+            None => Ok(build_rebase_status(repo, None, "当前没有进行中的变基")),
+        }
+    }).await
+}
+
+// 继续变基：冲突解决后提交当前步骤，或在 edit 暂停后继续；message 可覆盖当前步骤的提交信息
+#[tauri::command]
+async fn continue_rebase(repo_path: String, message: Option<String>, cache: tauri::State<'_, RepoCache>) -> Result<RebaseStatus, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        let mut state = load_rebase_state(repo)
+            .ok_or("当前没有由 GitLite 发起的变基")?;
+
+        match state.stopped.as_deref() {
+            Some("conflict") => {
+                let index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
+                let conflicts = conflicted_paths(&index);
+                if !conflicts.is_empty() {
+                    return Err(format!("仍有未解决的冲突: {}", conflicts.join(", ")));
+                }
+                let mut item = state.todo[state.current].clone();
+                if message.is_some() {
+                    item.message = message;
+                }
+                commit_rebase_step(repo, &item)?;
+                state.todo[state.current] = item.clone();
+                state.current += 1;
+                state.stopped = None;
+                if item.action == "edit" {
+                    state.stopped = Some("edit".to_string());
+                    save_rebase_state(repo, &state)?;
+                    return Ok(build_rebase_status(repo, Some(&state), "已在 edit 提交处暂停"));
+                }
+            }
+            Some("edit") => {
+                // 若在暂停期间暂存了改动或提供了新信息，则修订当前提交
+                let head_commit = repo.head()
+                    .and_then(|h| h.peel_to_commit())
+                    .map_err(|e| format!("Failed to get HEAD commit: {}", e))?;
+                let mut index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
+                let tree_id = write_index_tree(repo, &mut index).map_err(|e| format!("Failed to write tree: {}", e))?;
+                if tree_id != head_commit.tree_id() || message.is_some() {
+                    let tree = repo.find_tree(tree_id).map_err(|e| format!("Failed to find tree: {}", e))?;
+                    let committer = get_signature(repo)?;
+                    let new_oid = head_commit.amend(None, None, Some(&committer), None, message.as_deref(), Some(&tree))
+                        .map_err(|e| format!("Failed to amend commit: {}", e))?;
+                    repo.set_head_detached(new_oid)
+                        .map_err(|e| format!("Failed to update HEAD: {}", e))?;
+                }
+                state.stopped = None;
+            }
+            _ => {}
+        }
+
+        run_rebase_steps(repo, state)
+    }).await
+}
+
+// 跳过当前步骤（丢弃该提交的改动）后继续变基
+#[tauri::command]
+async fn skip_rebase(repo_path: String, cache: tauri::State<'_, RepoCache>) -> Result<RebaseStatus, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        let mut state = load_rebase_state(repo)
+            .ok_or("当前没有由 GitLite 发起的变基")?;
+
+        if state.stopped.as_deref() == Some("conflict") {
+            // 不能使用 reset --hard：libgit2 会一并清理变基状态
+            let head_tree = repo.head()
+                .and_then(|h| h.peel_to_tree())
+                .map_err(|e| format!("Failed to get HEAD tree: {}", e))?;
+            let mut index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
+            index.read_tree(&head_tree)
+                .and_then(|_| index.write())
+                .map_err(|e| format!("Failed to reset index: {}", e))?;
+            let mut checkout = git2::build::CheckoutBuilder::new();
+            checkout.force();
+            repo.checkout_tree(head_tree.as_object(), Some(&mut checkout))
+                .map_err(|e| format!("Failed to reset working directory: {}", e))?;
+            state.current += 1;
+        }
+        state.stopped = None;
+        run_rebase_steps(repo, state)
+    }).await
+}
+
+// 中止变基，恢复到变基前的分支与工作区
+#[tauri::command]
+async fn abort_rebase(repo_path: String, cache: tauri::State<'_, RepoCache>) -> Result<String, String> {
+    with_repo(&cache, repo_path.clone(), move |repo| {
+        let state = load_rebase_state(repo);
+        let mut rebase = repo.open_rebase(None)
+            .map_err(|e| format!("当前没有进行中的变基: {}", e))?;
+        rebase.abort()
+            .map_err(|e| format!("Failed to abort rebase: {}", e))?;
+        let _ = fs::remove_file(gitlite_state_file(repo, "rebase.json"));
+        log_message("INFO", &format!("rebase: aborted | path={}", repo_path));
+
+        if let Some(stash_oid) = state.and_then(|s| s.autostash).and_then(|id| Oid::from_str(&id).ok()) {
+            return match pop_autostash(repo.path(), stash_oid) {
+                Ok(msg) => Ok(format!("已中止变基，{}", msg)),
+                Err(e) => Ok(format!("已中止变基，{}", e)),
+            };
+        }
+        Ok("已中止变基".to_string())
+    }).await
+}
+
+// 拣选 / 还原的执行结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SequenceResult {
+    pub operation: String,              // "cherry-pick" / "revert"
+    pub applied: Vec<String>,           // 已处理完成的原提交
+    pub created: Vec<String>,           // 新创建的提交（no_commit 模式下为空）
+    pub stopped_at: Option<String>,     // 发生冲突的提交
+    pub conflicted_files: Vec<String>,
+    pub remaining: Vec<String>,         // 冲突解决后待继续处理的提交
+    pub message: String,
+}
+
+// 持久化在 .git/gitlite/sequencer.json 中的拣选 / 还原进度
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SequencerState {
+    pub operation: String,
+    pub orig_head: String,
+    pub current: String,
+    pub remaining: Vec<String>,
+    pub mainline: Option<u32>,
+    pub no_commit: bool,
+    pub applied: Vec<String>,
+    pub created: Vec<String>,
+}
+
+fn load_sequencer_state(repo: &Repository) -> Option<SequencerState> {
+    let content = fs::read_to_string(gitlite_state_file(repo, "sequencer.json")).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_sequencer_state(repo: &Repository, state: &SequencerState) -> Result<(), String> {
+    let state_file = gitlite_state_file(repo, "sequencer.json");
+    if let Some(dir) = state_file.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create state directory: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(state)
+        .map_err(|e| format!("Failed to serialize sequencer state: {}", e))?;
+    fs::write(&state_file, content)
+        .map_err(|e| format!("Failed to write sequencer state: {}", e))
+}
+
+// 合并提交必须指定 mainline（以第几个父提交为主线，从 1 开始）；普通提交忽略该参数
+fn mainline_for(commit: &git2::Commit, mainline: Option<u32>) -> Result<u32, String> {
+    if commit.parent_count() <= 1 {
+        return Ok(0);
+    }
+    match mainline {
+        Some(m) if m >= 1 && (m as usize) <= commit.parent_count() => Ok(m),
+        Some(m) => Err(format!("提交 {:.7} 没有第 {} 个父提交", commit.id(), m)),
+        None => Err(format!("提交 {:.7} 是合并提交，需要指定 mainline", commit.id())),
+    }
+}
+
+// 为 no_commit 模式生成临时提交（不被任何引用指向），作为下一个提交的应用基础
+fn create_scratch_commit(repo: &Repository, tree_id: Oid, parent: &git2::Commit) -> Result<Oid, String> {
+    let signature = get_signature(repo)?;
+    let tree = repo.find_tree(tree_id).map_err(|e| format!("Failed to find tree: {}", e))?;
+    repo.commit(None, &signature, &signature, "gitlite: no-commit scratch", &tree, &[parent])
+        .map_err(|e| format!("Failed to create scratch commit: {}", e))
+}
+
+// 提交当前索引中拣选 / 还原的结果；返回 None 表示改动为空
+fn commit_sequencer_result(repo: &Repository, operation: &str, original: &git2::Commit) -> Result<Option<Oid>, String> {
+    let mut index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
+    let tree_id = write_index_tree(repo, &mut index).map_err(|e| format!("Failed to write tree: {}", e))?;
+    let head_commit = repo.head()
+        .and_then(|h| h.peel_to_commit())
+        .map_err(|e| format!("Failed to get HEAD commit: {}", e))?;
+    if tree_id == head_commit.tree_id() {
+        repo.cleanup_state().map_err(|e| format!("Failed to clean up state: {}", e))?;
+        return Ok(None);
+    }
+
+    let tree = repo.find_tree(tree_id).map_err(|e| format!("Failed to find tree: {}", e))?;
+    let committer = get_signature(repo)?;
+    // MERGE_MSG 中保存了 libgit2 生成的提交信息（拣选为原信息，还原为 "Revert ..."）
+    let message = repo.message().unwrap_or_else(|_| original.message().unwrap_or("").to_string());
+    let author = if operation == "cherry-pick" { original.author() } else { committer.clone() };
+    let oid = repo.commit(Some("HEAD"), &author, &committer, &message, &tree, &[&head_commit])
+        .map_err(|e| format!("Failed to commit: {}", e))?;
+    repo.cleanup_state().map_err(|e| format!("Failed to clean up state: {}", e))?;
+    Ok(Some(oid))
+}
+
+// 依次拣选 / 还原 state.current 及 state.remaining 中的提交，遇到冲突时暂停并保存进度
+fn run_sequencer(repo: &Repository, mut state: SequencerState) -> Result<SequenceResult, String> {
+    let mut pending: Vec<String> = std::iter::once(state.current.clone())
+        .chain(state.remaining.iter().cloned())
+        .collect();
+    // no_commit 模式下以当前索引内容为基础逐个叠加
+    let mut base = repo.head()
+        .and_then(|h| h.peel_to_commit())
+        .map_err(|e| format!("Failed to get HEAD commit: {}", e))?;
+    if state.no_commit {
+        let mut index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
+        let tree_id = write_index_tree(repo, &mut index).map_err(|e| format!("Failed to write tree: {}", e))?;
+        if tree_id != base.tree_id() {
+            let scratch = create_scratch_commit(repo, tree_id, &base)?;
+            base = repo.find_commit(scratch).map_err(|e| format!("Failed to find commit: {}", e))?;
+        }
+    }
+
+    while !pending.is_empty() {
+        let commit_id = pending.remove(0);
+        let oid = Oid::from_str(&commit_id)
+            .map_err(|e| format!("Invalid commit ID: {}", e))?;
+        let commit = repo.find_commit(oid)
+            .map_err(|e| format!("Failed to find commit: {}", e))?;
+        let mainline = mainline_for(&commit, state.mainline)?;
+
+        let conflicts = if state.no_commit {
+            let mut merged = if state.operation == "revert" {
+                repo.revert_commit(&commit, &base, mainline, None)
+            } else {
+                repo.cherrypick_commit(&commit, &base, mainline, None)
+            }.map_err(|e| format!("Failed to {} {}: {}", state.operation, commit_id, e))?;
+
+            if merged.has_conflicts() {
+                let mut checkout = git2::build::CheckoutBuilder::new();
+                checkout.safe().allow_conflicts(true).conflict_style_merge(true);
+                repo.checkout_index(Some(&mut merged), Some(&mut checkout))
+                    .map_err(|e| format!("Failed to update working directory: {}", e))?;
+                // 与 git 一致记录 CHERRY_PICK_HEAD / REVERT_HEAD，使 repo.state() 与命令行能识别进行中的操作
+                let head_file = if state.operation == "revert" { "REVERT_HEAD" } else { "CHERRY_PICK_HEAD" };
+                fs::write(repo.path().join(head_file), format!("{}\n", commit.id()))
+                    .map_err(|e| format!("Failed to write {}: {}", head_file, e))?;
+                let index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
+                conflicted_paths(&index)
+            } else {
+                let tree_id = merged.write_tree_to(repo)
+                    .map_err(|e| format!("Failed to write tree: {}", e))?;
+                let scratch = create_scratch_commit(repo, tree_id, &base)?;
+                base = repo.find_commit(scratch).map_err(|e| format!("Failed to find commit: {}", e))?;
+                Vec::new()
+            }
+        } else {
+            if state.operation == "revert" {
+                let mut opts = git2::RevertOptions::new();
+                opts.mainline(mainline);
+                repo.revert(&commit, Some(&mut opts))
+            } else {
+                let mut opts = git2::CherrypickOptions::new();
+                opts.mainline(mainline);
+                repo.cherrypick(&commit, Some(&mut opts))
+            }.map_err(|e| format!("Failed to {} {}: {}", state.operation, commit_id, e))?;
+            let index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
+            conflicted_paths(&index)
+        };
+
+        if !conflicts.is_empty() {
+            state.current = commit_id.clone();
+            state.remaining = pending.clone();
+            save_sequencer_state(repo, &state)?;
+            log_message("WARN", &format!("{}: stopped on conflict | commit={} files={:?}", state.operation, commit_id, conflicts));
+            return Ok(SequenceResult {
+                operation: state.operation.clone(),
+                applied: state.applied.clone(),
+                created: state.created.clone(),
+                stopped_at: Some(commit_id.clone()),
+                conflicted_files: conflicts,
+                remaining: pending,
+                message: format!("处理提交 {:.7} 时发生冲突，请解决冲突后继续或中止", commit_id),
+            });
+        }
+
+        if !state.no_commit {
+            if let Some(new_oid) = commit_sequencer_result(repo, &state.operation, &commit)? {
+                state.created.push(new_oid.to_string());
+            }
+        }
+        state.applied.push(commit_id);
+    }
+
+    // no_commit 模式：将最终结果写入索引与工作区，不创建提交
+    if state.no_commit {
+        let mut checkout = git2::build::CheckoutBuilder::new();
+        checkout.safe();
+        repo.checkout_tree(base.tree().map_err(|e| format!("Failed to get tree: {}", e))?.as_object(), Some(&mut checkout))
+            .map_err(|e| format!("Failed to update working directory: {}", e))?;
+        let mut index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
+        index.read_tree(&base.tree().map_err(|e| format!("Failed to get tree: {}", e))?)
+            .and_then(|_| index.write())
+            .map_err(|e| format!("Failed to update index: {}", e))?;
+        repo.cleanup_state().map_err(|e| format!("Failed to clean up state: {}", e))?;
+    }
+
+    let _ = fs::remove_file(gitlite_state_file(repo, "sequencer.json"));
+    log_message("INFO", &format!("{}: finished | applied={} created={}", state.operation, state.applied.len(), state.created.len()));
+    let message = if state.no_commit {
+        format!("已将 {} 个提交的改动写入暂存区", state.applied.len())
+    } else {
+        format!("已完成 {} 个提交，新建 {} 个提交", state.applied.len(), state.created.len())
+    };
+    Ok(SequenceResult {
+        operation: state.operation,
+        applied: state.applied,
+        created: state.created,
+        stopped_at: None,
+        conflicted_files: Vec::new(),
+        remaining: Vec::new(),
+        message,
+    })
+}
+
+fn start_sequencer(
+    repo: &Repository,
+    operation: &str,
+    commit_ids: Vec<String>,
+    mainline: Option<u32>,
+    no_commit: Option<bool>,
+) -> Result<SequenceResult, String> {
+    log_message("INFO", &format!("{}: attempt start | path={} commits={:?}", operation, repo.path().display(), commit_ids));
+
+    if repo.state() != git2::RepositoryState::Clean {
+        return Err(format!("仓库当前处于 {:?} 状态，请先完成或中止当前操作", repo.state()));
+    }
+    if has_local_changes(repo)? {
+        return Err("存在未提交的更改，请先提交或贮藏".to_string());
+    }
+    if commit_ids.is_empty() {
+        return Err("No commits specified".to_string());
+    }
+
+    // 支持短 ID 与任意可解析的修订表达式
+    let mut resolved = Vec::new();
+    for id in &commit_ids {
+        let commit = repo.revparse_single(id)
+            .and_then(|obj| obj.peel_to_commit())
+            .map_err(|e| format!("Failed to resolve commit '{}': {}", id, e))?;
+        mainline_for(&commit, mainline)?;
+        resolved.push(commit.id().to_string());
+    }
+
+    let orig_head = repo.head()
+        .ok()
+        .and_then(|h| h.target())
+        .ok_or("Failed to get HEAD")?;
+    let state = SequencerState {
+        operation: operation.to_string(),
+        orig_head: orig_head.to_string(),
+        current: resolved.remove(0),
+        remaining: resolved,
+        mainline,
+        no_commit: no_commit.unwrap_or(false),
+        applied: Vec::new(),
+        created: Vec::new(),
+    };
+    run_sequencer(repo, state)
+}
+
+fn continue_sequencer(repo: &Repository, operation: &str) -> Result<SequenceResult, String> {
+    let index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
+    let conflicts = conflicted_paths(&index);
+    if !conflicts.is_empty() {
+        return Err(format!("仍有未解决的冲突: {}", conflicts.join(", ")));
+    }
+
+    let state = load_sequencer_state(repo).filter(|s| s.operation == operation);
+    let in_progress = match operation {
+        "revert" => repo.state() == git2::RepositoryState::Revert,
+        _ => repo.state() == git2::RepositoryState::CherryPick,
+    };
+
+    let mut state = match state {
+        Some(state) => state,
+        None if in_progress => {
+            // 由命令行发起的单个拣选 / 还原：直接提交当前结果
+            let head_file = if operation == "revert" { "REVERT_HEAD" } else { "CHERRY_PICK_HEAD" };
+            let original_id = fs::read_to_string(repo.path().join(head_file))
+                .map_err(|e| format!("Failed to read {}: {}", head_file, e))?;
+            let original = Oid::from_str(original_id.trim())
+                .and_then(|oid| repo.find_commit(oid))
+                .map_err(|e| format!("Failed to find commit: {}", e))?;
+            let created = commit_sequencer_result(repo, operation, &original)?;
+            return Ok(SequenceResult {
+                operation: operation.to_string(),
+                applied: vec![original.id().to_string()],
+                created: created.map(|oid| vec![oid.to_string()]).unwrap_or_default(),
+                stopped_at: None,
+                conflicted_files: Vec::new(),
+                remaining: Vec::new(),
+                message: "已完成".to_string(),
+            });
+        }
+        None => return Err(format!("当前没有进行中的 {}", operation)),
+    };
+
+    let current = state.current.clone();
+    if state.no_commit {
+        repo.cleanup_state().map_err(|e| format!("Failed to clean up state: {}", e))?;
+        state.applied.push(current);
+    } else if in_progress {
+        let original = Oid::from_str(&current)
+            .and_then(|oid| repo.find_commit(oid))
+            .map_err(|e| format!("Failed to find commit: {}", e))?;
+        if let Some(new_oid) = commit_sequencer_result(repo, operation, &original)? {
+            state.created.push(new_oid.to_string());
+        }
+        state.applied.push(current);
+    } else {
+        // 冲突解决后已通过普通提交完成了当前提交
+        state.applied.push(current);
+    }
+
+    if state.remaining.is_empty() {
+        let _ = fs::remove_file(gitlite_state_file(repo, "sequencer.json"));
+        if state.no_commit {
+            repo.cleanup_state().map_err(|e| format!("Failed to clean up state: {}", e))?;
+        }
+        return Ok(SequenceResult {
+            operation: state.operation,
+            message: format!("已完成 {} 个提交", state.applied.len()),
+            applied: state.applied,
+            created: state.created,
+            stopped_at: None,
+            conflicted_files: Vec::new(),
+            remaining: Vec::new(),
+        });
+    }
+    state.current = state.remaining.remove(0);
+    run_sequencer(repo, state)
+}
+
+// 中止拣选 / 还原：恢复到操作开始前的 HEAD、索引与工作区
+fn abort_sequencer(repo: &Repository, operation: &str) -> Result<String, String> {
+    let state = load_sequencer_state(repo).filter(|s| s.operation == operation);
+    // 没有本应用的状态文件时，只在命令行发起的同类操作进行中才回到 HEAD，否则硬重置会丢掉未提交的修改
+    let in_progress = match operation {
+        "cherry-pick" => matches!(repo.state(), git2::RepositoryState::CherryPick | git2::RepositoryState::CherryPickSequence),
+        _ => matches!(repo.state(), git2::RepositoryState::Revert | git2::RepositoryState::RevertSequence),
+    };
+    let target = match state.as_ref() {
+        Some(state) => repo.revparse_single(&state.orig_head),
+        None if in_progress => repo.revparse_single("HEAD"),
+        None => return Err(format!("当前没有进行中的 {}", operation)),
+    }.map_err(|e| format!("Failed to resolve original HEAD: {}", e))?;
+
+    repo.reset(&target, git2::ResetType::Hard, None)
+        .map_err(|e| format!("Failed to reset: {}", e))?;
+    repo.cleanup_state().map_err(|e| format!("Failed to clean up state: {}", e))?;
+    let _ = fs::remove_file(gitlite_state_file(repo, "sequencer.json"));
+
+    log_message("INFO", &format!("{}: aborted | path={}", operation, repo.path().display()));
+    Ok(format!("已中止 {}", operation))
+}
+
+// 拣选一个或多个提交；no_commit 为 true 时只写入暂存区
+#[tauri::command]
+async fn cherry_pick(
+    repo_path: String,
+    commit_ids: Vec<String>,
+    mainline: Option<u32>,
+    no_commit: Option<bool>,
+    cache: tauri::State<'_, RepoCache>,
+) -> Result<SequenceResult, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        start_sequencer(repo, "cherry-pick", commit_ids, mainline, no_commit)
+    }).await
+}
+
+#[tauri::command]
+async fn cherry_pick_continue(repo_path: String, cache: tauri::State<'_, RepoCache>) -> Result<SequenceResult, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        continue_sequencer(repo, "cherry-pick")
+    }).await
+}
+
+#[tauri::command]
+async fn cherry_pick_abort(repo_path: String, cache: tauri::State<'_, RepoCache>) -> Result<String, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        abort_sequencer(repo, "cherry-pick")
+    }).await
+}
+
+// 还原一个或多个提交；no_commit 为 true 时只写入暂存区
+#[tauri::command]
+async fn revert(
+    repo_path: String,
+    commit_ids: Vec<String>,
+    mainline: Option<u32>,
+    no_commit: Option<bool>,
+    cache: tauri::State<'_, RepoCache>,
+) -> Result<SequenceResult, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        start_sequencer(repo, "revert", commit_ids, mainline, no_commit)
+    }).await
+}
+
+#[tauri::command]
+async fn revert_continue(repo_path: String, cache: tauri::State<'_, RepoCache>) -> Result<SequenceResult, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        continue_sequencer(repo, "revert")
+    }).await
+}
+
+#[tauri::command]
+async fn revert_abort(repo_path: String, cache: tauri::State<'_, RepoCache>) -> Result<String, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        abort_sequencer(repo, "revert")
+    }).await
+}
+
+// 重置结果（dry_run 时仅为预览，不修改仓库）
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResetResult {
+    pub mode: String,
+    pub target: String,
+    pub previous_head: String,
+    pub backup_ref: Option<String>,   // hard 重置前创建的备份引用
+    pub affected_files: Vec<FileChange>,
+    pub dry_run: bool,
+    pub message: String,
+}
+
+// 重置前的安全备份
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResetBackup {
+    pub name: String,              // refs/gitlite/backups/... 引用名
+    pub head: String,              // 重置前的 HEAD
+    pub worktree: Option<String>,  // 重置前未提交改动的快照（贮藏格式的提交）
+    pub created: String,
+}
+
+// 将 "soft" / "mixed" / "hard" 转为 git2 的重置类型
+fn parse_reset_mode(mode: &str) -> Result<git2::ResetType, String> {
+    match mode {
+        "soft" => Ok(git2::ResetType::Soft),
+        "mixed" => Ok(git2::ResetType::Mixed),
+        "hard" => Ok(git2::ResetType::Hard),
+        other => Err(format!("不支持的重置模式: {}，只允许 soft / mixed / hard", other)),
+    }
+}
+
+// 将差异转换为文件变更列表（含增删行数）
+fn diff_to_file_changes(diff: &git2::Diff) -> Vec<FileChange> {
+    let mut files = Vec::new();
+    for (idx, delta) in diff.deltas().enumerate() {
+        let status = match FileStatus::from_delta(delta.status()) {
+            FileStatus::Unknown => FileStatus::Modified,
+            status => status,
+        };
+        let old_path = delta.old_file().path()
+            .filter(|_| matches!(status, FileStatus::Renamed | FileStatus::Copied))
+            .map(|p| p.to_string_lossy().replace('\\', "/"));
+        let path = delta.new_file().path()
+            .or_else(|| delta.old_file().path())
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_default();
+        let (additions, deletions) = git2::Patch::from_diff(diff, idx)
+            .ok()
+            .flatten()
+            .and_then(|patch| patch.line_stats().ok())
+            .map(|(_, add, del)| (add as i32, del as i32))
+            .unwrap_or((0, 0));
+        files.push(FileChange {
+            path,
+            old_path,
+            status,
+            additions,
+            deletions,
+        });
+    }
+    files
+}
+
+// 计算重置会影响的文件：
+// soft 为重置后显示为已暂存的文件，mixed 为被移出暂存区的文件，hard 为内容将被覆盖的文件
+fn preview_reset(repo: &Repository, target: &git2::Commit, mode: &str) -> Result<Vec<FileChange>, String> {
+    let target_tree = target.tree().map_err(|e| format!("Failed to get target tree: {}", e))?;
+    let diff = if mode == "hard" {
+        repo.diff_tree_to_workdir_with_index(Some(&target_tree), None)
+    } else {
+        let index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
+        repo.diff_tree_to_index(Some(&target_tree), Some(&index), None)
+    }.map_err(|e| format!("Failed to create diff: {}", e))?;
+    Ok(diff_to_file_changes(&diff))
+}
+
+// 备份引用名中的时间格式（本地时间），列出备份时据此还原创建时间
+const RESET_BACKUP_TIME_FORMAT: &str = "%Y%m%d-%H%M%S%.3f";
+
+// 创建重置前的备份引用：refs/gitlite/backups/<时间> 指向原 HEAD，
+// 若有未提交改动，另存一份快照到 <时间>-worktree（会将工作区恢复到 HEAD）
+fn create_reset_backup(repo_dir: &Path, reason: &str) -> Result<String, String> {
+    let mut repo = Repository::open(repo_dir)
+        .map_err(|e| format!("Failed to open repository: {}", e))?;
+    let head_oid = repo.head()
+        .ok()
+        .and_then(|h| h.target())
+        .ok_or("Failed to get HEAD")?;
+    let name = format!("refs/gitlite/backups/{}", chrono::Local::now().format(RESET_BACKUP_TIME_FORMAT));
+    repo.reference(&name, head_oid, false, &format!("gitlite backup: {}", reason))
+        .map_err(|e| format!("Failed to create backup ref: {}", e))?;
+
+    if has_local_changes(&repo)? {
+        let signature = get_signature(&repo)?;
+        let snapshot = repo.stash_save(&signature, &format!("gitlite backup: {}", reason), None)
+            .map_err(|e| format!("Failed to snapshot working directory: {}", e))?;
+        repo.reference(&format!("{}-worktree", name), snapshot, false, "gitlite backup: worktree snapshot")
+            .map_err(|e| format!("Failed to create backup ref: {}", e))?;
+        // 快照已由备份引用保存，从贮藏列表中移除
+        repo.stash_drop(0)
+            .map_err(|e| format!("Failed to drop snapshot stash: {}", e))?;
+    }
+    Ok(name)
+}
+
+// 重置 HEAD 到指定修订（soft / mixed / hard），dry_run 时仅返回受影响的文件
+#[tauri::command]
+async fn reset_to(repo_path: String, rev: String, mode: String, dry_run: Option<bool>, cache: tauri::State<'_, RepoCache>) -> Result<ResetResult, String> {
+    with_repo(&cache, repo_path.clone(), move |repo| {
+        let reset_type = parse_reset_mode(&mode)?;
+        let dry_run = dry_run.unwrap_or(false);
+        log_message("INFO", &format!("reset: attempt start | path={} rev={} mode={} dry_run={}", repo_path, rev, mode, dry_run));
+
+        if repo.state() == git2::RepositoryState::RebaseMerge || repo.state() == git2::RepositoryState::RebaseInteractive {
+            return Err("变基进行中，请先完成或中止变基".to_string());
+        }
+
+        let target = repo.revparse_single(&rev)
+            .and_then(|obj| obj.peel_to_commit())
+            .map_err(|e| format!("Failed to resolve '{}': {}", rev, e))?;
+        let previous_head = repo.head()
+            .ok()
+            .and_then(|h| h.target())
+            .map(|oid| oid.to_string())
+            .unwrap_or_default();
+        let affected_files = preview_reset(repo, &target, &mode)?;
+
+        if dry_run {
+            return Ok(ResetResult {
+                mode,
+                target: target.id().to_string(),
+                previous_head,
+                backup_ref: None,
+                message: format!("{} 个文件将受影响", affected_files.len()),
+                affected_files,
+                dry_run,
+            });
+        }
+
+        let backup_ref = if reset_type == git2::ResetType::Hard {
+            Some(create_reset_backup(repo.path(), &format!("reset --hard {}", rev))?)
+        } else {
+            None
+        };
+
+        repo.reset(target.as_object(), reset_type, None)
+            .map_err(|e| format!("Failed to reset: {}", e))?;
+
+        log_message("INFO", &format!("reset: success | mode={} target={} backup={:?}", mode, target.id(), backup_ref));
+        Ok(ResetResult {
+            message: format!("已将 HEAD 重置到 {:.7}", target.id()),
+            mode,
+            target: target.id().to_string(),
+            previous_head,
+            backup_ref,
+            affected_files,
+            dry_run,
+        })
+    }).await
+}
+
+// 列出重置前创建的备份（最新的在前）
+#[tauri::command]
+async fn list_reset_backups(repo_path: String, cache: tauri::State<'_, RepoCache>) -> Result<Vec<ResetBackup>, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        let refs = repo.references_glob("refs/gitlite/backups/*")
+            .map_err(|e| format!("Failed to list backups: {}", e))?;
+
+        let mut backups = Vec::new();
+        for reference in refs.flatten() {
+            let name = match reference.name() {
+                Some(name) if !name.ends_with("-worktree") => name.to_string(),
+                _ => continue,
+            };
+            let head = match reference.peel_to_commit() {
+                Ok(commit) => commit.id().to_string(),
+                Err(_) => continue,
+            };
+            let worktree = repo.refname_to_id(&format!("{}-worktree", name))
+                .ok()
+                .map(|oid| oid.to_string());
+            // 自定义命名空间下的引用没有 reflog，创建时间取自引用名
+            let created = name.strip_prefix("refs/gitlite/backups/")
+                .and_then(|stamp| chrono::NaiveDateTime::parse_from_str(stamp, RESET_BACKUP_TIME_FORMAT).ok())
+                .and_then(|dt| dt.and_local_timezone(chrono::Local).earliest())
+                .map(|dt| dt.to_rfc3339())
+                .unwrap_or_default();
+            backups.push(ResetBackup { name, head, worktree, created });
+        }
+        backups.sort_by(|a, b| b.name.cmp(&a.name));
+        Ok(backups)
+    }).await
+}
+
+// 从备份恢复：将当前分支重置回备份时的 HEAD，并恢复当时未提交的改动
+#[tauri::command]
+async fn restore_reset_backup(repo_path: String, name: String, cache: tauri::State<'_, RepoCache>) -> Result<String, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        let head_commit = repo.find_reference(&name)
+            .and_then(|r| r.peel_to_commit())
+            .map_err(|e| format!("Backup not found: {}", e))?;
+        let worktree_commit = repo.find_reference(&format!("{}-worktree", name))
+            .and_then(|r| r.peel_to_commit())
+            .ok();
+
+        // 恢复本身也是一次 hard 重置，先备份当前状态
+        let current_backup = create_reset_backup(repo.path(), &format!("restore {}", name))?;
+
+        repo.reset(head_commit.as_object(), git2::ResetType::Hard, None)
+            .map_err(|e| format!("Failed to reset: {}", e))?;
+
+        if let Some(snapshot) = worktree_commit {
+            // 快照为贮藏格式：提交树为工作区内容，第二个父提交为当时的暂存区
+            let worktree_tree = snapshot.tree().map_err(|e| format!("Failed to get snapshot tree: {}", e))?;
+            let mut checkout = git2::build::CheckoutBuilder::new();
+            checkout.force();
+            repo.checkout_tree(worktree_tree.as_object(), Some(&mut checkout))
+                .map_err(|e| format!("Failed to restore working directory: {}", e))?;
+            let index_tree = snapshot.parent(1)
+                .and_then(|c| c.tree())
+                .map_err(|e| format!("Failed to get snapshot index: {}", e))?;
+            let mut index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
+            index.read_tree(&index_tree)
+                .and_then(|_| index.write())
+                .map_err(|e| format!("Failed to restore index: {}", e))?;
+        }
+
+        log_message("INFO", &format!("reset: restored backup | name={} current_backup={}", name, current_backup));
+        Ok(format!("已恢复到 {:.7}（恢复前的状态已备份为 {}）", head_commit.id(), current_backup))
+    }).await
+}
+
+// 合并结果（dry_run 时仅为 merge_analysis 的预览）
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MergeResult {
+    pub source: String,
+    pub mode: String,
+    pub analysis: String,                 // up-to-date / fast-forward / normal
+    pub merged: bool,
+    pub commit: Option<String>,
+    pub conflicted_files: Vec<String>,    // 预览时为预计冲突的文件
+    pub dry_run: bool,
+    pub message: String,
+}
+
+// 解析合并模式：显式参数优先，其次 merge.ff 配置（false → no-ff，only → ff-only），默认 ff
+fn resolve_merge_mode(repo: &Repository, mode: Option<&str>) -> Result<String, String> {
+    if let Some(mode) = mode {
+        return match mode {
+            "ff" | "no-ff" | "ff-only" | "squash" => Ok(mode.to_string()),
+            other => Err(format!("不支持的合并模式: {}，只允许 ff / no-ff / ff-only / squash", other)),
+        };
+    }
+    let setting = repo.config()
+        .and_then(|c| c.get_string("merge.ff"))
+        .unwrap_or_default();
+    Ok(match setting.to_lowercase().as_str() {
+        "false" | "no" | "off" | "0" => "no-ff",
+        "only" => "ff-only",
+        _ => "ff",
+    }.to_string())
+}
+
+// 将分支名 / 远程分支 / 任意修订解析为被合并的提交，并生成默认合并说明
+fn resolve_merge_source(repo: &Repository, source: &str) -> Result<(Oid, String), String> {
+    if let Ok(branch) = repo.find_branch(source, git2::BranchType::Local) {
+        let oid = branch.get().peel_to_commit()
+            .map_err(|e| format!("Failed to resolve branch '{}': {}", source, e))?.id();
+        return Ok((oid, format!("Merge branch '{}'", source)));
+    }
+    if let Ok(branch) = repo.find_branch(source, git2::BranchType::Remote) {
+        let oid = branch.get().peel_to_commit()
+            .map_err(|e| format!("Failed to resolve branch '{}': {}", source, e))?.id();
+        return Ok((oid, format!("Merge remote-tracking branch '{}'", source)));
+    }
+    let commit = repo.revparse_single(source)
+        .and_then(|obj| obj.peel_to_commit())
+        .map_err(|e| format!("Failed to resolve '{}': {}", source, e))?;
+    Ok((commit.id(), format!("Merge commit '{}'", source)))
+}
+
+// 将任意分支或修订合并到当前分支（ff / no-ff / ff-only / squash），合并前先用 merge_analysis 预览
+#[tauri::command]
+async fn merge_branch(
+    repo_path: String,
+    source: String,
+    mode: Option<String>,
+    message: Option<String>,
+    dry_run: Option<bool>,
+    cache: tauri::State<'_, RepoCache>,
+) -> Result<MergeResult, String> {
+    with_repo(&cache, repo_path.clone(), move |repo| {
+        let dry_run = dry_run.unwrap_or(false);
+        log_message("INFO", &format!("merge: attempt start | path={} source={} mode={:?} dry_run={}", repo_path, source, mode, dry_run));
+
+        let mode = resolve_merge_mode(repo, mode.as_deref())?;
+        if repo.state() != git2::RepositoryState::Clean {
+            return Err(format!("仓库当前处于 {:?} 状态，请先完成或中止进行中的操作", repo.state()));
+        }
+
+        let head = repo.head().map_err(|e| format!("Failed to get HEAD: {}", e))?;
+        let head_commit = head.peel_to_commit()
+            .map_err(|e| format!("Failed to get HEAD commit: {}", e))?;
+        let branch_name = if head.is_branch() { head.shorthand().map(|s| s.to_string()) } else { None };
+        let (source_oid, default_message) = resolve_merge_source(repo, &source)?;
+        let message = message.filter(|m| !m.trim().is_empty()).unwrap_or(default_message);
+
+        // 预览：合并分析 + 在内存中试合并，找出预计冲突的文件，不触碰工作区
+        let (analysis, _) = {
+            let annotated = repo.find_annotated_commit(source_oid)
+                .map_err(|e| format!("Failed to find commit: {}", e))?;
+            repo.merge_analysis(&[&annotated])
+                .map_err(|e| format!("Failed to analyze merge: {}", e))?
+        };
+        let analysis_name = if analysis.is_up_to_date() {
+            "up-to-date"
+        } else if analysis.is_fast_forward() {
+            "fast-forward"
+        } else {
+            "normal"
+        };
+        let source_commit = repo.find_commit(source_oid)
+            .map_err(|e| format!("Failed to find commit: {}", e))?;
+        let predicted_conflicts = if analysis.is_up_to_date() {
+            Vec::new()
+        } else {
+            let merged_index = repo.merge_commits(&head_commit, &source_commit, None)
+                .map_err(|e| format!("Failed to preview merge: {}", e))?;
+            conflicted_paths(&merged_index)
+        };
+
+        let mut result = MergeResult {
+            source: source.clone(),
+            mode: mode.clone(),
+            analysis: analysis_name.to_string(),
+            merged: false,
+            commit: None,
+            conflicted_files: predicted_conflicts,
+            dry_run,
+            message: String::new(),
+        };
+
+        if analysis.is_up_to_date() {
+            result.message = "Already up to date".to_string();
+            return Ok(result);
+        }
+        if mode == "ff-only" && !analysis.is_fast_forward() {
+            if dry_run {
+                // 预览时不报错，说明无法快进即可
+                result.conflicted_files.clear();
+                result.message = "无法快进：分支已分叉（ff-only）".to_string();
+                return Ok(result);
+            }
+            return Err("Cannot fast-forward: branches have diverged (ff-only)".to_string());
+        }
+        if dry_run {
+            result.message = if result.conflicted_files.is_empty() {
+                format!("可以合并（{}）", analysis_name)
+            } else {
+                format!("合并将产生冲突: {}", result.conflicted_files.join(", "))
+            };
+            return Ok(result);
+        }
+
+        // 快进：ff / ff-only 模式下直接移动当前分支
+        if analysis.is_fast_forward() && (mode == "ff" || mode == "ff-only") {
+            match &branch_name {
+                Some(name) => fast_forward_branch(repo, name, source_oid)?,
+                None => {
+                    let mut checkout = git2::build::CheckoutBuilder::new();
+                    checkout.safe();
+                    repo.checkout_tree(source_commit.as_object(), Some(&mut checkout))
+                        .map_err(|e| format!("Failed to checkout fast-forward target: {}", e))?;
+                    repo.set_head_detached(source_oid)
+                        .map_err(|e| format!("Failed to fast-forward: {}", e))?;
+                }
+            }
+            log_message("INFO", &format!("merge: fast-forward | source={} target={}", source, source_oid));
+            result.merged = true;
+            result.commit = Some(source_oid.to_string());
+            result.message = format!("Fast-forward to {:.7}", source_oid);
+            return Ok(result);
+        }
+
+        let annotated = repo.find_annotated_commit(source_oid)
+            .map_err(|e| format!("Failed to find commit: {}", e))?;
+        let mut checkout = git2::build::CheckoutBuilder::new();
+        checkout.safe().allow_conflicts(true).conflict_style_merge(true);
+        repo.merge(&[&annotated], None, Some(&mut checkout))
+            .map_err(|e| format!("Failed to merge: {}", e))?;
+
+        let mut index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
+        result.conflicted_files = conflicted_paths(&index);
+
+        if mode == "squash" {
+            // 与 git merge --squash 一致：只更新暂存区和工作区，不记录 MERGE_HEAD，由用户自行提交
+            repo.cleanup_state()
+                .map_err(|e| format!("Failed to clean up merge state: {}", e))?;
+            let _ = fs::write(repo.path().join("SQUASH_MSG"), format!("{}\n", message));
+            result.merged = result.conflicted_files.is_empty();
+            result.message = if result.merged {
+                "Squash merge staged; commit to complete".to_string()
+            } else {
+                format!("Squash merge has conflicts in: {}", result.conflicted_files.join(", "))
+            };
+            log_message("INFO", &format!("merge: squash staged | source={} conflicts={}", source, result.conflicted_files.len()));
+            return Ok(result);
+        }
+
+        if !result.conflicted_files.is_empty() {
+            // 保留 MERGE_HEAD，用自定义说明覆盖 MERGE_MSG，解决冲突后通过 commit_changes 完成合并
+            let _ = fs::write(repo.path().join("MERGE_MSG"), format!("{}\n", message));
+            log_message("WARN", &format!("merge: conflicts | source={} files={}", source, result.conflicted_files.join(",")));
+            result.message = format!(
+                "Merge conflicts in: [{}]. Resolve them and commit to complete the merge.",
+                result.conflicted_files.join(", ")
+            );
+            return Ok(result);
+        }
+
+        let commit_id = commit_merge_result(repo, &mut index, source_oid, &message)?;
+        log_message("INFO", &format!("merge: success | source={} commit={}", source, commit_id));
+        result.merged = true;
+        result.commit = Some(commit_id.to_string());
+        result.message = format!("Merged {} (commit: {})", source, commit_id);
+        Ok(result)
+    }).await
+}
+
+// 冲突文件概要
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConflictInfo {
+    pub path: String,
+    pub kind: ConflictKind,
+    pub binary: bool,
+}
+
+// 冲突文件的三方内容与带冲突标记的合并结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConflictContent {
+    pub path: String,
+    pub kind: ConflictKind,
+    pub binary: bool,
+    pub ancestor: Option<String>,
+    pub ours: Option<String>,
+    pub theirs: Option<String>,
+    pub merged: Option<String>,   // 一方被删除或为二进制文件时无法生成
+    pub automergeable: bool,
+    pub our_label: String,
+    pub their_label: String,
+}
+
+fn conflict_kind(conflict: &git2::IndexConflict) -> ConflictKind {
+    match (conflict.ancestor.is_some(), conflict.our.is_some(), conflict.their.is_some()) {
+        (true, false, true) => ConflictKind::DeletedByUs,
+        (true, true, false) => ConflictKind::DeletedByThem,
+        (true, false, false) => ConflictKind::BothDeleted,
+        (false, true, true) => ConflictKind::BothAdded,
+        (false, true, false) => ConflictKind::AddedByUs,
+        (false, false, true) => ConflictKind::AddedByThem,
+        _ => ConflictKind::BothModified,
+    }
+}
+
+fn find_conflict(index: &git2::Index, file_path: &str) -> Result<git2::IndexConflict, String> {
+    let conflicts = index.conflicts()
+        .map_err(|e| format!("Failed to read conflicts: {}", e))?;
+    for conflict in conflicts.flatten() {
+        let entry = conflict.our.as_ref().or(conflict.their.as_ref()).or(conflict.ancestor.as_ref());
+        if entry.map(|e| e.path.as_slice() == file_path.as_bytes()).unwrap_or(false) {
+            return Ok(conflict);
+        }
+    }
+    Err(format!("文件 {} 没有未解决的冲突", file_path))
+}
+
+// 读取冲突条目对应的 blob 内容与是否为二进制
+fn conflict_blob(repo: &Repository, entry: Option<&git2::IndexEntry>) -> Result<Option<(Vec<u8>, bool)>, String> {
+    match entry {
+        Some(entry) => {
+            let blob = repo.find_blob(entry.id)
+                .map_err(|e| format!("Failed to read blob {}: {}", entry.id, e))?;
+            Ok(Some((blob.content().to_vec(), blob.is_binary())))
+        }
+        None => Ok(None),
+    }
+}
+
+// 冲突标记中“对方”的标签：被合并 / 拣选 / 还原的提交，或变基停下的提交
+fn conflict_their_label(repo: &Repository) -> String {
+    for name in ["MERGE_HEAD", "CHERRY_PICK_HEAD", "REVERT_HEAD"] {
+        if let Ok(content) = fs::read_to_string(repo.path().join(name)) {
+            if let Some(line) = content.lines().next() {
+                return line.trim().chars().take(7).collect();
+            }
+        }
+    }
+    // 变基因冲突停下时，current 指向正在应用的步骤
+    load_rebase_state(repo)
+        .filter(|state| state.stopped.as_deref() == Some("conflict"))
+        .and_then(|state| state.todo.get(state.current).map(|item| item.commit_id.clone()))
+        .map(|id| id.chars().take(7).collect())
+        .unwrap_or_else(|| "theirs".to_string())
+}
+
+// 列出所有未解决的冲突文件
+#[tauri::command]
+async fn get_conflicts(repo_path: String, cache: tauri::State<'_, RepoCache>) -> Result<Vec<ConflictInfo>, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        let index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
+        let conflicts = index.conflicts()
+            .map_err(|e| format!("Failed to read conflicts: {}", e))?;
+
+        let mut result = Vec::new();
+        for conflict in conflicts.flatten() {
+            let entry = match conflict.our.as_ref().or(conflict.their.as_ref()).or(conflict.ancestor.as_ref()) {
+                Some(entry) => entry,
+                None => continue,
+            };
+            let mut binary = false;
+            for side in [conflict.our.as_ref(), conflict.their.as_ref()] {
+                if let Some((_, is_binary)) = conflict_blob(repo, side)? {
+                    binary |= is_binary;
+                }
+            }
+            result.push(ConflictInfo {
+                path: String::from_utf8_lossy(&entry.path).to_string(),
+                kind: conflict_kind(&conflict),
+                binary,
+            });
+        }
+        Ok(result)
+    }).await
+}
+
+// 获取冲突文件的共同祖先 / 我方 / 对方内容，以及 merge_file 生成的带冲突标记的合并结果
+#[tauri::command]
+async fn get_conflict_content(repo_path: String, file_path: String, cache: tauri::State<'_, RepoCache>) -> Result<ConflictContent, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        let index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
+        let conflict = find_conflict(&index, &file_path)?;
+
+        let ancestor = conflict_blob(repo, conflict.ancestor.as_ref())?;
+        let ours = conflict_blob(repo, conflict.our.as_ref())?;
+        let theirs = conflict_blob(repo, conflict.their.as_ref())?;
+        let binary = [&ancestor, &ours, &theirs].iter()
+            .any(|side| side.as_ref().map(|(_, b)| *b).unwrap_or(false));
+
+        let our_label = repo.head().ok()
+            .and_then(|h| h.shorthand().map(|s| s.to_string()))
+            .unwrap_or_else(|| "HEAD".to_string());
+        let their_label = conflict_their_label(repo);
+
+        let mut merged = None;
+        let mut automergeable = false;
+        if let (Some(our_entry), Some(their_entry), false) = (&conflict.our, &conflict.their, binary) {
+            // 双方新增的文件没有共同祖先，以空文件作为基准
+            let ancestor_entry = match &conflict.ancestor {
+                Some(entry) => git2::IndexEntry { path: entry.path.clone(), ..*entry },
+                None => {
+                    let empty = repo.blob(b"").map_err(|e| format!("Failed to write blob: {}", e))?;
+                    git2::IndexEntry { id: empty, path: our_entry.path.clone(), ..*our_entry }
+                }
+            };
+            let mut opts = git2::MergeFileOptions::new();
+            opts.ancestor_label("base").our_label(our_label.as_str()).their_label(their_label.as_str());
+            let result = repo.merge_file_from_index(&ancestor_entry, our_entry, their_entry, Some(&mut opts))
+                .map_err(|e| format!("Failed to merge file: {}", e))?;
+            automergeable = result.is_automergeable();
+            merged = Some(String::from_utf8_lossy(result.content()).to_string());
+        }
+
+        let to_text = |side: Option<(Vec<u8>, bool)>| {
+            side.map(|(content, is_binary)| if is_binary { String::new() } else { String::from_utf8_lossy(&content).to_string() })
+        };
+        Ok(ConflictContent {
+            path: file_path,
+            kind: conflict_kind(&conflict),
+            binary,
+            ancestor: to_text(ancestor),
+            ours: to_text(ours),
+            theirs: to_text(theirs),
+            merged,
+            automergeable,
+            our_label,
+            their_label,
+        })
+    }).await
+}
+
+// 解决冲突：ours / theirs 取某一方的版本，merged 使用编辑后的内容，resolved 以工作区当前文件为准
+#[tauri::command]
+async fn resolve_conflict(
+    repo_path: String,
+    file_path: String,
+    resolution: String,
+    content: Option<String>,
+    cache: tauri::State<'_, RepoCache>,
+) -> Result<String, String> {
+    with_repo(&cache, repo_path.clone(), move |repo| {
+        log_message("INFO", &format!("conflict: resolve | path={} file={} resolution={}", repo_path, file_path, resolution));
+        let workdir = repo.workdir().ok_or("Repository has no working directory")?.to_path_buf();
+        let mut index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
+        let conflict = find_conflict(&index, &file_path)?;
+        let full_path = workdir.join(&file_path);
+
+        // None 表示解决结果为删除该文件
+        let resolved: Option<Vec<u8>> = match resolution.as_str() {
+            "ours" => conflict_blob(repo, conflict.our.as_ref())?.map(|(c, _)| c),
+            "theirs" => conflict_blob(repo, conflict.their.as_ref())?.map(|(c, _)| c),
+            "merged" => Some(content.ok_or("使用编辑结果解决冲突时必须提供文件内容")?.into_bytes()),
+            "resolved" => {
+                if full_path.exists() {
+                    fs::read(&full_path).ok()
+                } else {
+                    None
+                }
+            }
+            other => return Err(format!("不支持的解决方式: {}，只允许 ours / theirs / merged / resolved", other)),
+        };
+
+        match resolved {
+            Some(bytes) => {
+                if let Some(parent) = full_path.parent() {
+                    fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+                }
+                fs::write(&full_path, bytes).map_err(|e| format!("Failed to write file: {}", e))?;
+                index.add_path(Path::new(&file_path))
+                    .map_err(|e| format!("Failed to stage resolved file: {}", e))?;
+            }
+            None => {
+                if full_path.exists() {
+                    fs::remove_file(&full_path).map_err(|e| format!("Failed to remove file: {}", e))?;
+                }
+                index.remove_path(Path::new(&file_path))
+                    .map_err(|e| format!("Failed to remove file from index: {}", e))?;
+            }
+        }
+        index.write().map_err(|e| format!("Failed to write index: {}", e))?;
+
+        let remaining = conflicted_paths(&index).len();
+        log_message("INFO", &format!("conflict: resolved | file={} remaining={}", file_path, remaining));
+        Ok(format!("Resolved {} ({} conflicts remaining)", file_path, remaining))
+    }).await
+}
+
+// 引用日志条目
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReflogEntry {
+    pub index: usize,
+    pub selector: String,      // 例如 HEAD@{2}
+    pub old_id: String,
+    pub new_id: String,
+    pub message: String,
+    pub committer: String,
+    pub timestamp: String,     // RFC 3339
+}
+
+// 将 "HEAD" / 分支短名 / 完整引用名解析为 reflog 使用的引用名
+fn resolve_reflog_name(repo: &Repository, reference: Option<&str>) -> Result<String, String> {
+    let name = reference.unwrap_or("HEAD");
+    if name == "HEAD" || name.starts_with("refs/") {
+        return Ok(name.to_string());
+    }
+    repo.resolve_reference_from_short_name(name)
+        .ok()
+        .and_then(|r| r.name().map(|n| n.to_string()))
+        .ok_or_else(|| format!("Reference not found: {}", name))
+}
+
+// 获取引用日志（默认 HEAD），最新的条目在前
+#[tauri::command]
+async fn get_reflog(repo_path: String, reference: Option<String>, limit: Option<usize>, cache: tauri::State<'_, RepoCache>) -> Result<Vec<ReflogEntry>, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        let name = resolve_reflog_name(repo, reference.as_deref())?;
+        let reflog = repo.reflog(&name)
+            .map_err(|e| format!("Failed to read reflog: {}", e))?;
+        let short_name = name.strip_prefix("refs/heads/").unwrap_or(&name);
+
+        let mut entries = Vec::new();
+        for (index, entry) in reflog.iter().enumerate().take(limit.unwrap_or(usize::MAX)) {
+            let committer = entry.committer();
+            let timestamp = chrono::DateTime::from_timestamp(committer.when().seconds(), 0)
+                .map(|dt| dt.with_timezone(&chrono::Local).to_rfc3339())
+                .unwrap_or_default();
+            entries.push(ReflogEntry {
+                index,
+                selector: format!("{}@{{{}}}", short_name, index),
+                old_id: entry.id_old().to_string(),
+                new_id: entry.id_new().to_string(),
+                message: entry.message().unwrap_or("").to_string(),
+                committer: committer.name().unwrap_or("Unknown").to_string(),
+                timestamp,
+            });
+        }
+        Ok(entries)
+    }).await
+}
+
+// 将引用恢复到某条 reflog 记录的位置；若是当前分支则执行 hard 重置（先创建备份）
+#[tauri::command]
+async fn restore_reflog_entry(repo_path: String, reference: Option<String>, index: usize, cache: tauri::State<'_, RepoCache>) -> Result<String, String> {
+    with_repo(&cache, repo_path.clone(), move |repo| {
+        log_message("INFO", &format!("reflog: restore | path={} ref={:?} index={}", repo_path, reference, index));
+        let name = resolve_reflog_name(repo, reference.as_deref())?;
+        let target = {
+            let reflog = repo.reflog(&name)
+                .map_err(|e| format!("Failed to read reflog: {}", e))?;
+            reflog.get(index)
+                .map(|entry| entry.id_new())
+                .ok_or_else(|| format!("Reflog entry {}@{{{}}} not found", name, index))?
+        };
+        let commit = repo.find_commit(target)
+            .map_err(|e| format!("Failed to find commit {}: {}", target, e))?;
+
+        let head = repo.head().ok();
+        let current_ref = head.as_ref().and_then(|h| h.name()).map(|n| n.to_string());
+        let is_current = name == "HEAD" || current_ref.as_deref() == Some(name.as_str());
+
+        if is_current {
+            if matches!(repo.state(), git2::RepositoryState::RebaseMerge | git2::RepositoryState::RebaseInteractive) {
+                return Err("变基进行中，请先完成或中止变基".to_string());
+            }
+            let backup = create_reset_backup(repo.path(), &format!("restore {}@{{{}}}", name, index))?;
+            repo.reset(commit.as_object(), git2::ResetType::Hard, None)
+                .map_err(|e| format!("Failed to reset: {}", e))?;
+            log_message("INFO", &format!("reflog: restored current | target={} backup={}", target, backup));
+            Ok(format!("已恢复到 {:.7}（恢复前的状态已备份为 {}）", target, backup))
+        } else {
+            let mut reference = repo.find_reference(&name)
+                .map_err(|e| format!("Reference not found: {}", e))?;
+            reference.set_target(target, &format!("gitlite: restore to {}@{{{}}}", name, index))
+                .map_err(|e| format!("Failed to move reference: {}", e))?;
+            log_message("INFO", &format!("reflog: restored | ref={} target={}", name, target));
+            Ok(format!("已将 {} 移动到 {:.7}", name, target))
+        }
+    }).await
+}
+
+// 收集仓库的远程配置
+fn list_remote_infos(repo: &Repository) -> Vec<RemoteInfo> {
+    let mut remotes = Vec::new();
+    if let Ok(names) = repo.remotes() {
+        for name in names.iter().flatten() {
+            if let Ok(remote) = repo.find_remote(name) {
+                let collect_refspecs = |direction: git2::Direction| -> Vec<String> {
+                    remote.refspecs()
+                        .filter(|spec| spec.direction() == direction)
+                        .filter_map(|spec| spec.str().map(|s| s.to_string()))
+                        .collect()
+                };
+                remotes.push(RemoteInfo {
+                    name: name.to_string(),
+                    url: remote.url().map(|u| u.to_string()),
+                    push_url: remote.pushurl().map(|u| u.to_string()),
+                    fetch_refspecs: collect_refspecs(git2::Direction::Fetch),
+                    push_refspecs: collect_refspecs(git2::Direction::Push),
+                });
+            }
+        }
+    }
+    remotes
+}
+
+fn validate_remote_name(name: &str) -> Result<(), String> {
+    if git2::Remote::is_valid_name(name) {
+        Ok(())
+    } else {
+        Err(format!("无效的远程仓库名称: {}", name))
+    }
+}
+
+// 列出远程仓库
+#[tauri::command]
+async fn get_remotes(repo_path: String, cache: tauri::State<'_, RepoCache>) -> Result<Vec<RemoteInfo>, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        Ok(list_remote_infos(repo))
+    }).await
+}
+
+// 添加远程仓库
+#[tauri::command]
+async fn add_remote(repo_path: String, name: String, url: String, cache: tauri::State<'_, RepoCache>) -> Result<String, String> {
+    with_repo(&cache, repo_path.clone(), move |repo| {
+        log_message("INFO", &format!("remote: add | path={} name={} url={}", repo_path, name, url));
+        validate_remote_name(&name)?;
+        repo.remote(&name, url.trim())
+            .map_err(|e| format!("Failed to add remote '{}': {}", name, e))?;
+        Ok(format!("Successfully added remote: {}", name))
+    }).await
+}
+
+// 重命名远程仓库（同时迁移远程跟踪分支与分支的上游配置）
+#[tauri::command]
+async fn rename_remote(repo_path: String, old_name: String, new_name: String, cache: tauri::State<'_, RepoCache>) -> Result<String, String> {
+    with_repo(&cache, repo_path.clone(), move |repo| {
+        log_message("INFO", &format!("remote: rename | path={} from={} to={}", repo_path, old_name, new_name));
+        validate_remote_name(&new_name)?;
+        let problems = repo.remote_rename(&old_name, &new_name)
+            .map_err(|e| format!("Failed to rename remote '{}': {}", old_name, e))?;
+
+        // 无法自动更新的非默认 refspec 需要用户手动处理
+        let problems: Vec<String> = problems.iter().flatten().map(|p| p.to_string()).collect();
+        if !problems.is_empty() {
+            log_message("WARN", &format!("remote: rename left refspecs unchanged | {}", problems.join(", ")));
+            return Ok(format!("Renamed remote {} to {}; refspecs not updated: {}", old_name, new_name, problems.join(", ")));
+        }
+        Ok(format!("Successfully renamed remote {} to {}", old_name, new_name))
+    }).await
+}
+
+// 删除远程仓库（同时删除其远程跟踪分支）
+#[tauri::command]
+async fn remove_remote(repo_path: String, name: String, cache: tauri::State<'_, RepoCache>) -> Result<String, String> {
+    with_repo(&cache, repo_path.clone(), move |repo| {
+        log_message("INFO", &format!("remote: remove | path={} name={}", repo_path, name));
+        repo.remote_delete(&name)
+            .map_err(|e| format!("Failed to remove remote '{}': {}", name, e))?;
+        Ok(format!("Successfully removed remote: {}", name))
+    }).await
+}
+
+// 修改远程仓库的拉取 / 推送 URL；push_url 传空字符串表示清除单独的推送地址
+#[tauri::command]
+async fn set_remote_url(
+    repo_path: String,
+    name: String,
+    url: Option<String>,
+    push_url: Option<String>,
+    cache: tauri::State<'_, RepoCache>,
+) -> Result<String, String> {
+    with_repo(&cache, repo_path.clone(), move |repo| {
+        log_message("INFO", &format!("remote: set url | path={} name={} url={:?} push_url={:?}", repo_path, name, url, push_url));
+        repo.find_remote(&name)
+            .map_err(|e| format!("Failed to find remote '{}': {}", name, e))?;
+
+        if let Some(url) = url {
+            if url.trim().is_empty() {
+                return Err("远程仓库 URL 不能为空".to_string());
+            }
+            repo.remote_set_url(&name, url.trim())
+                .map_err(|e| format!("Failed to set remote url: {}", e))?;
+        }
+        if let Some(push_url) = push_url {
+            let push_url = push_url.trim();
+            repo.remote_set_pushurl(&name, if push_url.is_empty() { None } else { Some(push_url) })
+                .map_err(|e| format!("Failed to set remote push url: {}", e))?;
+        }
+        Ok(format!("Successfully updated remote: {}", name))
+    }).await
+}
+
+// 克隆选项
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CloneRequest {
+    pub branch: Option<String>,     // 检出的分支，默认为远程 HEAD
+    pub depth: Option<i32>,         // 浅克隆深度
+    pub recurse_submodules: bool,   // 递归初始化并更新子模块
+    pub bare: bool,
+    pub mirror: bool,               // 镜像克隆（隐含 bare，获取所有引用）
+}
+
+// 克隆结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CloneResult {
+    pub path: String,
+    pub bare: bool,
+    pub head: Option<String>,
+    pub submodules: Vec<String>,
+    pub message: String,
+}
+
+// 更新子模块（paths 为空时更新全部），返回已更新的子模块路径；
+// init 时先初始化未初始化的子模块，否则跳过它们；recursive 时继续更新嵌套子模块
+fn update_submodules(
+    repo: &Repository,
+    paths: &[String],
+    init: bool,
+    recursive: bool,
+    credentials: &CredentialProvider,
+    operation: &RunningOperation,
+    app_handle: Option<&tauri::AppHandle>,
+) -> Result<Vec<String>, String> {
+    let mut updated = Vec::new();
+    let config = repo.config().ok();
+    let submodules = repo.submodules()
+        .map_err(|e| format!("Failed to list submodules: {}", e))?;
+    for mut submodule in submodules {
+        if operation.is_cancelled() {
+            return Err(OPERATION_CANCELLED.to_string());
+        }
+        let path = submodule.path().to_string_lossy().replace('\\', "/");
+        if !paths.is_empty() && !paths.iter().any(|p| p.trim_end_matches('/') == path) {
+            continue;
+        }
+        let name = submodule.name().unwrap_or(&path).to_string();
+        let initialized = config.as_ref()
+            .map(|c| c.get_string(&format!("submodule.{}.url", name)).is_ok())
+            .unwrap_or(false);
+        if !init && !initialized {
+            log_message("INFO", &format!("submodule: skip uninitialized {}", path));
+            continue;
+        }
+        log_message("INFO", &format!("submodule: updating {}", path));
+        let progress = ProgressReporter::new(app_handle.cloned(), "submodule", &path, Some(operation));
+        let mut callbacks = credentials.callbacks();
+        progress.attach(&mut callbacks);
+        let mut fetch_opts = git2::FetchOptions::new();
+        fetch_opts.remote_callbacks(callbacks);
+        let mut update_opts = git2::SubmoduleUpdateOptions::new();
+        update_opts.fetch(fetch_opts);
+        submodule.update(init, Some(&mut update_opts))
+            .map_err(|e| operation.or_cancelled(format!("Failed to update submodule '{}': {}", path, e)))?;
+        updated.push(path.clone());
+
+        if recursive {
+            let sub_repo = submodule.open()
+                .map_err(|e| format!("Failed to open submodule '{}': {}", path, e))?;
+            let nested = update_submodules(&sub_repo, &[], init, true, credentials, operation, app_handle)?;
+            updated.extend(nested.into_iter().map(|p| format!("{}/{}", path, p)));
         }
     }
-    
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    logs.push((timestamp, "SUCCESS".to_string(), "Git诊断完成".to_string()));
-    
-    Ok(logs)
+    Ok(updated)
 }
 
-// 拉取更改 - 带日志流
+// 克隆远程仓库：支持指定分支、浅克隆、递归子模块、bare / mirror，
+// 使用应用的代理与凭据配置并通过 git-progress 事件报告进度，完成后加入最近仓库列表
 #[tauri::command]
-async fn pull_changes_with_logs(repo_path: String) -> Result<Vec<(String, String, String)>, String> {
-    let mut logs = Vec::new();
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    
-    logs.push((timestamp, "INFO".to_string(), format!("pull: attempt start | path={}", repo_path)));
-    
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    logs.push((timestamp, "INFO".to_string(), "正在打开仓库...".to_string()));
-    
-    let repo = match Repository::open(&repo_path) {
-        Ok(r) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "INFO".to_string(), "仓库打开成功".to_string()));
-            r
-        },
-        Err(e) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "ERROR".to_string(), format!("打开仓库失败: {}", e)));
-            return Err(format!("Failed to open repository: {}", e));
+async fn clone_repository(
+    url: String,
+    dest: String,
+    options: Option<CloneRequest>,
+    operation_id: Option<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<CloneResult, String> {
+    run_blocking(move || {
+        let options = options.unwrap_or_default();
+        let operation = RunningOperation::begin("clone", operation_id);
+        log_message("INFO", &format!("clone: attempt start | url={} dest={} options={:?}", url, dest, options));
+
+        if url.trim().is_empty() {
+            return Err("仓库地址不能为空".to_string());
+        }
+        let dest_path = Path::new(&dest);
+        if dest_path.exists() {
+            let empty = fs::read_dir(dest_path).map(|mut d| d.next().is_none()).unwrap_or(false);
+            if !empty {
+                return Err(format!("目标目录已存在且不为空: {}", dest));
+            }
+        }
+        if options.mirror && options.branch.is_some() {
+            return Err("镜像克隆不能指定分支".to_string());
+        }
+        let bare = options.bare || options.mirror;
+
+        // 应用本地配置中启用的代理；否则让 libgit2 按 Git 配置 / 环境变量自动检测
+        let (proxy_config, is_from_git) = load_proxy_config()?;
+        let proxy_url = if is_from_git { None } else { build_proxy_url(&proxy_config).ok() };
+        let mut proxy_opts = git2::ProxyOptions::new();
+        match proxy_url.as_deref() {
+            Some(proxy) => {
+                log_message("INFO", &format!("clone: using proxy {}://{}:{}", proxy_config.protocol, proxy_config.host, proxy_config.port));
+                proxy_opts.url(proxy);
+            }
+            None => {
+                proxy_opts.auto();
+            }
+        }
+        let credentials = CredentialProvider::with_config(
+            git2::Config::open_default().ok(),
+            None,
+            "clone",
+            Some(app_handle.clone()),
+            Some(&operation),
+        );
+        let progress = ProgressReporter::new(Some(app_handle.clone()), "clone", &url, Some(&operation));
+        let mut callbacks = credentials.callbacks();
+        progress.attach(&mut callbacks);
+
+        let mut fetch_opts = git2::FetchOptions::new();
+        fetch_opts.remote_callbacks(callbacks);
+        fetch_opts.proxy_options(proxy_opts);
+        if let Some(depth) = options.depth.filter(|d| *d > 0) {
+            fetch_opts.depth(depth);
         }
-    };
 
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    logs.push((timestamp, "INFO".to_string(), "正在获取HEAD引用...".to_string()));
+        let mut checkout = git2::build::CheckoutBuilder::new();
+        checkout.progress(|_path, current, total| {
+            progress.emit(progress.progress("checkout", current, total));
+        });
 
-    let head = match repo.head() {
-        Ok(h) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "INFO".to_string(), "获取HEAD引用成功".to_string()));
-            h
-        },
-        Err(e) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "ERROR".to_string(), format!("获取HEAD失败: {}", e)));
-            return Err(format!("Failed to get HEAD: {}", e));
+        let mut builder = git2::build::RepoBuilder::new();
+        builder.bare(bare);
+        builder.fetch_options(fetch_opts);
+        if !bare {
+            builder.with_checkout(checkout);
+        }
+        if let Some(branch) = options.branch.as_deref().filter(|b| !b.is_empty()) {
+            builder.branch(branch);
+        }
+        if options.mirror {
+            // 镜像克隆：获取所有引用到同名位置
+            builder.remote_create(|repo, name, url| {
+                let remote = repo.remote_with_fetch(name, url, "+refs/*:refs/*")?;
+                repo.config()?.set_bool(&format!("remote.{}.mirror", name), true)?;
+                Ok(remote)
+            });
         }
-    };
-    
-    let branch_name = head.shorthand().unwrap_or("main");
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    logs.push((timestamp, "INFO".to_string(), format!("当前分支: {}", branch_name)));
 
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    logs.push((timestamp, "INFO".to_string(), "正在查找远程仓库 origin...".to_string()));
+        let repo = builder.clone(&url, dest_path).map_err(|e| {
+            log_message("ERROR", &format!("clone: failed: {} | url={}", e, url));
+            operation.or_cancelled(format!("Failed to clone repository: {}", e))
+        })?;
+        credentials.finish();
 
-    let mut remote = match repo.find_remote("origin") {
-        Ok(r) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "INFO".to_string(), "找到远程仓库 origin".to_string()));
-            r
-        },
-        Err(e) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "ERROR".to_string(), format!("未找到远程仓库 origin: {}", e)));
-            return Err(format!("Failed to find remote 'origin': {}", e));
-        }
-    };
+        let submodules = if options.recurse_submodules && !bare {
+            update_submodules(&repo, &[], true, true, &credentials, &operation, Some(&app_handle))?
+        } else {
+            Vec::new()
+        };
 
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    logs.push((timestamp, "INFO".to_string(), "正在设置认证...".to_string()));
+        let head = repo.head().ok().and_then(|h| h.shorthand().map(|s| s.to_string()));
+        let path = dest_path.canonicalize()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|_| dest.clone());
+        // bare 仓库没有工作区，无法在 GitLite 中打开，不加入最近仓库
+        if !bare {
+            if let Err(e) = add_recent_repo(path.clone()) {
+                log_message("WARN", &format!("clone: failed to save recent repo: {}", e));
+            }
+        }
 
-    // 认证与 Fetch 选项
-    let cfg = repo.config().ok();
-    let mut callbacks = git2::RemoteCallbacks::new();
-    callbacks.credentials(move |url, username_from_url, allowed| {
-        if allowed.contains(git2::CredentialType::DEFAULT) {
-            return git2::Cred::default();
+        let mut message = format!("已克隆到 {}", path);
+        if !submodules.is_empty() {
+            message = format!("{}，更新了 {} 个子模块", message, submodules.len());
         }
-        if allowed.contains(git2::CredentialType::SSH_KEY) {
-            return git2::Cred::ssh_key_from_agent(username_from_url.unwrap_or("git"));
+        log_message("INFO", &format!("clone: success | path={} head={:?}", path, head));
+        Ok(CloneResult { path, bare, head, submodules, message })
+    }).await
+}
+
+// 内置的 .gitignore 模板（src-tauri/templates/gitignore）
+const GITIGNORE_TEMPLATES: &[(&str, &str)] = &[
+    ("Node", include_str!("../templates/gitignore/Node.gitignore")),
+    ("Python", include_str!("../templates/gitignore/Python.gitignore")),
+    ("Rust", include_str!("../templates/gitignore/Rust.gitignore")),
+    ("Go", include_str!("../templates/gitignore/Go.gitignore")),
+    ("Java", include_str!("../templates/gitignore/Java.gitignore")),
+    ("C++", include_str!("../templates/gitignore/C++.gitignore")),
+];
+
+// 初始化选项
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InitRequest {
+    pub initial_branch: Option<String>,     // 默认取 init.defaultBranch，未配置时为 main
+    pub bare: bool,
+    pub gitignore_template: Option<String>, // 见 list_gitignore_templates
+    pub readme: bool,
+    pub initial_commit: bool,
+}
+
+// 初始化结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InitResult {
+    pub path: String,
+    pub bare: bool,
+    pub branch: String,
+    pub commit: Option<String>,
+    pub files: Vec<String>,
+    pub message: String,
+}
+
+// 列出内置的 .gitignore 模板名称
+#[tauri::command]
+async fn list_gitignore_templates() -> Result<Vec<String>, String> {
+    Ok(GITIGNORE_TEMPLATES.iter().map(|(name, _)| name.to_string()).collect())
+}
+
+// 初始化新仓库：可指定初始分支与 bare 模式，可选写入 .gitignore 模板、README 并创建初始提交
+#[tauri::command]
+async fn init_repository(path: String, options: Option<InitRequest>) -> Result<InitResult, String> {
+    run_blocking(move || {
+        let options = options.unwrap_or_default();
+        log_message("INFO", &format!("init: attempt start | path={} options={:?}", path, options));
+
+        let dir = Path::new(&path);
+        if dir.exists() && !dir.is_dir() {
+            return Err(format!("目标路径不是目录: {}", path));
         }
-        if allowed.contains(git2::CredentialType::USER_PASS_PLAINTEXT) {
-            if let Some(cfg) = cfg.as_ref() {
-                if let Ok(cred) = git2::Cred::credential_helper(cfg, url, username_from_url) {
-                    return Ok(cred);
-                }
-            }
+        if dir.exists() && Repository::open(dir).is_ok() {
+            return Err(format!("目录已经是 Git 仓库: {}", path));
         }
-        Err(git2::Error::from_str("No authentication method available"))
-    });
 
-    let mut fetch_opts = git2::FetchOptions::new();
-    fetch_opts.remote_callbacks(callbacks);
+        let template = match options.gitignore_template.as_deref().filter(|t| !t.is_empty()) {
+            Some(name) => Some(
+                GITIGNORE_TEMPLATES.iter()
+                    .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                    .map(|(_, content)| *content)
+                    .ok_or_else(|| format!("不支持的 .gitignore 模板: {}，可用模板: {}", name,
+                        GITIGNORE_TEMPLATES.iter().map(|(n, _)| *n).collect::<Vec<_>>().join(", ")))?,
+            ),
+            None => None,
+        };
+        if options.bare && (template.is_some() || options.readme || options.initial_commit) {
+            return Err("bare 仓库没有工作区，不能写入 .gitignore / README 或创建初始提交".to_string());
+        }
 
-    // 首先执行 fetch
-    let refspec = format!("refs/heads/{}:refs/remotes/origin/{}", branch_name, branch_name);
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    logs.push((timestamp, "INFO".to_string(), format!("开始获取远程分支 {}...", branch_name)));
+        let branch = match options.initial_branch.as_deref().map(|b| b.trim()).filter(|b| !b.is_empty()) {
+            Some(branch) => branch.to_string(),
+            None => git2::Config::open_default()
+                .and_then(|c| c.get_string("init.defaultBranch"))
+                .unwrap_or_else(|_| "main".to_string()),
+        };
+        if !git2::Branch::name_is_valid(&branch).unwrap_or(false) {
+            return Err(format!("无效的分支名: {}", branch));
+        }
 
-    match remote.fetch::<&str>(&[&refspec], Some(&mut fetch_opts), None) {
-        Ok(_) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "INFO".to_string(), "获取远程信息成功".to_string()));
-        },
-        Err(e) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "ERROR".to_string(), format!("获取远程信息失败: {}", e)));
-            return Err(format!("Failed to fetch: {}", e));
+        let mut init_opts = git2::RepositoryInitOptions::new();
+        init_opts.bare(options.bare)
+            .mkpath(true)
+            .initial_head(&branch);
+        let repo = Repository::init_opts(dir, &init_opts)
+            .map_err(|e| format!("Failed to initialize repository: {}", e))?;
+
+        // 写入初始文件（已存在的文件不覆盖）
+        let mut files = Vec::new();
+        if let Some(content) = template {
+            let gitignore = dir.join(".gitignore");
+            if gitignore.exists() {
+                log_message("WARN", "init: .gitignore already exists, skip template");
+            } else {
+                fs::write(&gitignore, content)
+                    .map_err(|e| format!("Failed to write .gitignore: {}", e))?;
+            }
+            files.push(".gitignore".to_string());
+        }
+        if options.readme {
+            let readme = dir.join("README.md");
+            if readme.exists() {
+                log_message("WARN", "init: README.md already exists, skip");
+            } else {
+                let name = dir.canonicalize().ok()
+                    .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+                    .unwrap_or_else(|| "Project".to_string());
+                fs::write(&readme, format!("# {}\n", name))
+                    .map_err(|e| format!("Failed to write README.md: {}", e))?;
+            }
+            files.push("README.md".to_string());
         }
-    }
 
-    // 获取远程分支引用
-    let remote_branch_ref = format!("refs/remotes/origin/{}", branch_name);
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    logs.push((timestamp, "INFO".to_string(), format!("正在获取远程分支引用: {}", remote_branch_ref)));
+        // 初始提交只包含上面写入的初始文件
+        let commit = if options.initial_commit {
+            let mut index = repo.index()
+                .map_err(|e| format!("Failed to get index: {}", e))?;
+            for file in &files {
+                index.add_path(Path::new(file))
+                    .map_err(|e| format!("Failed to add '{}' to index: {}", file, e))?;
+            }
+            index.write()
+                .map_err(|e| format!("Failed to write index: {}", e))?;
+            let tree_id = index.write_tree()
+                .map_err(|e| format!("Failed to write tree: {}", e))?;
+            let tree = repo.find_tree(tree_id)
+                .map_err(|e| format!("Failed to find tree: {}", e))?;
+            let signature = get_signature(&repo)?;
+            let oid = repo.commit(Some("HEAD"), &signature, &signature, "Initial commit", &tree, &[])
+                .map_err(|e| format!("Failed to create initial commit: {}", e))?;
+            Some(oid.to_string())
+        } else {
+            None
+        };
 
-    let remote_branch_oid = match repo.refname_to_id(&remote_branch_ref) {
-        Ok(oid) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "INFO".to_string(), "获取远程分支引用成功".to_string()));
-            oid
-        },
-        Err(e) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "ERROR".to_string(), format!("获取远程分支引用失败: {}", e)));
-            return Err(format!("Failed to get remote branch reference: {}", e));
+        let path = dir.canonicalize()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|_| path.clone());
+        // bare 仓库没有工作区，无法在 GitLite 中打开，不加入最近仓库
+        if !options.bare {
+            if let Err(e) = add_recent_repo(path.clone()) {
+                log_message("WARN", &format!("init: failed to save recent repo: {}", e));
+            }
         }
-    };
 
-    // 获取本地HEAD的OID
-    let local_head_oid = match repo.refname_to_id("HEAD") {
-        Ok(oid) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "INFO".to_string(), "获取本地HEAD引用成功".to_string()));
-            oid
-        },
-        Err(e) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "ERROR".to_string(), format!("获取本地HEAD引用失败: {}", e)));
-            return Err(format!("Failed to get local HEAD reference: {}", e));
-        }
-    };
+        let message = match &commit {
+            Some(oid) => format!("已初始化仓库 {}（分支 {}，初始提交 {:.7}）", path, branch, oid),
+            None => format!("已初始化仓库 {}（分支 {}）", path, branch),
+        };
+        log_message("INFO", &format!("init: success | path={} branch={} commit={:?}", path, branch, commit));
+        Ok(InitResult { path, bare: options.bare, branch, commit, files, message })
+    }).await
+}
 
-    // 检查是否需要合并
-    if remote_branch_oid == local_head_oid {
-        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-        logs.push((timestamp, "INFO".to_string(), "本地分支已是最新状态".to_string()));
-        
-        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-        logs.push((timestamp, "SUCCESS".to_string(), "操作完成 - 无需拉取".to_string()));
-        
-        return Ok(logs);
-    }
+// 子模块信息
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubmoduleInfo {
+    pub name: String,
+    pub path: String,
+    pub url: Option<String>,
+    pub branch: Option<String>,
+    pub head_id: Option<String>,    // 父仓库 HEAD 中记录的提交
+    pub index_id: Option<String>,   // 父仓库索引中记录的提交
+    pub workdir_id: Option<String>, // 子模块当前检出的提交
+    pub initialized: bool,          // 已写入 .git/config
+    pub cloned: bool,               // 工作区中已有子模块仓库
+    pub out_of_sync: bool,          // 检出的提交与父仓库记录的不一致
+    pub dirty: bool,                // 子模块内有未提交的修改或未跟踪文件
+    pub status: String,             // "uninitialized" | "not-cloned" | "out-of-sync" | "dirty" | "clean"
+}
 
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    logs.push((timestamp, "INFO".to_string(), "检测到远程更新，准备合并...".to_string()));
+// 工作区状态中的子模块变更（与普通文件变更分开报告）
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubmoduleChange {
+    pub path: String,
+    pub staged: bool,               // 父仓库索引中记录的提交已变更（新增 / 删除 / 修改）
+    pub new_commits: bool,          // 子模块检出的提交与索引记录不同
+    pub modified_content: bool,
+    pub untracked_content: bool,
+    pub index_change: Option<FileStatus>,   // 父仓库索引相对 HEAD：added / deleted / modified
+    pub workdir_change: Option<FileStatus>, // 工作区相对索引：added / deleted / modified（检出了其他提交）
+    pub index_id: Option<String>,
+    pub workdir_id: Option<String>,
+}
 
-    // 检查工作区是否有未提交的更改
-    let index = match repo.index() {
-        Ok(i) => i,
-        Err(e) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "ERROR".to_string(), format!("获取索引失败: {}", e)));
-            return Err(format!("Failed to get index: {}", e));
-        }
+fn build_submodule_info(repo: &Repository, submodule: &git2::Submodule) -> SubmoduleInfo {
+    let path = submodule.path().to_string_lossy().replace('\\', "/");
+    let name = submodule.name().unwrap_or(&path).to_string();
+    let status = repo.submodule_status(&name, git2::SubmoduleIgnore::None)
+        .unwrap_or(git2::SubmoduleStatus::empty());
+    let initialized = status.contains(git2::SubmoduleStatus::IN_CONFIG)
+        && repo.config().map(|c| c.get_string(&format!("submodule.{}.url", name)).is_ok()).unwrap_or(false);
+    let cloned = submodule.open().is_ok();
+    let out_of_sync = cloned && status.contains(git2::SubmoduleStatus::WD_MODIFIED);
+    let dirty = cloned && status.intersects(
+        git2::SubmoduleStatus::WD_INDEX_MODIFIED
+            | git2::SubmoduleStatus::WD_WD_MODIFIED
+            | git2::SubmoduleStatus::WD_UNTRACKED,
+    );
+    let summary = if !initialized {
+        "uninitialized"
+    } else if !cloned {
+        "not-cloned"
+    } else if out_of_sync {
+        "out-of-sync"
+    } else if dirty {
+        "dirty"
+    } else {
+        "clean"
     };
+    SubmoduleInfo {
+        name,
+        path,
+        url: submodule.url().map(|u| u.to_string()),
+        branch: submodule.branch().map(|b| b.to_string()),
+        head_id: submodule.head_id().map(|id| id.to_string()),
+        index_id: submodule.index_id().map(|id| id.to_string()),
+        workdir_id: submodule.workdir_id().map(|id| id.to_string()),
+        initialized,
+        cloned,
+        out_of_sync,
+        dirty,
+        status: summary.to_string(),
+    }
+}
 
-    // 检查是否有未暂存的更改
-    let diff_count = repo.diff_index_to_workdir(Some(&index), None)
-        .map_err(|e| format!("Failed to create diff: {}", e))?
-        .stats()
-        .map_err(|e| format!("Failed to get diff stats: {}", e))?
-        .files_changed();
+// 父仓库中子模块的路径 → 子模块名
+fn submodule_paths(repo: &Repository) -> std::collections::HashMap<String, String> {
+    repo.submodules()
+        .map(|subs| subs.iter()
+            .map(|s| {
+                let path = s.path().to_string_lossy().replace('\\', "/");
+                (path.clone(), s.name().unwrap_or(&path).to_string())
+            })
+            .collect())
+        .unwrap_or_default()
+}
 
-    if diff_count > 0 {
-        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-        logs.push((timestamp, "WARN".to_string(), format!("检测到 {} 个未提交的更改", diff_count)));
-        
-        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-        logs.push((timestamp, "ERROR".to_string(), "无法拉取：存在未提交的更改，请先提交或贮藏".to_string()));
-        
-        return Err("Cannot pull: You have uncommitted changes. Please commit or stash them first.".to_string());
+// 收集工作区中有变更的子模块；只检查 pathspecs 范围内的子模块，未要求时不扫描子模块内部的工作区
+fn collect_submodule_changes(
+    repo: &Repository,
+    submodules: &std::collections::HashMap<String, String>,
+    options: &WorkspaceStatusOptions,
+) -> Vec<SubmoduleChange> {
+    let pathspec = if options.pathspecs.is_empty() {
+        None
+    } else {
+        git2::Pathspec::new(options.pathspecs.iter()).ok()
+    };
+    let ignore = if options.scan_submodule_content { git2::SubmoduleIgnore::None } else { git2::SubmoduleIgnore::Dirty };
+    let mut changes = Vec::new();
+    for (path, name) in submodules {
+        if let Some(pathspec) = &pathspec {
+            if !pathspec.matches_path(Path::new(path), git2::PathspecFlags::DEFAULT) {
+                continue;
+            }
+        }
+        let status = match repo.submodule_status(name, ignore) {
+            Ok(status) => status,
+            Err(_) => continue,
+        };
+        let staged = status.intersects(
+            git2::SubmoduleStatus::INDEX_ADDED
+                | git2::SubmoduleStatus::INDEX_DELETED
+                | git2::SubmoduleStatus::INDEX_MODIFIED,
+        );
+        let new_commits = status.contains(git2::SubmoduleStatus::WD_MODIFIED);
+        let modified_content = status.intersects(git2::SubmoduleStatus::WD_INDEX_MODIFIED | git2::SubmoduleStatus::WD_WD_MODIFIED);
+        let untracked_content = status.contains(git2::SubmoduleStatus::WD_UNTRACKED);
+        let index_change = if status.contains(git2::SubmoduleStatus::INDEX_ADDED) {
+            Some(FileStatus::Added)
+        } else if status.contains(git2::SubmoduleStatus::INDEX_DELETED) {
+            Some(FileStatus::Deleted)
+        } else if status.contains(git2::SubmoduleStatus::INDEX_MODIFIED) {
+            Some(FileStatus::Modified)
+        } else {
+            None
+        };
+        let workdir_change = if status.contains(git2::SubmoduleStatus::WD_ADDED) {
+            Some(FileStatus::Added)
+        } else if status.contains(git2::SubmoduleStatus::WD_DELETED) {
+            Some(FileStatus::Deleted)
+        } else if new_commits {
+            Some(FileStatus::Modified)
+        } else {
+            None
+        };
+        if !(staged || new_commits || modified_content || untracked_content || workdir_change.is_some()) {
+            continue;
+        }
+        let submodule = repo.find_submodule(name).ok();
+        changes.push(SubmoduleChange {
+            path: path.clone(),
+            staged,
+            new_commits,
+            modified_content,
+            untracked_content,
+            index_change,
+            workdir_change,
+            index_id: submodule.as_ref().and_then(|s| s.index_id()).map(|id| id.to_string()),
+            workdir_id: submodule.as_ref().and_then(|s| s.workdir_id()).map(|id| id.to_string()),
+        });
     }
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    changes
+}
 
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    logs.push((timestamp, "INFO".to_string(), "工作区状态检查通过，开始合并...".to_string()));
+// 按路径或名称查找子模块
+fn find_submodule_by_path<'r>(repo: &'r Repository, path: &str) -> Result<git2::Submodule<'r>, String> {
+    let path = path.trim_end_matches('/');
+    repo.find_submodule(path)
+        .or_else(|e| submodule_paths(repo).get(path)
+            .and_then(|name| repo.find_submodule(name).ok())
+            .ok_or(e))
+        .map_err(|e| format!("Submodule not found '{}': {}", path, e))
+}
 
-    // 执行合并
-    let remote_commit = match repo.find_commit(remote_branch_oid) {
-        Ok(c) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "INFO".to_string(), "找到远程提交".to_string()));
-            c
-        },
-        Err(e) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "ERROR".to_string(), format!("查找远程提交失败: {}", e)));
-            return Err(format!("Failed to find remote commit: {}", e));
-        }
-    };
+// 列出子模块及其状态
+#[tauri::command]
+async fn get_submodules(repo_path: String, cache: tauri::State<'_, RepoCache>) -> Result<Vec<SubmoduleInfo>, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        let submodules = repo.submodules()
+            .map_err(|e| format!("Failed to list submodules: {}", e))?;
+        // 只在 HEAD 中存在的条目（已暂存删除）不再列出，与 git submodule status 一致
+        Ok(submodules.iter()
+            .filter(|s| {
+                let name = s.name().unwrap_or_default();
+                repo.submodule_status(name, git2::SubmoduleIgnore::All)
+                    .map(|st| st.intersects(git2::SubmoduleStatus::IN_INDEX | git2::SubmoduleStatus::IN_CONFIG))
+                    .unwrap_or(true)
+            })
+            .map(|s| build_submodule_info(repo, s))
+            .collect())
+    }).await
+}
 
-    let local_commit = match repo.find_commit(local_head_oid) {
-        Ok(c) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "INFO".to_string(), "找到本地提交".to_string()));
-            c
-        },
-        Err(e) => {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "ERROR".to_string(), format!("查找本地提交失败: {}", e)));
-            return Err(format!("Failed to find local commit: {}", e));
+// 初始化子模块（将 .gitmodules 中的 URL 写入 .git/config），paths 为空时初始化全部
+#[tauri::command]
+async fn init_submodules(repo_path: String, paths: Option<Vec<String>>, cache: tauri::State<'_, RepoCache>) -> Result<Vec<String>, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        let paths = paths.unwrap_or_default();
+        let mut initialized = Vec::new();
+        for mut submodule in repo.submodules().map_err(|e| format!("Failed to list submodules: {}", e))? {
+            let path = submodule.path().to_string_lossy().replace('\\', "/");
+            if !paths.is_empty() && !paths.iter().any(|p| p.trim_end_matches('/') == path) {
+                continue;
+            }
+            submodule.init(false)
+                .map_err(|e| format!("Failed to init submodule '{}': {}", path, e))?;
+            initialized.push(path);
         }
-    };
+        log_message("INFO", &format!("submodule: initialized {:?}", initialized));
+        Ok(initialized)
+    }).await
+}
 
-    // 检查是否是快进合并
-    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-    logs.push((timestamp, "INFO".to_string(), "正在检查合并类型...".to_string()));
+// 更新子模块到父仓库记录的提交；init 时同时初始化，recursive 时递归更新嵌套子模块
+#[tauri::command]
+async fn update_submodules_cmd(
+    repo_path: String,
+    paths: Option<Vec<String>>,
+    init: Option<bool>,
+    recursive: Option<bool>,
+    operation_id: Option<String>,
+    cache: tauri::State<'_, RepoCache>,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<String>, String> {
+    let cache = cache.inner().clone();
+    run_blocking(move || {
+        let operation = RunningOperation::begin("submodule", operation_id);
+        cache.with(&repo_path, |repo| {
+            let credentials = CredentialProvider::new(repo, "submodule", Some(app_handle.clone()), Some(&operation));
+            let updated = update_submodules(
+                repo,
+                &paths.unwrap_or_default(),
+                init.unwrap_or(true),
+                recursive.unwrap_or(false),
+                &credentials,
+                &operation,
+                Some(&app_handle),
+            )?;
+            credentials.finish();
+            Ok(updated)
+        })
+    }).await
+}
 
-    let is_ff = match repo.merge_base(local_head_oid, remote_branch_oid) {
-        Ok(base) => base == local_head_oid,
-        Err(_) => false,
-    };
+// 将 .gitmodules 中的 URL 同步到 .git/config 与子模块的远程配置
+#[tauri::command]
+async fn sync_submodules(repo_path: String, paths: Option<Vec<String>>, recursive: Option<bool>, cache: tauri::State<'_, RepoCache>) -> Result<Vec<String>, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        fn sync_all(repo: &Repository, paths: &[String], recursive: bool) -> Result<Vec<String>, String> {
+            let mut synced = Vec::new();
+            for mut submodule in repo.submodules().map_err(|e| format!("Failed to list submodules: {}", e))? {
+                let path = submodule.path().to_string_lossy().replace('\\', "/");
+                if !paths.is_empty() && !paths.iter().any(|p| p.trim_end_matches('/') == path) {
+                    continue;
+                }
+                submodule.sync()
+                    .map_err(|e| format!("Failed to sync submodule '{}': {}", path, e))?;
+                synced.push(path.clone());
+                if recursive {
+                    if let Ok(sub_repo) = submodule.open() {
+                        let nested = sync_all(&sub_repo, &[], true)?;
+                        synced.extend(nested.into_iter().map(|p| format!("{}/{}", path, p)));
+                    }
+                }
+            }
+            Ok(synced)
+        }
 
-    if is_ff {
-        // 快进合并
-        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-        logs.push((timestamp, "INFO".to_string(), "检测到快进合并，执行快进操作...".to_string()));
+        let synced = sync_all(repo, &paths.unwrap_or_default(), recursive.unwrap_or(false))?;
+        log_message("INFO", &format!("submodule: synced {:?}", synced));
+        Ok(synced)
+    }).await
+}
 
-        let mut reference = match repo.find_reference("HEAD") {
-            Ok(r) => {
-                let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-                logs.push((timestamp, "INFO".to_string(), "找到HEAD引用".to_string()));
-                r
-            },
-            Err(e) => {
-                let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-                logs.push((timestamp, "ERROR".to_string(), format!("查找HEAD引用失败: {}", e)));
-                return Err(format!("Failed to find HEAD reference: {}", e));
+// 添加子模块：克隆到 path，写入 .gitmodules 并暂存；可指定跟踪分支
+#[tauri::command]
+async fn add_submodule(
+    repo_path: String,
+    url: String,
+    path: String,
+    branch: Option<String>,
+    operation_id: Option<String>,
+    cache: tauri::State<'_, RepoCache>,
+    app_handle: tauri::AppHandle,
+) -> Result<SubmoduleInfo, String> {
+    let cache = cache.inner().clone();
+    run_blocking(move || {
+        let operation = RunningOperation::begin("submodule", operation_id);
+        cache.with(&repo_path, |repo| {
+            let path = path.trim().trim_end_matches('/').replace('\\', "/");
+            if url.trim().is_empty() || path.is_empty() {
+                return Err("子模块地址和路径不能为空".to_string());
+            }
+            if submodule_paths(repo).contains_key(&path) {
+                return Err(format!("子模块已存在: {}", path));
             }
+            log_message("INFO", &format!("submodule: add {} -> {}", url, path));
+
+            let credentials = CredentialProvider::new(repo, "submodule", Some(app_handle.clone()), Some(&operation));
+            {
+                let progress = ProgressReporter::new(Some(app_handle.clone()), "submodule", &path, Some(&operation));
+                let mut callbacks = credentials.callbacks();
+                progress.attach(&mut callbacks);
+                let mut fetch_opts = git2::FetchOptions::new();
+                fetch_opts.remote_callbacks(callbacks);
+                let mut update_opts = git2::SubmoduleUpdateOptions::new();
+                update_opts.fetch(fetch_opts);
+
+                let mut submodule = repo.submodule(url.trim(), Path::new(&path), true)
+                    .map_err(|e| format!("Failed to add submodule: {}", e))?;
+                let sub_repo = submodule.clone(Some(&mut update_opts))
+                    .map_err(|e| operation.or_cancelled(format!("Failed to clone submodule: {}", e)))?;
+
+                // 检出指定分支（对应 git submodule add -b）
+                if let Some(branch) = branch.as_deref().filter(|b| !b.is_empty()) {
+                    let remote_ref = sub_repo.find_reference(&format!("refs/remotes/origin/{}", branch))
+                        .map_err(|e| format!("Remote branch '{}' not found in submodule: {}", branch, e))?;
+                    let commit = remote_ref.peel_to_commit()
+                        .map_err(|e| format!("Failed to resolve branch '{}': {}", branch, e))?;
+                    let mut local = sub_repo.branch(branch, &commit, true)
+                        .map_err(|e| format!("Failed to create branch '{}': {}", branch, e))?;
+                    local.set_upstream(Some(&format!("origin/{}", branch))).ok();
+                    sub_repo.set_head(&format!("refs/heads/{}", branch))
+                        .map_err(|e| format!("Failed to set HEAD: {}", e))?;
+                    sub_repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+                        .map_err(|e| format!("Failed to checkout branch '{}': {}", branch, e))?;
+                }
+                submodule.add_finalize()
+                    .map_err(|e| format!("Failed to finalize submodule: {}", e))?;
+            }
+            credentials.finish();
+
+            if let Some(branch) = branch.as_deref().filter(|b| !b.is_empty()) {
+                let name = find_submodule_by_path(repo, &path)?.name().unwrap_or(&path).to_string();
+                repo.submodule_set_branch(&name, branch)
+                    .map_err(|e| format!("Failed to set submodule branch: {}", e))?;
+                let mut index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
+                index.add_path(Path::new(".gitmodules"))
+                    .and_then(|_| index.write())
+                    .map_err(|e| format!("Failed to stage .gitmodules: {}", e))?;
+            }
+
+            let submodule = find_submodule_by_path(repo, &path)?;
+            Ok(build_submodule_info(repo, &submodule))
+        })
+    }).await
+}
+
+// 删除子模块：移除工作区目录、索引条目、.gitmodules 与 .git/config 中的配置
+// 与 git rm 一致保留 .git/modules 下的仓库；子模块有本地修改时需 force 才会删除
+#[tauri::command]
+async fn remove_submodule(
+    repo_path: String,
+    path: String,
+    force: Option<bool>,
+    cache: tauri::State<'_, RepoCache>,
+) -> Result<String, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        let workdir = repo.workdir()
+            .ok_or_else(|| "Repository has no working directory".to_string())?
+            .to_path_buf();
+        let (name, path) = {
+            let submodule = find_submodule_by_path(repo, &path)?;
+            let path = submodule.path().to_string_lossy().replace('\\', "/");
+            (submodule.name().unwrap_or(&path).to_string(), path)
         };
+        if !force.unwrap_or(false) {
+            let status = repo.submodule_status(&name, git2::SubmoduleIgnore::None)
+                .map_err(|e| format!("Failed to get submodule status: {}", e))?;
+            let dirty = git2::SubmoduleStatus::WD_MODIFIED
+                | git2::SubmoduleStatus::WD_INDEX_MODIFIED
+                | git2::SubmoduleStatus::WD_WD_MODIFIED
+                | git2::SubmoduleStatus::WD_UNTRACKED;
+            if status.intersects(dirty) {
+                return Err(format!("子模块 {} 含有未提交的修改或未跟踪的文件，请先处理或强制删除", path));
+            }
+        }
+        log_message("INFO", &format!("submodule: remove {} ({})", path, name));
+
+        // 删除配置中 submodule.<name>.* 的所有条目
+        fn remove_section(config: &mut git2::Config, name: &str) -> Result<(), git2::Error> {
+            let prefix = format!("submodule.{}.", name);
+            let mut keys = Vec::new();
+            let mut entries = config.entries(None)?;
+            while let Some(entry) = entries.next() {
+                if let Some(key) = entry?.name() {
+                    if key.starts_with(&prefix) {
+                        keys.push(key.to_string());
+                    }
+                }
+            }
+            drop(entries);
+            for key in keys {
+                config.remove(&key)?;
+            }
+            Ok(())
+        }
 
-        if let Err(e) = reference.set_target(remote_branch_oid, "Fast-forward merge") {
-            let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-            logs.push((timestamp, "ERROR".to_string(), format!("快进合并失败: {}", e)));
-            return Err(format!("Failed to fast-forward: {}", e));
+        let gitmodules = workdir.join(".gitmodules");
+        let mut gitmodules_empty = false;
+        if gitmodules.exists() {
+            let mut config = git2::Config::open(&gitmodules)
+                .map_err(|e| format!("Failed to open .gitmodules: {}", e))?;
+            remove_section(&mut config, &name)
+                .map_err(|e| format!("Failed to update .gitmodules: {}", e))?;
+            gitmodules_empty = config.entries(None).map(|mut e| e.next().is_none()).unwrap_or(false);
+        }
+        if let Ok(mut config) = repo.config().and_then(|c| c.open_level(git2::ConfigLevel::Local)) {
+            remove_section(&mut config, &name)
+                .map_err(|e| format!("Failed to update .git/config: {}", e))?;
         }
 
-        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-        logs.push((timestamp, "INFO".to_string(), "快进合并成功".to_string()));
-        
-        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-        logs.push((timestamp, "SUCCESS".to_string(), "操作完成 - 快进合并成功".to_string()));
-        
-        Ok(logs)
+        let mut index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
+        index.remove_path(Path::new(&path))
+            .map_err(|e| format!("Failed to remove submodule from index: {}", e))?;
+        if gitmodules_empty {
+            fs::remove_file(&gitmodules)
+                .map_err(|e| format!("Failed to remove .gitmodules: {}", e))?;
+            index.remove_path(Path::new(".gitmodules")).ok();
+        } else if gitmodules.exists() {
+            index.add_path(Path::new(".gitmodules"))
+                .map_err(|e| format!("Failed to stage .gitmodules: {}", e))?;
+        }
+        index.write().map_err(|e| format!("Failed to write index: {}", e))?;
+
+        let sub_dir = workdir.join(&path);
+        if sub_dir.exists() {
+            fs::remove_dir_all(&sub_dir)
+                .map_err(|e| format!("Failed to remove submodule directory: {}", e))?;
+        }
+        Ok(format!("已删除子模块 {}", path))
+    }).await
+}
+
+// 工作树信息
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorktreeInfo {
+    pub name: Option<String>,       // 主工作树为 None
+    pub path: String,
+    pub branch: Option<String>,     // 检出的分支（分离头指针时为 None）
+    pub head: Option<String>,
+    pub is_main: bool,
+    pub is_current: bool,           // 是否为当前打开的工作树
+    pub locked: bool,
+    pub lock_reason: Option<String>,
+    pub prunable: bool,             // 工作树目录已不存在，可清理
+}
+
+// 添加工作树的参数
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorktreeAddRequest {
+    pub branch: Option<String>,      // 检出的分支，为空时按工作树名新建分支（同 git worktree add <path>）
+    pub new_branch: bool,            // 新建 branch 分支
+    pub start_point: Option<String>, // 新建分支的起点，默认 HEAD
+    pub lock: bool,
+}
+
+// 主仓库（所有工作树共享的 .git 目录）
+fn open_main_repository(repo: &Repository) -> Result<Repository, git2::Error> {
+    if repo.is_worktree() {
+        Repository::open(repo.commondir())
     } else {
-        // 需要创建合并提交
-        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-        logs.push((timestamp, "INFO".to_string(), "检测到需要合并提交，开始合并操作...".to_string()));
+        Repository::open(repo.path())
+    }
+}
 
-        let mut merge_index = match repo.merge_commits(&local_commit, &remote_commit, None) {
-            Ok(index) => {
-                let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-                logs.push((timestamp, "INFO".to_string(), "合并提交创建成功".to_string()));
-                index
-            },
-            Err(e) => {
-                let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-                logs.push((timestamp, "ERROR".to_string(), format!("合并提交失败: {}", e)));
-                return Err(format!("Failed to merge: {}", e));
-            }
+// 读取工作树 gitdir 下的 HEAD 文件：返回 (分支名, 提交 id)
+fn read_worktree_head(main: &Repository, gitdir: &Path) -> (Option<String>, Option<String>) {
+    let content = fs::read_to_string(gitdir.join("HEAD")).unwrap_or_default();
+    let content = content.trim();
+    match content.strip_prefix("ref:") {
+        Some(target) => {
+            let target = target.trim();
+            let head = main.refname_to_id(target).ok().map(|id| id.to_string());
+            (Some(target.trim_start_matches("refs/heads/").to_string()), head)
+        }
+        None if !content.is_empty() => (None, Some(content.to_string())),
+        None => (None, None),
+    }
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a.components().eq(b.components()),
+    }
+}
+
+// 列出主工作树与所有关联工作树
+fn list_worktree_infos(repo: &Repository) -> Result<Vec<WorktreeInfo>, git2::Error> {
+    let main = open_main_repository(repo)?;
+    let current = repo.workdir().unwrap_or(repo.path()).to_path_buf();
+
+    let main_path = main.workdir().unwrap_or(main.path()).to_path_buf();
+    let (branch, head) = read_worktree_head(&main, main.path());
+    let mut worktrees = vec![WorktreeInfo {
+        name: None,
+        path: main_path.to_string_lossy().trim_end_matches(['/', '\\']).to_string(),
+        branch,
+        head,
+        is_main: true,
+        is_current: same_path(&main_path, &current),
+        locked: false,
+        lock_reason: None,
+        prunable: false,
+    }];
+
+    for name in main.worktrees()?.iter().flatten() {
+        let worktree = match main.find_worktree(name) {
+            Ok(worktree) => worktree,
+            Err(_) => continue,
         };
+        let (locked, lock_reason) = match worktree.is_locked() {
+            Ok(git2::WorktreeLockStatus::Locked(reason)) => (true, reason),
+            _ => (false, None),
+        };
+        let (branch, head) = read_worktree_head(&main, &main.path().join("worktrees").join(name));
+        worktrees.push(WorktreeInfo {
+            name: Some(name.to_string()),
+            path: worktree.path().to_string_lossy().trim_end_matches(['/', '\\']).to_string(),
+            branch,
+            head,
+            is_main: false,
+            is_current: same_path(worktree.path(), &current),
+            locked,
+            lock_reason,
+            prunable: worktree.is_prunable(None).unwrap_or(false),
+        });
+    }
+    Ok(worktrees)
+}
 
-        // 将合并结果写入工作区
-        let merge_tree = repo.find_tree(merge_index.write_tree().map_err(|e| format!("Failed to write merge tree: {}", e))?)
-            .map_err(|e| format!("Failed to find merge tree: {}", e))?;
+// 若分支已在其他工作树中检出，返回该工作树路径
+fn branch_checked_out_elsewhere(repo: &Repository, branch: &str) -> Option<String> {
+    list_worktree_infos(repo).ok()?
+        .into_iter()
+        .find(|w| !w.is_current && !w.prunable && w.branch.as_deref() == Some(branch))
+        .map(|w| w.path)
+}
 
-        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-        logs.push((timestamp, "INFO".to_string(), "正在创建合并提交...".to_string()));
+// 列出工作树
+#[tauri::command]
+async fn get_worktrees(repo_path: String, cache: tauri::State<'_, RepoCache>) -> Result<Vec<WorktreeInfo>, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        list_worktree_infos(repo)
+            .map_err(|e| format!("Failed to list worktrees: {}", e))
+    }).await
+}
 
-        // 创建合并提交
-        let signature = git2::Signature::now("GitLite User", "gitlite@example.com")
-            .map_err(|e| format!("Failed to create signature: {}", e))?;
+// 添加工作树
+#[tauri::command]
+async fn add_worktree(repo_path: String, path: String, options: Option<WorktreeAddRequest>, cache: tauri::State<'_, RepoCache>) -> Result<WorktreeInfo, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        let options = options.unwrap_or_default();
+        let main = open_main_repository(repo)
+            .map_err(|e| format!("Failed to open main repository: {}", e))?;
+
+        let target = std::path::PathBuf::from(path.trim());
+        if path.trim().is_empty() {
+            return Err("工作树路径不能为空".to_string());
+        }
+        if target.exists() && fs::read_dir(&target).map(|mut d| d.next().is_some()).unwrap_or(true) {
+            return Err(format!("目标目录已存在且不为空: {}", target.display()));
+        }
 
-        let merge_commit_id = repo.commit(
-            Some("HEAD"),
-            &signature,
-            &signature,
-            &format!("Merge branch 'origin/{}'", branch_name),
-            &merge_tree,
-            &[&local_commit, &remote_commit],
-        ).map_err(|e| format!("Failed to create merge commit: {}", e))?;
+        // 工作树名取目录名，重名时追加序号
+        let base_name = target.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .filter(|n| !n.is_empty())
+            .ok_or_else(|| format!("Invalid worktree path: {}", path))?;
+        let existing: Vec<String> = main.worktrees()
+            .map(|names| names.iter().flatten().map(|n| n.to_string()).collect())
+            .unwrap_or_default();
+        let mut name = base_name.clone();
+        let mut suffix = 1;
+        while existing.contains(&name) {
+            name = format!("{}{}", base_name, suffix);
+            suffix += 1;
+        }
+
+        let branch_name = options.branch.as_deref().map(str::trim).filter(|b| !b.is_empty());
+        let reference = match branch_name {
+            Some(branch) if options.new_branch => {
+                let start = options.start_point.as_deref().filter(|s| !s.trim().is_empty()).unwrap_or("HEAD");
+                let commit = repo.revparse_single(start)
+                    .and_then(|o| o.peel_to_commit())
+                    .map_err(|e| format!("无法解析起点「{}」: {}", start, e.message()))?;
+                let branch = repo.branch(branch, &commit, false)
+                    .map_err(|e| format!("Failed to create branch: {}", e))?;
+                Some(branch.into_reference())
+            }
+            Some(branch) => {
+                if let Some(other) = branch_checked_out_elsewhere(repo, branch) {
+                    return Err(format!("分支「{}」已在工作树 {} 中检出", branch, other));
+                }
+                if repo.head().ok().and_then(|h| h.shorthand().map(|s| s == branch)).unwrap_or(false) {
+                    return Err(format!("分支「{}」是当前分支，无法在另一个工作树中检出", branch));
+                }
+                let branch = repo.find_branch(branch, git2::BranchType::Local)
+                    .map_err(|_| format!("未找到本地分支「{}」", branch))?;
+                Some(branch.into_reference())
+            }
+            None => None,
+        };
 
-        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-        logs.push((timestamp, "INFO".to_string(), "合并提交创建成功".to_string()));
-        
-        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-        logs.push((timestamp, "SUCCESS".to_string(), format!("操作完成 - 合并提交成功 (commit: {})", merge_commit_id)));
+        let mut add_opts = git2::WorktreeAddOptions::new();
+        add_opts.lock(options.lock);
+        add_opts.reference(reference.as_ref());
+        log_message("INFO", &format!("worktree: add {} -> {} (branch={:?})", name, target.display(), branch_name));
+        main.worktree(&name, &target, Some(&add_opts))
+            .map_err(|e| format!("Failed to add worktree: {}", e))?;
+
+        list_worktree_infos(repo)
+            .map_err(|e| format!("Failed to list worktrees: {}", e))?
+            .into_iter()
+            .find(|w| w.name.as_deref() == Some(name.as_str()))
+            .ok_or_else(|| "Failed to read new worktree".to_string())
+    }).await
+}
 
-        Ok(logs)
-    }
+// 锁定工作树，防止被清理
+#[tauri::command]
+async fn lock_worktree(repo_path: String, name: String, reason: Option<String>, cache: tauri::State<'_, RepoCache>) -> Result<String, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        let main = open_main_repository(repo)
+            .map_err(|e| format!("Failed to open main repository: {}", e))?;
+        let worktree = main.find_worktree(&name)
+            .map_err(|e| format!("Worktree not found '{}': {}", name, e))?;
+        worktree.lock(reason.as_deref().filter(|r| !r.trim().is_empty()))
+            .map_err(|e| format!("Failed to lock worktree: {}", e))?;
+        Ok(format!("已锁定工作树 {}", name))
+    }).await
 }
 
-// 获取已暂存文件的差异
+// 解锁工作树
 #[tauri::command]
-async fn get_staged_file_diff(repo_path: String, file_path: String) -> Result<String, String> {
-    let repo = Repository::open(&repo_path)
-        .map_err(|e| format!("Failed to open repository: {}", e))?;
-    
-    let head = repo.head()
-        .map_err(|e| format!("Failed to get HEAD: {}", e))?
-        .peel_to_commit()
-        .map_err(|e| format!("Failed to peel to commit: {}", e))?;
-    
-    let head_tree = head.tree()
-        .map_err(|e| format!("Failed to get HEAD tree: {}", e))?;
-    
-    let mut index = repo.index()
-        .map_err(|e| format!("Failed to get index: {}", e))?;
-    
-    let index_tree = repo.find_tree(index.write_tree().map_err(|e| format!("Failed to write tree: {}", e))?)
-        .map_err(|e| format!("Failed to find index tree: {}", e))?;
-    
-    let diff = repo.diff_tree_to_tree(Some(&head_tree), Some(&index_tree), None)
-        .map_err(|e| format!("Failed to create diff: {}", e))?;
-    
-    let mut diff_text = String::new();
-    diff.print(git2::DiffFormat::Patch, |delta, _hunk, line| {
-        // 检查是否是目标文件
-        let current_file = delta.new_file().path()
-            .or_else(|| delta.old_file().path())
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_default();
-        
-        if current_file == file_path {
-            // 添加diff行前缀
-            let prefix = match line.origin() {
-                '+' => "+",
-                '-' => "-",
-                ' ' => " ",
-                _ => "",
-            };
-            // 安全地处理 UTF-8 编码
-            let content = std::str::from_utf8(line.content()).unwrap_or("[INVALID UTF-8]");
-            diff_text.push_str(&format!("{}{}\n", prefix, content));
-        }
-        true
-    }).map_err(|e| format!("Failed to print diff: {}", e))?;
-    
-    Ok(diff_text)
+async fn unlock_worktree(repo_path: String, name: String, cache: tauri::State<'_, RepoCache>) -> Result<String, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        let main = open_main_repository(repo)
+            .map_err(|e| format!("Failed to open main repository: {}", e))?;
+        let worktree = main.find_worktree(&name)
+            .map_err(|e| format!("Worktree not found '{}': {}", name, e))?;
+        worktree.unlock()
+            .map_err(|e| format!("Failed to unlock worktree: {}", e))?;
+        Ok(format!("已解锁工作树 {}", name))
+    }).await
 }
 
-// 获取未暂存文件的差异
+// 清理目录已不存在的工作树（已锁定的保留），返回被清理的工作树名
 #[tauri::command]
-async fn get_unstaged_file_diff(repo_path: String, file_path: String) -> Result<String, String> {
-    let repo = Repository::open(&repo_path)
-        .map_err(|e| format!("Failed to open repository: {}", e))?;
-    
-    let index = repo.index()
-        .map_err(|e| format!("Failed to get index: {}", e))?;
-    
-    let diff = repo.diff_index_to_workdir(Some(&index), None)
-        .map_err(|e| format!("Failed to create diff: {}", e))?;
-    
-    let mut diff_text = String::new();
-    diff.print(git2::DiffFormat::Patch, |delta, _hunk, line| {
-        // 检查是否是目标文件
-        let current_file = delta.new_file().path()
-            .or_else(|| delta.old_file().path())
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_default();
-        
-        if current_file == file_path {
-            // 添加diff行前缀
-            let prefix = match line.origin() {
-                '+' => "+",
-                '-' => "-",
-                ' ' => " ",
-                _ => "",
+async fn prune_worktrees(repo_path: String, cache: tauri::State<'_, RepoCache>) -> Result<Vec<String>, String> {
+    with_repo(&cache, repo_path, move |repo| {
+        let main = open_main_repository(repo)
+            .map_err(|e| format!("Failed to open main repository: {}", e))?;
+        let names = main.worktrees()
+            .map_err(|e| format!("Failed to list worktrees: {}", e))?;
+        let mut pruned = Vec::new();
+        for name in names.iter().flatten() {
+            let worktree = match main.find_worktree(name) {
+                Ok(worktree) => worktree,
+                Err(_) => continue,
             };
-            // 安全地处理 UTF-8 编码
-            let content = std::str::from_utf8(line.content()).unwrap_or("[INVALID UTF-8]");
-            diff_text.push_str(&format!("{}{}\n", prefix, content));
+            if worktree.is_prunable(None).unwrap_or(false) {
+                worktree.prune(None)
+                    .map_err(|e| format!("Failed to prune worktree '{}': {}", name, e))?;
+                pruned.push(name.to_string());
+            }
         }
-        true
-    }).map_err(|e| format!("Failed to print diff: {}", e))?;
-    
-    Ok(diff_text)
+        log_message("INFO", &format!("worktree: pruned {:?}", pruned));
+        Ok(pruned)
+    }).await
 }
 
-// 获取未跟踪文件的内容
+// 以仓库方式打开指定工作树（name 为空时打开主工作树）
 #[tauri::command]
-async fn get_untracked_file_content(repo_path: String, file_path: String) -> Result<String, String> {
-    let full_path = Path::new(&repo_path).join(&file_path);
-    
-    if full_path.is_dir() {
-        return Err("Cannot show content of directory".to_string());
-    }
-    
-    let content = fs::read_to_string(&full_path)
-        .map_err(|e| format!("Failed to read file: {}", e))?;
-    
-    // 格式化为类似diff的格式，显示为新增文件
-    let lines: Vec<&str> = content.lines().collect();
-    let mut diff_text = format!("diff --git a/{} b/{}\n", file_path, file_path);
-    diff_text.push_str("new file mode 100644\n");
-    diff_text.push_str("index 0000000..0000000\n");
-    diff_text.push_str("--- /dev/null\n");
-    diff_text.push_str(&format!("+++ b/{}\n", file_path));
-    
-    for (i, line) in lines.iter().enumerate() {
-        diff_text.push_str(&format!("@@ -0,0 +{},1 @@\n", i + 1));
-        diff_text.push_str(&format!("+{}\n", line));
+async fn open_worktree(repo_path: String, name: Option<String>, cache: tauri::State<'_, RepoCache>) -> Result<RepoInfo, String> {
+    let worktree = with_repo(&cache, repo_path, move |repo| {
+        list_worktree_infos(repo)
+            .map_err(|e| format!("Failed to list worktrees: {}", e))?
+            .into_iter()
+            .find(|w| w.name == name)
+            .ok_or_else(|| format!("未找到工作树「{}」", name.as_deref().unwrap_or("main")))
+    }).await?;
+    if worktree.prunable {
+        return Err(format!("工作树目录已不存在: {}", worktree.path));
     }
-    
-    Ok(diff_text)
+    open_repository(worktree.path, cache).await
 }
 
-// 获取文件内容
-#[tauri::command]
-async fn get_file_content(repo_path: String, file_path: String) -> Result<String, String> {
-    let full_path = Path::new(&repo_path).join(&file_path);
-    
-    if full_path.is_dir() {
-        return Err("Cannot read content of directory".to_string());
+// Git LFS 指针文件（.gitattributes 中 filter=lfs 的路径在仓库中保存为指针）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LfsPointer {
+    pub oid: String, // sha256
+    pub size: u64,
+}
+
+// LFS 文件信息
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LfsFileInfo {
+    pub path: String,
+    pub tracked: bool,                 // .gitattributes 中 filter=lfs
+    pub pointer: Option<LfsPointer>,
+    pub available: bool,               // 对象已在 .git/lfs/objects 中
+    pub object_path: Option<String>,
+}
+
+// 指针文件不会超过 1 KB
+const LFS_POINTER_MAX_SIZE: u64 = 1024;
+// 超过该大小的 LFS 对象不作为文本读取
+const LFS_TEXT_MAX_SIZE: u64 = 5 * 1024 * 1024;
+
+fn parse_lfs_pointer(content: &[u8]) -> Option<LfsPointer> {
+    if content.len() as u64 > LFS_POINTER_MAX_SIZE {
+        return None;
     }
-    
-    let content = fs::read_to_string(&full_path)
-        .map_err(|e| format!("Failed to read file: {}", e))?;
-    
-    Ok(content)
+    let text = std::str::from_utf8(content).ok()?;
+    let mut lines = text.lines();
+    if !lines.next()?.starts_with("version https://git-lfs.github.com/spec/") {
+        return None;
+    }
+    let mut oid = None;
+    let mut size = None;
+    for line in lines {
+        if let Some(value) = line.strip_prefix("oid sha256:") {
+            oid = Some(value.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("size ") {
+            size = value.trim().parse().ok();
+        }
+    }
+    let oid = oid.filter(|o| o.len() == 64 && o.chars().all(|c| c.is_ascii_hexdigit()))?;
+    Some(LfsPointer { oid, size: size? })
 }
 
-// 获取贮藏列表
-#[tauri::command]
-async fn get_stash_list(repo_path: String) -> Result<Vec<StashInfo>, String> {
-    let mut repo = Repository::open(&repo_path)
-        .map_err(|e| format!("Failed to open repository: {}", e))?;
+fn is_lfs_tracked(repo: &Repository, path: &str) -> bool {
+    repo.get_attr(Path::new(path), "filter", git2::AttrCheckFlags::default())
+        .ok()
+        .flatten()
+        == Some("lfs")
+}
 
-    let mut stashes = Vec::new();
-    
-    // 获取当前分支名
-    let current_branch = match repo.head() {
-        Ok(head) => {
-            if let Some(name) = head.shorthand() {
-                name.to_string()
-            } else {
-                "detached".to_string()
-            }
-        },
-        Err(_) => "unknown".to_string(),
+// 找出由 LFS 跟踪的路径。只有全局属性文件或路径所在各级目录的 .gitattributes 提到 lfs 时
+// 才逐个查询属性，目录检查结果按目录缓存，未使用 LFS 的仓库不做任何属性查询
+fn lfs_tracked_paths<'a>(repo: &Repository, paths: impl Iterator<Item = &'a String>) -> Vec<String> {
+    let workdir = match repo.workdir() {
+        Some(workdir) => workdir,
+        None => return Vec::new(),
     };
-    
-    // 收集贮藏信息
-    let mut stash_data = Vec::new();
-    repo.stash_foreach(|_index, message, oid| {
-        stash_data.push((oid.to_string(), message.to_string()));
-        true // 继续遍历
-    }).map_err(|e| format!("Failed to iterate stashes: {}", e))?;
-    
-    // 处理每个贮藏
-    for (stash_id, stash_message) in stash_data {
-        let oid = match Oid::from_str(&stash_id) {
-            Ok(oid) => oid,
-            Err(_) => continue,
-        };
-        let timestamp = match repo.find_commit(oid) {
-            Ok(commit) => commit.time().seconds().to_string(),
-            Err(_) => "0".to_string(),
-        };
-        
-        stashes.push(StashInfo {
-            id: stash_id,
-            message: stash_message,
-            timestamp,
-            branch: current_branch.clone(),
+    let mentions_lfs = |file: &Path| fs::read_to_string(file).map(|c| c.contains("lfs")).unwrap_or(false);
+    let global = mentions_lfs(&repo.commondir().join("info").join("attributes"))
+        || repo.config()
+            .and_then(|c| c.get_path("core.attributesFile"))
+            .map(|file| mentions_lfs(&file))
+            .unwrap_or(false);
+
+    let mut dirs: HashMap<PathBuf, bool> = HashMap::new();
+    let mut tracked = Vec::new();
+    for path in paths {
+        let candidate = global || Path::new(path).ancestors().skip(1).any(|dir| {
+            *dirs.entry(dir.to_path_buf())
+                .or_insert_with(|| mentions_lfs(&workdir.join(dir).join(".gitattributes")))
         });
+        if candidate && is_lfs_tracked(repo, path) {
+            tracked.push(path.clone());
+        }
     }
-    
-    Ok(stashes)
+    tracked
 }
 
-// 创建贮藏
-#[tauri::command]
-async fn create_stash(repo_path: String, message: String) -> Result<String, String> {
-    log_message("INFO", &format!("create_stash: attempt start | path={} message={}", repo_path, message));
-    
-    let mut repo = Repository::open(&repo_path)
-        .map_err(|e| {
-            let error_msg = format!("Failed to open repository: {}", e);
-            log_message("ERROR", &format!("create_stash: {}", error_msg));
-            error_msg
-        })?;
+// 本地 LFS 对象路径：<gitdir>/lfs/objects/ab/cd/abcd...
+fn lfs_object_path(repo: &Repository, oid: &str) -> std::path::PathBuf {
+    let common = if repo.is_worktree() { repo.commondir() } else { repo.path() };
+    common.join("lfs").join("objects").join(&oid[0..2]).join(&oid[2..4]).join(oid)
+}
 
-    // 尝试从仓库获取签名，如果失败则使用默认签名
-    let signature = match repo.signature() {
-        Ok(sig) => {
-            log_message("DEBUG", &format!("create_stash: using repo signature | name={} email={}", 
-                sig.name().unwrap_or("unknown"), 
-                sig.email().unwrap_or("unknown")));
-            sig
-        },
-        Err(e) => {
-            log_message("WARN", &format!("create_stash: failed to get repo signature: {}, using default", e));
-            git2::Signature::now("GitLite User", "gitlite@example.com")
-                .map_err(|e| {
-                    let error_msg = format!("Failed to create default signature: {}", e);
-                    log_message("ERROR", &format!("create_stash: {}", error_msg));
-                    error_msg
-                })?
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+// 以 oid 和大小描述 LFS 对象，替代指针原文
+fn describe_lfs_object(repo: &Repository, pointer: Option<&LfsPointer>, size: u64) -> String {
+    match pointer {
+        Some(pointer) => {
+            let available = lfs_object_path(repo, &pointer.oid).is_file();
+            format!(
+                "[Git LFS] sha256:{} ({}){}",
+                pointer.oid,
+                format_size(pointer.size),
+                if available { "" } else { " · 本地未下载" }
+            )
         }
+        None => format!("[Git LFS] 文件内容 ({})", format_size(size)),
+    }
+}
+
+// 读取差异一侧的文件：返回 (指针, 大小)，文件不存在时返回 None；大文件不读取内容
+fn lfs_diff_side(repo: &Repository, file: &git2::DiffFile) -> Option<(Option<LfsPointer>, u64)> {
+    if !file.exists() {
+        return None;
+    }
+    if let Ok(blob) = repo.find_blob(file.id()) {
+        let size = blob.size() as u64;
+        return Some((parse_lfs_pointer(blob.content()), size));
+    }
+    // 工作区文件
+    let full_path = repo.workdir()?.join(file.path()?);
+    let size = fs::metadata(&full_path).ok()?.len();
+    let pointer = if size <= LFS_POINTER_MAX_SIZE {
+        fs::read(&full_path).ok().and_then(|c| parse_lfs_pointer(&c))
+    } else {
+        None
     };
+    Some((pointer, size))
+}
 
-    log_message("DEBUG", &format!("create_stash: signature obtained | name={} email={}", 
-        signature.name().unwrap_or("unknown"), 
-        signature.email().unwrap_or("unknown")));
+// 若差异涉及 LFS 文件，返回以 oid/大小表示的补丁文本，而不是指针原文或二进制内容
+fn lfs_delta_text(repo: &Repository, delta: &git2::DiffDelta) -> Option<String> {
+    let path = delta.new_file().path()
+        .or_else(|| delta.old_file().path())
+        .map(|p| p.to_string_lossy().replace('\\', "/"))?;
+    let old = lfs_diff_side(repo, &delta.old_file());
+    let new = lfs_diff_side(repo, &delta.new_file());
+    let has_pointer = [&old, &new].iter().any(|side| side.as_ref().is_some_and(|(p, _)| p.is_some()));
+    if !has_pointer && !is_lfs_tracked(repo, &path) {
+        return None;
+    }
 
-    // 检查工作区是否有更改
-    let has_changes = {
-        let statuses = repo.statuses(None)
-            .map_err(|e| {
-                let error_msg = format!("Failed to get status: {}", e);
-                log_message("ERROR", &format!("create_stash: {}", error_msg));
-                error_msg
-            })?;
-        
-        statuses.iter().any(|entry| {
-            let status = entry.status();
-            status.contains(git2::Status::WT_NEW) ||
-            status.contains(git2::Status::WT_MODIFIED) ||
-            status.contains(git2::Status::WT_DELETED) ||
-            status.contains(git2::Status::WT_TYPECHANGE) ||
-            status.contains(git2::Status::WT_RENAMED) ||
-            status.contains(git2::Status::INDEX_NEW) ||
-            status.contains(git2::Status::INDEX_MODIFIED) ||
-            status.contains(git2::Status::INDEX_DELETED)
-        })
-    };
-    
-    if !has_changes {
-        log_message("WARN", "create_stash: no changes to stash");
-        return Err("No changes to stash".to_string());
+    let mut text = format!("diff --git a/{} b/{}\n", path, path);
+    text.push_str(&match old { Some(_) => format!("--- a/{}\n", path), None => "--- /dev/null\n".to_string() });
+    text.push_str(&match new { Some(_) => format!("+++ b/{}\n", path), None => "+++ /dev/null\n".to_string() });
+    text.push_str(&format!(
+        "@@ -{} +{} @@\n",
+        if old.is_some() { "1,1" } else { "0,0" },
+        if new.is_some() { "1,1" } else { "0,0" }
+    ));
+    if let Some((pointer, size)) = &old {
+        text.push_str(&format!("-{}\n", describe_lfs_object(repo, pointer.as_ref(), *size)));
     }
-    
-    log_message("DEBUG", "create_stash: changes detected, proceeding with stash");
+    if let Some((pointer, size)) = &new {
+        text.push_str(&format!("+{}\n", describe_lfs_object(repo, pointer.as_ref(), *size)));
+    }
+    Some(text)
+}
 
-    let stash_id = repo.stash_save(&signature, &message, None)
-        .map_err(|e| {
-            let error_msg = format!("Failed to create stash: {}", e);
-            log_message("ERROR", &format!("create_stash: {}", error_msg));
-            error_msg
-        })?;
-    
-    log_message("INFO", &format!("create_stash: success | stash_id={}", stash_id));
-    Ok(format!("Successfully created stash: {}", stash_id))
+// 差异中所有 LFS 文件的补丁文本（路径 → 文本）
+fn lfs_diff_texts(repo: &Repository, diff: &git2::Diff) -> std::collections::HashMap<String, String> {
+    diff.deltas()
+        .filter_map(|delta| {
+            let path = delta.new_file().path()
+                .or_else(|| delta.old_file().path())
+                .map(|p| p.to_string_lossy().to_string())?;
+            lfs_delta_text(repo, &delta).map(|text| (path, text))
+        })
+        .collect()
 }
 
-// 应用贮藏
+// 读取工作区中的 LFS 文件：指针对应的对象已下载且为文本时返回对象内容，否则返回描述；非 LFS 文件返回 None
+fn lfs_workdir_content(repo: &Repository, file_path: &str, full_path: &Path) -> Option<String> {
+    let size = fs::metadata(full_path).ok()?.len();
+    if size <= LFS_POINTER_MAX_SIZE {
+        if let Some(pointer) = fs::read(full_path).ok().and_then(|c| parse_lfs_pointer(&c)) {
+            let object = lfs_object_path(repo, &pointer.oid);
+            if pointer.size <= LFS_TEXT_MAX_SIZE {
+                if let Some(text) = fs::read(&object).ok().and_then(|c| String::from_utf8(c).ok()) {
+                    return Some(text);
+                }
+            }
+            return Some(describe_lfs_object(repo, Some(&pointer), size));
+        }
+    }
+    if !is_lfs_tracked(repo, file_path) {
+        return None;
+    }
+    // 已检出（smudge）的 LFS 文件：大文件或二进制文件不作为文本返回
+    if size > LFS_TEXT_MAX_SIZE {
+        return Some(describe_lfs_object(repo, None, size));
+    }
+    match fs::read(full_path).ok().map(String::from_utf8) {
+        Some(Ok(text)) => Some(text),
+        _ => Some(describe_lfs_object(repo, None, size)),
+    }
+}
+
+// 获取 LFS 文件信息；revision 为空时读取工作区文件，否则读取该提交中的指针
 #[tauri::command]
-async fn apply_stash(repo_path: String, stash_id: String) -> Result<String, String> {
-    let mut repo = Repository::open(&repo_path)
-        .map_err(|e| format!("Failed to open repository: {}", e))?;
+async fn get_lfs_file_info(repo_path: String, file_path: String, revision: Option<String>, cache: tauri::State<'_, RepoCache>) -> Result<LfsFileInfo, String> {
+    with_repo(&cache, repo_path.clone(), move |repo| {
+        let pointer = match revision.as_deref().filter(|r| !r.trim().is_empty()) {
+            Some(revision) => {
+                let blob = repo.revparse_single(&format!("{}:{}", revision, file_path))
+                    .and_then(|o| o.peel_to_blob())
+                    .map_err(|e| format!("Failed to find '{}' at {}: {}", file_path, revision, e))?;
+                parse_lfs_pointer(blob.content())
+            }
+            None => {
+                let full_path = Path::new(&repo_path).join(&file_path);
+                match fs::metadata(&full_path) {
+                    Ok(meta) if meta.len() <= LFS_POINTER_MAX_SIZE => {
+                        fs::read(&full_path).ok().and_then(|c| parse_lfs_pointer(&c))
+                    }
+                    _ => None,
+                }
+            }
+        };
+        let object_path = pointer.as_ref().map(|p| lfs_object_path(repo, &p.oid));
+        Ok(LfsFileInfo {
+            path: file_path.clone(),
+            tracked: is_lfs_tracked(repo, &file_path),
+            available: object_path.as_ref().is_some_and(|p| p.is_file()),
+            object_path: object_path.map(|p| p.to_string_lossy().to_string()),
+            pointer,
+        })
+    }).await
+}
 
-    // 查找贮藏的索引 - 改进匹配逻辑
-    let mut stash_index = None;
-    let mut found_stash_info = None;
-    
-    repo.stash_foreach(|index, message, oid| {
-        let oid_str = oid.to_string();
-        // 支持完整SHA1 hash匹配和短hash匹配
-        if oid_str == stash_id || oid_str.starts_with(&stash_id) {
-            stash_index = Some(index);
-            found_stash_info = Some((oid_str, message.to_string()));
-            false // 停止遍历
-        } else {
-            true // 继续遍历
+// 仓库文件监听：工作区、引用或 HEAD 变化时向前端推送事件
+#[derive(Clone, Default)]
+pub struct RepoWatchers {
+    watchers: Arc<Mutex<HashMap<PathBuf, Arc<Mutex<notify::RecommendedWatcher>>>>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RepoChangeEvent {
+    pub repo_path: String,
+}
+
+// 最后一次相关变化后静默这么久才通知前端，合并编辑器保存、git 命令产生的一连串事件
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+// 持续有写入时，距第一次变化最多等待这么久也要通知一次
+const WATCH_MAX_DELAY: Duration = Duration::from_secs(2);
+
+// 会改变 HEAD 或当前操作状态的文件
+const HEAD_STATE_FILES: &[&str] = &["HEAD", "ORIG_HEAD", "MERGE_HEAD", "CHERRY_PICK_HEAD", "REVERT_HEAD", "REBASE_HEAD"];
+
+struct WatchRoots {
+    git_dir: PathBuf,
+    common_dir: PathBuf,
+    workdir: Option<PathBuf>,
+}
+
+#[derive(Default)]
+struct PendingChanges {
+    workspace: bool,
+    refs: bool,
+    head: bool,
+}
+
+fn canonical_path(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+// 将变化的路径归类，返回是否产生了新的待通知变化
+fn classify_change(repo: &Repository, roots: &WatchRoots, path: &Path, pending: &mut PendingChanges) -> bool {
+    // 锁文件会被重命名为目标文件，以目标文件的事件为准
+    if path.extension().is_some_and(|ext| ext == "lock") {
+        return false;
+    }
+    let before = (pending.workspace, pending.refs, pending.head);
+
+    if let Ok(rel) = path.strip_prefix(&roots.git_dir) {
+        match rel.to_str() {
+            Some(name) if HEAD_STATE_FILES.contains(&name) => pending.head = true,
+            Some("index") => pending.workspace = true,
+            _ => {}
         }
-    }).map_err(|e| format!("Failed to find stash: {}", e))?;
+    }
+    if let Ok(rel) = path.strip_prefix(&roots.common_dir) {
+        if rel.starts_with("refs") || rel == Path::new("packed-refs") {
+            pending.refs = true;
+        }
+    } else if path.starts_with(&roots.git_dir) {
+        // 独立于公共目录的 gitdir，上面已处理
+    } else if let Some(rel) = roots.workdir.as_ref().and_then(|w| path.strip_prefix(w).ok()) {
+        if !rel.as_os_str().is_empty() && !rel.starts_with(".git") && !repo.is_path_ignored(rel).unwrap_or(false) {
+            pending.workspace = true;
+        }
+    }
 
-    let index = match stash_index {
-        Some(idx) => idx,
-        None => {
-            // 提供更详细的错误信息
-            let mut available_stashes = Vec::new();
-            repo.stash_foreach(|_index, message, oid| {
-                available_stashes.push(format!("{}: {}", oid.to_string(), message.to_string()));
-                true
-            }).ok(); // 忽略错误，只是为了收集信息
-            
-            return Err(format!(
-                "Stash not found: {}. Available stashes: [{}]", 
-                stash_id, 
-                available_stashes.join(", ")
-            ));
+    before != (pending.workspace, pending.refs, pending.head)
+}
+
+fn emit_pending_changes(app_handle: &tauri::AppHandle, repo_path: &str, pending: &PendingChanges) {
+    let payload = RepoChangeEvent { repo_path: repo_path.to_string() };
+    let events = [
+        (pending.head, "head-changed"),
+        (pending.refs, "refs-changed"),
+        (pending.workspace, "workspace-changed"),
+    ];
+    for (changed, event) in events {
+        if changed {
+            let _ = app_handle.emit_all(event, payload.clone());
         }
+    }
+}
+
+// 逐个目录注册非递归监听并跳过 .git 与被忽略的目录（如 node_modules、target），
+// 避免递归监听整个工作区耗尽 inotify 的 max_user_watches
+fn watch_workdir_tree(
+    watcher: &mut notify::RecommendedWatcher,
+    repo: &Repository,
+    workdir: &Path,
+    dir: &Path,
+) -> notify::Result<()> {
+    use notify::{RecursiveMode, Watcher};
+
+    watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(()),
     };
+    for entry in entries.flatten() {
+        if entry.file_name() == ".git" || !entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+            continue;
+        }
+        let path = entry.path();
+        let ignored = path.strip_prefix(workdir)
+            .map(|rel| repo.is_path_ignored(rel).unwrap_or(false))
+            .unwrap_or(true);
+        if ignored {
+            continue;
+        }
+        // 目录可能在遍历过程中被删除，单个子目录失败不影响其余目录
+        if let Err(e) = watch_workdir_tree(watcher, repo, workdir, &path) {
+            log_message("WARN", &format!("监听目录失败 {}: {}", path.display(), e));
+        }
+    }
+    Ok(())
+}
 
-    // 创建贮藏应用选项
-    let mut options = git2::StashApplyOptions::new();
-    options.reinstantiate_index();
-    
-    match repo.stash_apply(index, Some(&mut options)) {
-        Ok(_) => {
-            let stash_info = found_stash_info.unwrap_or((stash_id, "unknown".to_string()));
-            Ok(format!("Successfully applied stash: {} ({})", stash_info.0, stash_info.1))
-        },
-        Err(e) => {
-            let error_msg = e.message();
-            let stash_info = found_stash_info.unwrap_or((stash_id, "unknown".to_string()));
-            
-            // 记录详细错误信息
-            eprintln!("Stash apply error for {}: {}", stash_info.0, error_msg);
-            
-            // 检查是否是重复应用的错误
-            if error_msg.contains("already applied") || error_msg.contains("nothing to commit") {
-                Ok(format!("Stash {} ({}) has already been applied or there are no changes to apply", 
-                          stash_info.0, stash_info.1))
-            } else if error_msg.contains("conflict") {
-                Err(format!("Failed to apply stash {} ({}): Conflicts detected. Error: {}. Please resolve conflicts manually.", 
-                           stash_info.0, stash_info.1, error_msg))
-            } else {
-                // 尝试不使用选项
-                match repo.stash_apply(index, None) {
-                    Ok(_) => {
-                        Ok(format!("Successfully applied stash: {} ({})", stash_info.0, stash_info.1))
-                    },
-                    Err(e2) => {
-                        let error_msg2 = e2.message();
-                        eprintln!("Second stash apply attempt failed for {}: {}", stash_info.0, error_msg2);
-                        
-                        if error_msg2.contains("already applied") || error_msg2.contains("nothing to commit") {
-                            Ok(format!("Stash {} ({}) has already been applied or there are no changes to apply", 
-                                      stash_info.0, stash_info.1))
-                        } else {
-                            Err(format!("Failed to apply stash {} ({}): {}. This may be because the stash has already been applied, there are conflicts, or the working directory is in an unexpected state.", 
-                                       stash_info.0, stash_info.1, error_msg2))
-                        }
+// 新建或移入工作区的目录需要补充监听
+fn watch_new_dir(watcher: &std::sync::Weak<Mutex<notify::RecommendedWatcher>>, repo: &Repository, roots: &WatchRoots, path: &Path) {
+    let workdir = match roots.workdir.as_ref() {
+        Some(workdir) => workdir,
+        None => return,
+    };
+    let rel = match path.strip_prefix(workdir) {
+        Ok(rel) if !rel.as_os_str().is_empty() && !rel.starts_with(".git") => rel,
+        _ => return,
+    };
+    if !path.is_dir() || repo.is_path_ignored(rel).unwrap_or(false) {
+        return;
+    }
+    if let Some(watcher) = watcher.upgrade() {
+        let mut watcher = watcher.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = watch_workdir_tree(&mut watcher, repo, workdir, path) {
+            log_message("WARN", &format!("监听目录失败 {}: {}", path.display(), e));
+        }
+    }
+}
+
+// 收集文件事件并在静默期结束后发送；监听器被释放后通道断开，线程随之退出
+fn run_watch_loop(
+    app_handle: tauri::AppHandle,
+    repo_path: String,
+    repo: Repository,
+    roots: WatchRoots,
+    watcher: std::sync::Weak<Mutex<notify::RecommendedWatcher>>,
+    rx: std::sync::mpsc::Receiver<notify::Result<notify::Event>>,
+) {
+    use notify::event::{EventKind, ModifyKind};
+    use std::sync::mpsc::RecvTimeoutError;
+
+    let mut pending = PendingChanges::default();
+    let mut first_change: Option<Instant> = None;
+    let mut deadline: Option<Instant> = None;
+    loop {
+        let received = match deadline {
+            Some(at) => rx.recv_timeout(at.saturating_duration_since(Instant::now())),
+            None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match received {
+            Ok(Ok(event)) => {
+                if matches!(event.kind, EventKind::Access(_)) {
+                    continue;
+                }
+                if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_))) {
+                    for path in &event.paths {
+                        watch_new_dir(&watcher, &repo, &roots, path);
+                    }
+                }
+                let mut changed = false;
+                for path in &event.paths {
+                    changed |= classify_change(&repo, &roots, path, &mut pending);
+                }
+                if changed || event.need_rescan() {
+                    if event.need_rescan() {
+                        pending = PendingChanges { workspace: true, refs: true, head: true };
                     }
+                    let now = Instant::now();
+                    let first = *first_change.get_or_insert(now);
+                    deadline = Some((now + WATCH_DEBOUNCE).min(first + WATCH_MAX_DELAY));
                 }
             }
+            Ok(Err(e)) => log_message("WARN", &format!("仓库监听出错 {}: {}", repo_path, e)),
+            Err(RecvTimeoutError::Timeout) => {
+                emit_pending_changes(&app_handle, &repo_path, &pending);
+                pending = PendingChanges::default();
+                first_change = None;
+                deadline = None;
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
         }
     }
 }
 
-// 删除贮藏
-#[tauri::command]
-async fn delete_stash(repo_path: String, stash_id: String) -> Result<String, String> {
-    let mut repo = Repository::open(&repo_path)
+fn start_repo_watcher(app_handle: tauri::AppHandle, repo_path: String) -> Result<Arc<Mutex<notify::RecommendedWatcher>>, String> {
+    use notify::{RecursiveMode, Watcher};
+
+    let repo = Repository::open(&repo_path)
         .map_err(|e| format!("Failed to open repository: {}", e))?;
+    let roots = WatchRoots {
+        git_dir: canonical_path(repo.path()),
+        common_dir: canonical_path(repo.commondir()),
+        workdir: repo.workdir().map(canonical_path),
+    };
 
-    // 查找贮藏的索引
-    let mut stash_index = None;
-    repo.stash_foreach(|index, _message, oid| {
-        if oid.to_string() == stash_id {
-            stash_index = Some(index);
-            false // 停止遍历
-        } else {
-            true // 继续遍历
-        }
-    }).map_err(|e| format!("Failed to find stash: {}", e))?;
+    // HEAD、index、packed-refs 等都在 git 目录顶层，只有 refs/ 需要递归监听；
+    // objects/、lfs/、logs/ 在提交、拉取时会产生大量无关事件
+    let mut git_targets: Vec<(PathBuf, RecursiveMode)> = vec![
+        (roots.git_dir.clone(), RecursiveMode::NonRecursive),
+        (roots.common_dir.join("refs"), RecursiveMode::Recursive),
+    ];
+    if roots.common_dir != roots.git_dir {
+        git_targets.push((roots.common_dir.clone(), RecursiveMode::NonRecursive));
+    }
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)
+        .map_err(|e| format!("Failed to create watcher: {}", e))?;
+    for (target, mode) in &git_targets {
+        watcher.watch(target, *mode)
+            .map_err(|e| format!("Failed to watch {}: {}", target.display(), e))?;
+    }
+    if let Some(workdir) = &roots.workdir {
+        watch_workdir_tree(&mut watcher, &repo, workdir, workdir)
+            .map_err(|e| format!("Failed to watch {}: {}", workdir.display(), e))?;
+    }
 
-    let index = stash_index.ok_or("Stash not found")?;
+    // 监听线程只持有弱引用，取消监听时监听器随注册表条目一起释放
+    let watcher = Arc::new(Mutex::new(watcher));
+    let weak = Arc::downgrade(&watcher);
+    std::thread::spawn(move || run_watch_loop(app_handle, repo_path, repo, roots, weak, rx));
+    Ok(watcher)
+}
 
-    repo.stash_drop(index)
-        .map_err(|e| format!("Failed to delete stash: {}", e))?;
-    
-    Ok(format!("Successfully deleted stash: {}", stash_id))
+// 开始监听仓库变化，推送 workspace-changed、refs-changed、head-changed 事件；同一仓库重复调用不会重复监听
+#[tauri::command]
+async fn watch_repository(repo_path: String, watchers: tauri::State<'_, RepoWatchers>, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let key = canonical_path(Path::new(&repo_path));
+    if watchers.watchers.lock().unwrap_or_else(|e| e.into_inner()).contains_key(&key) {
+        return Ok(());
+    }
+
+    // 大仓库逐个目录注册监听较慢，放到阻塞线程池中执行
+    let watcher = run_blocking(move || start_repo_watcher(app_handle, repo_path)).await?;
+    watchers.watchers.lock().unwrap_or_else(|e| e.into_inner()).entry(key).or_insert(watcher);
+    Ok(())
+}
+
+// 停止监听仓库
+#[tauri::command]
+async fn unwatch_repository(repo_path: String, watchers: tauri::State<'_, RepoWatchers>) -> Result<(), String> {
+    let key = canonical_path(Path::new(&repo_path));
+    watchers.watchers.lock().unwrap_or_else(|e| e.into_inner()).remove(&key);
+    Ok(())
 }
 
 // 创建系统托盘菜单
@@ -3009,6 +8008,8 @@ fn handle_window_event(event: &GlobalWindowEvent) {
 
 fn main() {
     tauri::Builder::default()
+        .manage(RepoCache::default())
+        .manage(RepoWatchers::default())
         .system_tray(create_system_tray())
         .on_system_tray_event(handle_system_tray_event)
         .on_window_event(|event| {
@@ -3049,8 +8050,160 @@ fn main() {
             delete_stash,
             get_proxy_config,
             save_proxy_config,
-            get_git_config_info
+            get_git_config_info,
+            get_rebase_todo,
+            start_rebase,
+            get_rebase_status,
+            continue_rebase,
+            skip_rebase,
+            abort_rebase,
+            cherry_pick,
+            cherry_pick_continue,
+            cherry_pick_abort,
+            revert,
+            revert_continue,
+            revert_abort,
+            reset_to,
+            list_reset_backups,
+            restore_reset_backup,
+            merge_branch,
+            get_conflicts,
+            get_conflict_content,
+            resolve_conflict,
+            get_reflog,
+            restore_reflog_entry,
+            pop_stash,
+            create_branch_from_stash,
+            get_stash_diff,
+            get_remotes,
+            add_remote,
+            rename_remote,
+            remove_remote,
+            set_remote_url,
+            submit_credentials,
+            cancel_operation,
+            clone_repository,
+            list_gitignore_templates,
+            init_repository,
+            get_submodules,
+            init_submodules,
+            update_submodules_cmd,
+            sync_submodules,
+            add_submodule,
+            remove_submodule,
+            get_worktrees,
+            add_worktree,
+            lock_worktree,
+            unlock_worktree,
+            prune_worktrees,
+            open_worktree,
+            get_lfs_file_info,
+            watch_repository,
+            unwatch_repository,
+            stage_paths,
+            unstage_paths,
+            stage_all,
+            unstage_all
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn porcelain_v2_ordinary_and_renamed() {
+        let oid = "0123456789012345678901234567890123456789";
+        let stdout = format!(
+            "1 .M N... 100644 100644 100644 {oid} {oid} src/main.rs\0\
+             1 A. N... 000000 100644 100644 {z} {oid} new file.txt\0\
+             2 R. N... 100644 100644 100644 {oid} {oid} R100 docs/new.md\0docs/old.md\0\
+             2 .R N... 100644 100644 100644 {oid} {oid} R090 b.txt\0a.txt\0",
+            oid = oid,
+            z = "0".repeat(40),
+        );
+        let entries = parse_porcelain_v2_status(stdout.as_bytes());
+        assert_eq!(entries.len(), 4);
+
+        assert_eq!(entries[0].path, "src/main.rs");
+        assert_eq!(entries[0].status, git2::Status::WT_MODIFIED);
+
+        assert_eq!(entries[1].path, "new file.txt");
+        assert_eq!(entries[1].status, git2::Status::INDEX_NEW);
+
+        assert_eq!(entries[2].path, "docs/new.md");
+        assert_eq!(entries[2].status, git2::Status::INDEX_RENAMED);
+        assert_eq!(entries[2].index_old_path.as_deref(), Some("docs/old.md"));
+        assert_eq!(entries[2].workdir_old_path, None);
+
+        assert_eq!(entries[3].path, "b.txt");
+        assert_eq!(entries[3].status, git2::Status::WT_RENAMED);
+        assert_eq!(entries[3].index_old_path, None);
+        assert_eq!(entries[3].workdir_old_path.as_deref(), Some("a.txt"));
+    }
+
+    #[test]
+    fn porcelain_v2_conflicted_untracked_and_ignored() {
+        let oid = "0123456789012345678901234567890123456789";
+        let stdout = format!(
+            "u UU N... 100644 100644 100644 100644 {oid} {oid} {oid} conflict.txt\0\
+             ? untracked dir/file.txt\0\
+             ! target/\0",
+            oid = oid,
+        );
+        let entries = parse_porcelain_v2_status(stdout.as_bytes());
+        assert_eq!(entries.len(), 3);
+
+        assert_eq!(entries[0].path, "conflict.txt");
+        assert_eq!(entries[0].status, git2::Status::CONFLICTED);
+
+        assert_eq!(entries[1].path, "untracked dir/file.txt");
+        assert_eq!(entries[1].status, git2::Status::WT_NEW);
+
+        assert_eq!(entries[2].path, "target/");
+        assert_eq!(entries[2].status, git2::Status::IGNORED);
+    }
+
+    #[test]
+    fn porcelain_v2_empty_output() {
+        assert!(parse_porcelain_v2_status(b"").is_empty());
+    }
+
+    const LFS_OID: &str = "4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393";
+
+    #[test]
+    fn lfs_pointer_valid() {
+        let content = format!("version https://git-lfs.github.com/spec/v1\noid sha256:{}\nsize 12345\n", LFS_OID);
+        let pointer = parse_lfs_pointer(content.as_bytes()).unwrap();
+        assert_eq!(pointer.oid, LFS_OID);
+        assert_eq!(pointer.size, 12345);
+    }
+
+    #[test]
+    fn lfs_pointer_with_extra_keys() {
+        let content = format!(
+            "version https://git-lfs.github.com/spec/v1\next-0-foo sha256:{oid}\noid sha256:{oid}\nsize 7\n",
+            oid = LFS_OID,
+        );
+        assert_eq!(parse_lfs_pointer(content.as_bytes()).unwrap().size, 7);
+    }
+
+    #[test]
+    fn lfs_pointer_rejects_invalid_content() {
+        // 缺少版本行
+        let no_version = format!("oid sha256:{}\nsize 1\n", LFS_OID);
+        assert!(parse_lfs_pointer(no_version.as_bytes()).is_none());
+        // oid 长度不对
+        assert!(parse_lfs_pointer(b"version https://git-lfs.github.com/spec/v1\noid sha256:abc\nsize 1\n").is_none());
+        // 缺少 size
+        let no_size = format!("version https://git-lfs.github.com/spec/v1\noid sha256:{}\n", LFS_OID);
+        assert!(parse_lfs_pointer(no_size.as_bytes()).is_none());
+        // 超过指针文件大小上限
+        let oversized = format!("version https://git-lfs.github.com/spec/v1\noid sha256:{}\nsize 1\n{}", LFS_OID, "x".repeat(2048));
+        assert!(parse_lfs_pointer(oversized.as_bytes()).is_none());
+        // 非 UTF-8 的二进制内容
+        assert!(parse_lfs_pointer(&[0xff, 0xfe, 0x00, 0x01]).is_none());
+    }
+}
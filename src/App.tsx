@@ -1,5 +1,5 @@
 import React, { useState, useEffect } from 'react' 
-import { useGit } from './hooks/useGit'
+import { useGit, createOperationId } from './hooks/useGit'
 import { useDarkMode } from './hooks/useDarkMode'
 import { invoke } from '@tauri-apps/api/tauri'
 import { listen } from '@tauri-apps/api/event'
@@ -11,7 +11,11 @@ import { FileList } from './components/FileList'
 import { UnifiedCommitView } from './components/UnifiedCommitView'
 import { LogModal } from './components/LogModal'
 import { ProxyConfigModal } from './components/ProxyConfigModal'
-import { CommitInfo, FileChange } from './types/git'
+import { CredentialPromptModal } from './components/CredentialPromptModal'
+import { CloneRepositoryModal } from './components/CloneRepositoryModal'
+import { InitRepositoryModal } from './components/InitRepositoryModal'
+import { WorktreeModal } from './components/WorktreeModal'
+import { CommitInfo, FileChange, GitProgress, CloneRequest, InitRequest } from './types/git'
 
 function App() {
   // 旧的三栏聚焦状态已废弃，保留为将来扩展可用；当前用 tab 切换
@@ -24,6 +28,14 @@ function App() {
     setAutoOpenEnabled,
     openRepository, 
     openRepositoryByPath,
+    cloneRepository,
+    initRepository,
+    getWorktrees,
+    addWorktree,
+    lockWorktree,
+    unlockWorktree,
+    pruneWorktrees,
+    openWorktree,
     checkoutBranch, 
     getCommitFiles, 
     getCommitsPaginated,
@@ -32,7 +44,8 @@ function App() {
     getSingleFileDiff,
     fetchChangesWithLogs,
     pushChangesWithRealtimeLogs,
-    pullChangesWithLogs
+    pullChangesWithLogs,
+    cancelOperation
   } = useGit()
   
   const { isDark, toggleDarkMode } = useDarkMode()
@@ -50,9 +63,15 @@ function App() {
   const [logModalTitle, setLogModalTitle] = useState('')
   const [logs, setLogs] = useState<Array<{timestamp: string, level: 'INFO' | 'DEBUG' | 'WARN' | 'ERROR' | 'SUCCESS', message: string}>>([])
   const [isOperationRunning, setIsOperationRunning] = useState(false)
+  const [progress, setProgress] = useState<GitProgress | null>(null)
+  const [operationId, setOperationId] = useState<string | null>(null)
+  const searchOperationRef = React.useRef<string | null>(null)
   
   // 代理配置弹窗状态
   const [proxyConfigOpen, setProxyConfigOpen] = useState(false)
+  const [cloneModalOpen, setCloneModalOpen] = useState(false)
+  const [initModalOpen, setInitModalOpen] = useState(false)
+  const [worktreeModalOpen, setWorktreeModalOpen] = useState(false)
 
   const handleCommitSelect = async (commit: CommitInfo) => {
     setSelectedCommit(commit)
@@ -92,17 +111,27 @@ function App() {
     if (!term.trim() || searchLoading) return
     setSearchLoading(true)
     setSearchResults(null)
+    const id = createOperationId('search')
+    searchOperationRef.current = id
     try {
-      const list = await searchCommits(term, 500)
-      setSearchResults(list)
+      const list = await searchCommits(term, 500, id)
+      if (searchOperationRef.current === id) {
+        setSearchResults(list)
+      }
     } catch (e) {
       console.error('全仓库搜索失败:', e)
     } finally {
+      searchOperationRef.current = null
       setSearchLoading(false)
     }
   }
 
   const handleClearSearchMode = () => {
+    // 退出搜索模式时取消仍在进行的全仓库搜索
+    if (searchOperationRef.current) {
+      cancelOperation(searchOperationRef.current).catch(() => {})
+      searchOperationRef.current = null
+    }
     setSearchResults(null)
   }
 
@@ -158,11 +187,14 @@ function App() {
     // 打开日志弹窗
     setLogModalTitle('拉取远程更改')
     setLogs([])
+    setProgress(null)
     setLogModalOpen(true)
     setIsOperationRunning(true)
+    const id = createOperationId('pull')
+    setOperationId(id)
     
     try {
-      const logData: Array<[string, string, string]> = await pullChangesWithLogs()
+      const logData: Array<[string, string, string]> = await pullChangesWithLogs(id)
       
       // 转换日志格式
       const formattedLogs = logData.map(([timestamp, level, message]) => ({
@@ -200,14 +232,17 @@ function App() {
     // 打开日志弹窗
     setLogModalTitle('获取远程更改')
     setLogs([])
+    setProgress(null)
     setLogModalOpen(true)
     setIsOperationRunning(true)
+    const id = createOperationId('fetch')
+    setOperationId(id)
     
     try {
-      const logData: Array<[string, string, string]> = await fetchChangesWithLogs()
+      const summary = await fetchChangesWithLogs(undefined, undefined, id)
       
       // 转换日志格式
-      const formattedLogs = logData.map(([timestamp, level, message]) => ({
+      const formattedLogs = summary.logs.map(([timestamp, level, message]) => ({
         timestamp,
         level: level as 'INFO' | 'DEBUG' | 'WARN' | 'ERROR',
         message
@@ -244,6 +279,7 @@ function App() {
     // 打开日志弹窗
     setLogModalTitle(title)
     setLogs([])
+    setProgress(null)
     setLogModalOpen(true)
     setIsOperationRunning(true)
     
@@ -283,6 +319,54 @@ function App() {
   }
 
 
+  const handleCloneRepository = async (url: string, dest: string, options: CloneRequest) => {
+    setCloneModalOpen(false)
+    setLogModalTitle('克隆仓库')
+    setLogs([{ timestamp: new Date().toLocaleTimeString(), level: 'INFO', message: `开始克隆 ${url} 到 ${dest}` }])
+    setProgress(null)
+    setLogModalOpen(true)
+    setIsOperationRunning(true)
+    const id = createOperationId('clone')
+    setOperationId(id)
+
+    try {
+      const result = await cloneRepository(url, dest, options, id)
+      setLogs(prev => [...prev, { timestamp: new Date().toLocaleTimeString(), level: 'SUCCESS', message: result.message }])
+      setSelectedCommit(null)
+      setCommitFiles([])
+      setSelectedFile(null)
+    } catch (error) {
+      console.error('克隆失败:', error)
+      setLogs(prev => [...prev, {
+        timestamp: new Date().toLocaleTimeString(),
+        level: 'ERROR' as const,
+        message: `克隆失败: ${error instanceof Error ? error.message : '未知错误'}`
+      }])
+    } finally {
+      setIsOperationRunning(false)
+    }
+  }
+
+  // 失败时抛出错误，由弹窗显示
+  const handleInitRepository = async (path: string, options: InitRequest) => {
+    await initRepository(path, options)
+    setInitModalOpen(false)
+    setSelectedCommit(null)
+    setCommitFiles([])
+    setSelectedFile(null)
+  }
+
+  // 切换到其他工作树后清空当前选择
+  const handleOpenWorktree = async (name?: string) => {
+    await openWorktree(name)
+    setSelectedCommit(null)
+    setCommitFiles([])
+    setSelectedFile(null)
+    setAllCommits([])
+    setHasMoreCommits(true)
+    setSearchResults(null)
+  }
+
   // 实时推送处理函数
   const handlePushChangesRealtime = async () => {
     if (!repoInfo) return
@@ -290,11 +374,14 @@ function App() {
     // 打开日志弹窗
     setLogModalTitle('推送本地更改 - 实时日志')
     setLogs([])
+    setProgress(null)
     setLogModalOpen(true)
     setIsOperationRunning(true)
+    const id = createOperationId('push')
+    setOperationId(id)
     
     try {
-      await pushChangesWithRealtimeLogs()
+      await pushChangesWithRealtimeLogs(id)
       setIsOperationRunning(false)
       
       // 重置状态
@@ -334,6 +421,26 @@ function App() {
     }
   }, [])
 
+  // 监听网络操作进度事件：服务端消息写入日志，其余更新进度条
+  useEffect(() => {
+    const unlisten = listen<GitProgress>('git-progress', (event) => {
+      const data = event.payload
+      if (data.phase === 'remote') {
+        setLogs(prev => [...prev, {
+          timestamp: new Date().toLocaleTimeString(),
+          level: 'DEBUG' as const,
+          message: `remote: ${data.message ?? ''}`
+        }])
+      } else {
+        setProgress(data)
+      }
+    })
+
+    return () => {
+      unlisten.then(fn => fn())
+    }
+  }, [])
+
   // 当仓库信息更新时，重置提交列表并退出全仓库搜索模式
   React.useEffect(() => {
     if (repoInfo) {
@@ -365,6 +472,9 @@ function App() {
       {/* 顶部工具栏 */}
       <TopToolbar
         onOpenRepository={openRepository}
+        onCloneRepository={() => setCloneModalOpen(true)}
+        onInitRepository={() => setInitModalOpen(true)}
+        onManageWorktrees={() => setWorktreeModalOpen(true)}
         onBranchSelect={handleBranchSelect}
         onOpenRemoteRepository={handleOpenRemoteRepository}
         onPullChanges={handlePullChanges}
@@ -453,6 +563,8 @@ function App() {
         title={logModalTitle}
         logs={logs}
         isRunning={isOperationRunning}
+        progress={progress}
+        onCancel={operationId ? () => cancelOperation(operationId).catch(() => {}) : undefined}
       />
       
       {/* 代理配置弹窗 */}
@@ -460,6 +572,35 @@ function App() {
         isOpen={proxyConfigOpen}
         onClose={() => setProxyConfigOpen(false)}
       />
+
+      {/* 克隆仓库弹窗 */}
+      <CloneRepositoryModal
+        isOpen={cloneModalOpen}
+        onClose={() => setCloneModalOpen(false)}
+        onClone={handleCloneRepository}
+      />
+
+      {/* 新建仓库弹窗 */}
+      <InitRepositoryModal
+        isOpen={initModalOpen}
+        onClose={() => setInitModalOpen(false)}
+        onInit={handleInitRepository}
+      />
+
+      {/* 工作树弹窗 */}
+      <WorktreeModal
+        isOpen={worktreeModalOpen}
+        onClose={() => setWorktreeModalOpen(false)}
+        getWorktrees={getWorktrees}
+        onAdd={addWorktree}
+        onLock={lockWorktree}
+        onUnlock={unlockWorktree}
+        onPrune={pruneWorktrees}
+        onOpen={handleOpenWorktree}
+      />
+
+      {/* 凭据输入弹窗 */}
+      <CredentialPromptModal />
     </div>
   )
 }
@@ -0,0 +1,153 @@
+import { useState } from 'react'
+import { Dialog, DialogContent, DialogHeader, DialogTitle } from './ui/dialog'
+import { Button } from './ui/button'
+import { Input } from './ui/input'
+import { Switch } from './ui/switch'
+import { Label } from './ui/label'
+import { CloneRequest } from '../types/git'
+import { open } from '@tauri-apps/api/dialog'
+import { FolderOpen } from 'lucide-react'
+
+interface CloneRepositoryModalProps {
+  isOpen: boolean
+  onClose: () => void
+  onClone: (url: string, dest: string, options: CloneRequest) => void
+}
+
+// 从仓库地址推断目录名：https://host/a/b.git → b
+const repoNameFromUrl = (url: string) => {
+  const name = url.trim().replace(/\/+$/, '').split(/[/:]/).pop() || ''
+  return name.replace(/\.git$/, '')
+}
+
+export function CloneRepositoryModal({ isOpen, onClose, onClone }: CloneRepositoryModalProps) {
+  const [url, setUrl] = useState('')
+  const [parentDir, setParentDir] = useState('')
+  const [dirName, setDirName] = useState('')
+  const [branch, setBranch] = useState('')
+  const [depth, setDepth] = useState('')
+  const [recurseSubmodules, setRecurseSubmodules] = useState(false)
+  const [bare, setBare] = useState(false)
+  const [mirror, setMirror] = useState(false)
+
+  const targetName = dirName || repoNameFromUrl(url) + (bare || mirror ? '.git' : '')
+  const separator = parentDir.includes('\\') ? '\\' : '/'
+  const dest = parentDir && targetName ? `${parentDir.replace(/[\\/]+$/, '')}${separator}${targetName}` : ''
+
+  const chooseParentDir = async () => {
+    const selected = await open({ directory: true, title: '选择克隆到的目录' })
+    if (selected && typeof selected === 'string') {
+      setParentDir(selected)
+    }
+  }
+
+  const handleSubmit = () => {
+    const depthValue = parseInt(depth)
+    onClone(url.trim(), dest, {
+      branch: !mirror && branch.trim() ? branch.trim() : undefined,
+      depth: !isNaN(depthValue) && depthValue > 0 ? depthValue : undefined,
+      recurse_submodules: !bare && !mirror && recurseSubmodules,
+      bare,
+      mirror,
+    })
+  }
+
+  return (
+    <Dialog open={isOpen} onOpenChange={onClose}>
+      <DialogContent className="max-w-lg">
+        <DialogHeader>
+          <DialogTitle>克隆仓库</DialogTitle>
+        </DialogHeader>
+
+        <div className="space-y-4">
+          <div className="space-y-2">
+            <Label htmlFor="clone-url">仓库地址</Label>
+            <Input
+              id="clone-url"
+              value={url}
+              onChange={(e) => setUrl(e.target.value)}
+              placeholder="https://github.com/user/repo.git 或 git@github.com:user/repo.git"
+              autoFocus
+            />
+          </div>
+
+          <div className="space-y-2">
+            <Label htmlFor="clone-parent">保存位置</Label>
+            <div className="flex gap-2">
+              <Input
+                id="clone-parent"
+                value={parentDir}
+                onChange={(e) => setParentDir(e.target.value)}
+                placeholder="选择目录"
+              />
+              <Button type="button" variant="outline" onClick={chooseParentDir} title="选择目录">
+                <FolderOpen className="h-4 w-4" />
+              </Button>
+            </div>
+          </div>
+
+          <div className="grid grid-cols-2 gap-4">
+            <div className="space-y-2">
+              <Label htmlFor="clone-dir">目录名</Label>
+              <Input
+                id="clone-dir"
+                value={dirName}
+                onChange={(e) => setDirName(e.target.value)}
+                placeholder={repoNameFromUrl(url) || '目录名'}
+              />
+            </div>
+            <div className="space-y-2">
+              <Label htmlFor="clone-branch">分支</Label>
+              <Input
+                id="clone-branch"
+                value={branch}
+                onChange={(e) => setBranch(e.target.value)}
+                placeholder="默认分支"
+                disabled={mirror}
+              />
+            </div>
+          </div>
+
+          <div className="space-y-2">
+            <Label htmlFor="clone-depth">克隆深度（留空为完整历史）</Label>
+            <Input
+              id="clone-depth"
+              type="number"
+              min="1"
+              value={depth}
+              onChange={(e) => setDepth(e.target.value)}
+              placeholder="完整历史"
+            />
+          </div>
+
+          <div className="space-y-3">
+            <div className="flex items-center space-x-2">
+              <Switch
+                id="clone-submodules"
+                checked={recurseSubmodules && !bare && !mirror}
+                onCheckedChange={(checked: boolean) => setRecurseSubmodules(checked)}
+                disabled={bare || mirror}
+              />
+              <Label htmlFor="clone-submodules" className="text-sm">递归克隆子模块</Label>
+            </div>
+            <div className="flex items-center space-x-2">
+              <Switch id="clone-bare" checked={bare} onCheckedChange={(checked: boolean) => setBare(checked)} />
+              <Label htmlFor="clone-bare" className="text-sm">Bare 仓库（无工作区）</Label>
+            </div>
+            <div className="flex items-center space-x-2">
+              <Switch id="clone-mirror" checked={mirror} onCheckedChange={(checked: boolean) => setMirror(checked)} />
+              <Label htmlFor="clone-mirror" className="text-sm">镜像克隆（所有引用）</Label>
+            </div>
+          </div>
+
+          {dest && <div className="text-xs text-muted-foreground break-all">克隆到: {dest}</div>}
+
+          <div className="flex justify-end space-x-2">
+            <Button variant="outline" onClick={onClose}>取消</Button>
+            <Button onClick={handleSubmit} disabled={!url.trim() || !dest}>克隆</Button>
+          </div>
+        </div>
+      </DialogContent>
+    </Dialog>
+  )
+}
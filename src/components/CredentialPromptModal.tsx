@@ -0,0 +1,114 @@
+import { useState, useEffect } from 'react'
+import { Dialog, DialogContent, DialogHeader, DialogTitle } from './ui/dialog'
+import { Button } from './ui/button'
+import { Input } from './ui/input'
+import { Switch } from './ui/switch'
+import { Label } from './ui/label'
+import { CredentialRequest, CredentialResponse } from '../types/git'
+import { invoke } from '@tauri-apps/api/tauri'
+import { listen } from '@tauri-apps/api/event'
+
+// 监听后端的 credential-request 事件，弹窗询问用户名 / 密码或 SSH 私钥口令
+export function CredentialPromptModal() {
+  const [request, setRequest] = useState<CredentialRequest | null>(null)
+  const [username, setUsername] = useState('')
+  const [password, setPassword] = useState('')
+  const [save, setSave] = useState(false)
+
+  useEffect(() => {
+    const unlisten = listen<CredentialRequest>('credential-request', (event) => {
+      setRequest(event.payload)
+      setUsername(event.payload.username || '')
+      setPassword('')
+      setSave(false)
+    })
+
+    return () => {
+      unlisten.then(fn => fn())
+    }
+  }, [])
+
+  const respond = async (response: CredentialResponse) => {
+    if (!request) return
+    const requestId = request.id
+    setRequest(null)
+    setPassword('')
+    try {
+      await invoke('submit_credentials', { requestId, response })
+    } catch (error) {
+      console.error('Failed to submit credentials:', error)
+    }
+  }
+
+  const isPassphrase = request?.kind === 'passphrase'
+
+  return (
+    <Dialog open={request !== null} onOpenChange={(open) => { if (!open) respond({ cancel: true }) }}>
+      <DialogContent className="max-w-md">
+        <DialogHeader>
+          <DialogTitle>{isPassphrase ? '输入 SSH 私钥口令' : '需要认证'}</DialogTitle>
+        </DialogHeader>
+
+        {request && (
+          <form
+            className="space-y-4"
+            onSubmit={(e) => {
+              e.preventDefault()
+              respond(isPassphrase ? { password } : { username, password, save })
+            }}
+          >
+            <div className="text-sm text-muted-foreground break-all">
+              {isPassphrase ? request.key_path : request.url}
+              {request.attempt > 1 && <span className="ml-2">（第 {request.attempt} 次尝试）</span>}
+            </div>
+
+            {!isPassphrase && (
+              <div className="space-y-2">
+                <Label htmlFor="credential-username">用户名</Label>
+                <Input
+                  id="credential-username"
+                  value={username}
+                  onChange={(e) => setUsername(e.target.value)}
+                  placeholder="用户名"
+                  autoFocus
+                />
+              </div>
+            )}
+
+            <div className="space-y-2">
+              <Label htmlFor="credential-password">{isPassphrase ? '口令' : '密码 / 访问令牌'}</Label>
+              <Input
+                id="credential-password"
+                type="password"
+                value={password}
+                onChange={(e) => setPassword(e.target.value)}
+                placeholder={isPassphrase ? '口令' : '密码或访问令牌'}
+                autoFocus={isPassphrase}
+              />
+            </div>
+
+            {!isPassphrase && (
+              <div className="flex items-center space-x-2">
+                <Switch
+                  id="credential-save"
+                  checked={save}
+                  onCheckedChange={(checked: boolean) => setSave(checked)}
+                />
+                <Label htmlFor="credential-save" className="text-sm">
+                  保存到 Git 凭据助手
+                </Label>
+              </div>
+            )}
+
+            <div className="flex justify-end space-x-2">
+              <Button type="button" variant="outline" onClick={() => respond({ cancel: true })}>
+                取消
+              </Button>
+              <Button type="submit">确定</Button>
+            </div>
+          </form>
+        )}
+      </DialogContent>
+    </Dialog>
+  )
+}
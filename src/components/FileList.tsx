@@ -51,6 +51,10 @@ export function FileList({ files, selectedFile, onFileSelect }: FileListProps) {
         return '删除'
       case 'renamed':
         return '重命名'
+      case 'copied':
+        return '复制'
+      case 'typechange':
+        return '类型变更'
       default:
         return status
     }
@@ -0,0 +1,143 @@
+import { useState, useEffect } from 'react'
+import { Dialog, DialogContent, DialogHeader, DialogTitle } from './ui/dialog'
+import { Button } from './ui/button'
+import { Input } from './ui/input'
+import { Select, SelectContent, SelectItem, SelectTrigger } from './ui/select'
+import { Switch } from './ui/switch'
+import { Label } from './ui/label'
+import { InitRequest } from '../types/git'
+import { invoke } from '@tauri-apps/api/tauri'
+import { open } from '@tauri-apps/api/dialog'
+import { FolderOpen } from 'lucide-react'
+
+interface InitRepositoryModalProps {
+  isOpen: boolean
+  onClose: () => void
+  onInit: (path: string, options: InitRequest) => Promise<void>
+}
+
+export function InitRepositoryModal({ isOpen, onClose, onInit }: InitRepositoryModalProps) {
+  const [path, setPath] = useState('')
+  const [initialBranch, setInitialBranch] = useState('main')
+  const [templates, setTemplates] = useState<string[]>([])
+  const [template, setTemplate] = useState('none')
+  const [readme, setReadme] = useState(true)
+  const [initialCommit, setInitialCommit] = useState(true)
+  const [bare, setBare] = useState(false)
+  const [creating, setCreating] = useState(false)
+  const [submitError, setSubmitError] = useState<string | null>(null)
+
+  // 加载内置 .gitignore 模板列表
+  useEffect(() => {
+    if (isOpen && templates.length === 0) {
+      invoke<string[]>('list_gitignore_templates')
+        .then(setTemplates)
+        .catch(error => console.error('Failed to load gitignore templates:', error))
+    }
+  }, [isOpen])
+
+  const choosePath = async () => {
+    const selected = await open({ directory: true, title: '选择新仓库目录' })
+    if (selected && typeof selected === 'string') {
+      setPath(selected)
+    }
+  }
+
+  const handleSubmit = async () => {
+    setCreating(true)
+    setSubmitError(null)
+    try {
+      await onInit(path.trim(), {
+        initial_branch: initialBranch.trim() || undefined,
+        bare,
+        gitignore_template: !bare && template !== 'none' ? template : undefined,
+        readme: !bare && readme,
+        initial_commit: !bare && initialCommit,
+      })
+    } catch (error) {
+      setSubmitError(error instanceof Error ? error.message : String(error))
+    } finally {
+      setCreating(false)
+    }
+  }
+
+  return (
+    <Dialog open={isOpen} onOpenChange={onClose}>
+      <DialogContent className="max-w-lg">
+        <DialogHeader>
+          <DialogTitle>新建仓库</DialogTitle>
+        </DialogHeader>
+
+        <div className="space-y-4">
+          <div className="space-y-2">
+            <Label htmlFor="init-path">仓库目录</Label>
+            <div className="flex gap-2">
+              <Input
+                id="init-path"
+                value={path}
+                onChange={(e) => setPath(e.target.value)}
+                placeholder="不存在的目录会自动创建"
+                autoFocus
+              />
+              <Button type="button" variant="outline" onClick={choosePath} title="选择目录">
+                <FolderOpen className="h-4 w-4" />
+              </Button>
+            </div>
+          </div>
+
+          <div className="grid grid-cols-2 gap-4">
+            <div className="space-y-2">
+              <Label htmlFor="init-branch">初始分支</Label>
+              <Input
+                id="init-branch"
+                value={initialBranch}
+                onChange={(e) => setInitialBranch(e.target.value)}
+                placeholder="main"
+              />
+            </div>
+            <div className="space-y-2">
+              <Label htmlFor="init-template">.gitignore 模板</Label>
+              <Select value={template} onValueChange={setTemplate}>
+                <SelectTrigger>
+                  {template === 'none' ? '不使用' : template}
+                </SelectTrigger>
+                <SelectContent>
+                  <SelectItem value="none">不使用</SelectItem>
+                  {templates.map(name => (
+                    <SelectItem key={name} value={name}>{name}</SelectItem>
+                  ))}
+                </SelectContent>
+              </Select>
+            </div>
+          </div>
+
+          <div className="space-y-3">
+            <div className="flex items-center space-x-2">
+              <Switch id="init-readme" checked={readme && !bare} onCheckedChange={(checked: boolean) => setReadme(checked)} disabled={bare} />
+              <Label htmlFor="init-readme" className="text-sm">创建 README.md</Label>
+            </div>
+            <div className="flex items-center space-x-2">
+              <Switch id="init-commit" checked={initialCommit && !bare} onCheckedChange={(checked: boolean) => setInitialCommit(checked)} disabled={bare} />
+              <Label htmlFor="init-commit" className="text-sm">创建初始提交</Label>
+            </div>
+            <div className="flex items-center space-x-2">
+              <Switch id="init-bare" checked={bare} onCheckedChange={(checked: boolean) => setBare(checked)} />
+              <Label htmlFor="init-bare" className="text-sm">Bare 仓库（无工作区）</Label>
+            </div>
+          </div>
+
+          {submitError && (
+            <div className="text-sm text-destructive break-all">{submitError}</div>
+          )}
+
+          <div className="flex justify-end space-x-2">
+            <Button variant="outline" onClick={onClose}>取消</Button>
+            <Button onClick={handleSubmit} disabled={!path.trim() || creating}>
+              {creating ? '创建中...' : '创建'}
+            </Button>
+          </div>
+        </div>
+      </DialogContent>
+    </Dialog>
+  )
+}
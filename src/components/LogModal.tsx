@@ -1,7 +1,8 @@
 import { useEffect, useRef } from 'react'
 import { Dialog, DialogContent, DialogHeader, DialogTitle } from './ui/dialog'
 import { Button } from './ui/button'
-import { Copy, Download } from 'lucide-react'
+import { Copy, Download, Square } from 'lucide-react'
+import { GitProgress } from '../types/git'
 
 interface LogEntry {
   timestamp: string
@@ -15,9 +16,11 @@ interface LogModalProps {
   title: string
   logs: LogEntry[]
   isRunning: boolean
+  progress?: GitProgress | null
+  onCancel?: () => void
 }
 
-export function LogModal({ isOpen, onClose, title, logs, isRunning }: LogModalProps) {
+export function LogModal({ isOpen, onClose, title, logs, isRunning, progress, onCancel }: LogModalProps) {
   const logContainerRef = useRef<HTMLDivElement>(null)
 
   // 自动滚动到最新日志
@@ -55,6 +58,22 @@ export function LogModal({ isOpen, onClose, title, logs, isRunning }: LogModalPr
     }
   }
 
+  const formatBytes = (bytes: number) => {
+    if (bytes < 1024) return `${bytes} B`
+    if (bytes < 1024 * 1024) return `${(bytes / 1024).toFixed(1)} KiB`
+    return `${(bytes / 1024 / 1024).toFixed(2)} MiB`
+  }
+
+  const getPhaseLabel = (phase: string) => {
+    switch (phase) {
+      case 'receiving': return '接收对象'
+      case 'resolving': return '处理增量'
+      case 'packing': return '打包对象'
+      case 'pushing': return '推送对象'
+      default: return phase
+    }
+  }
+
   const copyLogs = () => {
     const logText = logs.map(log => 
       `[${log.timestamp}] [${log.level}] ${log.message}`
@@ -87,6 +106,17 @@ export function LogModal({ isOpen, onClose, title, logs, isRunning }: LogModalPr
               {title} - 实时日志
             </DialogTitle>
             <div className="flex items-center gap-2">
+              {isRunning && onCancel && (
+                <Button
+                  variant="ghost"
+                  size="sm"
+                  onClick={onCancel}
+                  className="text-red-400 hover:text-red-300 hover:bg-red-900/20"
+                  title="取消操作"
+                >
+                  <Square className="h-4 w-4" />
+                </Button>
+              )}
               <Button
                 variant="ghost"
                 size="sm"
@@ -139,6 +169,23 @@ export function LogModal({ isOpen, onClose, title, logs, isRunning }: LogModalPr
           </div>
         </div>
         
+        {isRunning && progress && progress.total > 0 && (
+          <div className="px-6 py-2 border-t border-green-800 text-xs text-green-500">
+            <div className="flex items-center justify-between mb-1">
+              <span>
+                {progress.remote} · {getPhaseLabel(progress.phase)}: {progress.current}/{progress.total}
+              </span>
+              {progress.bytes > 0 && <span>{formatBytes(progress.bytes)}</span>}
+            </div>
+            <div className="h-1.5 bg-green-900/40 rounded">
+              <div
+                className="h-full bg-green-500 rounded transition-all"
+                style={{ width: `${Math.min(100, (progress.current / progress.total) * 100)}%` }}
+              />
+            </div>
+          </div>
+        )}
+
         <div className="px-6 py-3 border-t border-green-800 text-xs text-green-600">
           <div className="flex items-center justify-between">
             <span>日志条数: {logs.length}</span>
@@ -1,10 +1,13 @@
 import { Button } from './ui/button'
 import { invoke } from '@tauri-apps/api/tauri'
 import { Select, SelectContent, SelectItem, SelectTrigger } from './ui/select'
-import { FolderOpen, GitBranch, Moon, Sun, GitPullRequest, Download } from 'lucide-react'
+import { FolderOpen, GitBranch, Moon, Sun, GitPullRequest, Download, Copy, FolderPlus, GitFork } from 'lucide-react'
 
 interface TopToolbarProps {
   onOpenRepository: () => void
+  onCloneRepository?: () => void
+  onInitRepository?: () => void
+  onManageWorktrees?: () => void
   onBranchSelect: (branchName: string) => void
   onOpenRemoteRepository?: () => void
   onPullChanges?: () => void
@@ -17,6 +20,9 @@ interface TopToolbarProps {
 
 export function TopToolbar({
   onOpenRepository,
+  onCloneRepository,
+  onInitRepository,
+  onManageWorktrees,
   onBranchSelect,
   onOpenRemoteRepository,
   onPullChanges,
@@ -61,14 +67,23 @@ export function TopToolbar({
                 </SelectTrigger>
                 <SelectContent className="w-48">
                   {repoInfo.branches.map((branch: any) => (
-                    <SelectItem key={branch.name} value={branch.name} className="text-sm">
-                      <span className="truncate">{branch.name}</span>
+                    <SelectItem
+                      key={branch.name}
+                      value={branch.name}
+                      className={branch.worktree ? 'text-sm text-muted-foreground' : 'text-sm'}
+                    >
+                      <span className="truncate" title={branch.worktree ? `已在工作树 ${branch.worktree} 中检出` : undefined}>
+                        {branch.name}{branch.worktree && ' (其他工作树)'}
+                      </span>
                     </SelectItem>
                   ))}
                 </SelectContent>
               </Select>
             </div>
             <div className="flex items-center gap-3 text-sm">
+              {repoInfo.is_worktree && (
+                <span className="text-xs rounded bg-purple-600/10 text-purple-600 px-2 py-0.5" title="当前打开的是关联工作树">工作树</span>
+              )}
               <span className="text-muted-foreground">{repoInfo.commits.length} 提交</span>
               {typeof repoInfo.ahead === 'number' && repoInfo.ahead > 0 && (
                 <span className="text-xs rounded bg-blue-600/10 text-blue-600 px-2 py-0.5">{repoInfo.ahead} 待推送</span>
@@ -130,7 +145,42 @@ export function TopToolbar({
           </Button>
         )}
         
+        {repoInfo && onManageWorktrees && (
+          <Button
+            onClick={onManageWorktrees}
+            disabled={loading}
+            variant="outline"
+            className="flex items-center gap-2"
+            title="管理工作树"
+          >
+            <GitFork className="h-4 w-4" />
+            工作树{repoInfo.worktrees?.length > 1 ? ` (${repoInfo.worktrees.length})` : ''}
+          </Button>
+        )}
+
         {/* 移除单独的“打开远程仓库”按钮，改为点击 GitBranch 图标触发 */}
+        {onCloneRepository && (
+          <Button
+            onClick={onCloneRepository}
+            disabled={loading}
+            variant="outline"
+            className="flex items-center gap-2"
+          >
+            <Copy className="h-4 w-4" />
+            克隆仓库
+          </Button>
+        )}
+        {onInitRepository && (
+          <Button
+            onClick={onInitRepository}
+            disabled={loading}
+            variant="outline"
+            className="flex items-center gap-2"
+          >
+            <FolderPlus className="h-4 w-4" />
+            新建仓库
+          </Button>
+        )}
         <Button
           onClick={onOpenRepository}
           disabled={loading}
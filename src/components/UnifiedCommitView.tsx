@@ -224,6 +224,8 @@ export function UnifiedCommitView({
       case 'modified': return '修改'
       case 'deleted': return '删除'
       case 'renamed': return '重命名'
+      case 'copied': return '复制'
+      case 'typechange': return '类型变更'
       default: return status
     }
   }, [])
@@ -3,7 +3,8 @@ import { Button } from './ui/button'
 import { Input } from './ui/input'
 import { Card, CardContent, CardHeader, CardTitle } from './ui/card'
 import { Badge } from './ui/badge'
-import { FileChange } from '../types/git'
+import { FileChange, SubmoduleChange, RepoChangeEvent, ConflictedFile, ConflictKind, PushSummary } from '../types/git'
+import { listen } from '@tauri-apps/api/event'
 import { FileDiffModal } from './FileDiffModal'
 import { Eye, Archive, ArchiveRestore, Trash2, CheckCircle, AlertCircle, GitPullRequest, Download, RefreshCw } from 'lucide-react'
 import { shortenPathMiddle } from '../lib/utils'
@@ -21,13 +22,27 @@ interface WorkspaceStatusData {
   staged_files: FileChange[]
   unstaged_files: FileChange[]
   untracked_files: string[]
+  conflicted_files?: ConflictedFile[]
+  submodules?: SubmoduleChange[]
+  lfs_files?: string[]
+}
+
+// 汇总推送中被拒绝的引用，全部成功时返回 null
+const describeRejectedRefs = (summary: PushSummary) => {
+  const rejected = summary.refs.filter(ref => ref.status !== 'ok')
+  if (rejected.length === 0) return null
+  return `以下引用推送被拒绝：${rejected.map(ref => `${ref.refname}（${ref.message || 'rejected'}）`).join('；')}`
 }
 
 interface StashInfo {
   id: string
+  index: number
+  selector: string
   message: string
   timestamp: string
   branch: string
+  base_commit: string
+  file_count: number
 }
 
 export function WorkspaceStatus({  repoInfo,  onRefresh,
@@ -264,6 +279,21 @@ export function WorkspaceStatus({  repoInfo,  onRefresh,
     }
   }, [repoInfo, autoRefresh, refreshIntervalSec])
 
+  // 仓库监听推送工作区变化时立即刷新
+  useEffect(() => {
+    if (!repoInfo) return
+
+    const unlisten = listen<RepoChangeEvent>('workspace-changed', ({ payload }) => {
+      if (payload.repo_path === repoInfo.path) {
+        fetchWorkspaceStatus()
+      }
+    })
+
+    return () => {
+      unlisten.then(fn => fn())
+    }
+  }, [repoInfo])
+
   // 暂存文件
   const stageFile = async (filePath: string) => {
     if (!repoInfo) return
@@ -292,13 +322,11 @@ export function WorkspaceStatus({  repoInfo,  onRefresh,
       
       const { invoke } = await import('@tauri-apps/api/tauri')
       
-      // 批量暂存所有未暂存的文件
-      for (const file of workspaceStatus.unstaged_files) {
-        await invoke('stage_file', {
-          repoPath: repoInfo.path,
-          filePath: file.path,
-        })
-      }
+      // 一次写入索引，暂存所有已跟踪文件的修改和删除
+      await invoke('stage_all', {
+        repoPath: repoInfo.path,
+        trackedOnly: true,
+      })
       
       // 刷新工作区状态
       await fetchWorkspaceStatus()
@@ -309,6 +337,32 @@ export function WorkspaceStatus({  repoInfo,  onRefresh,
     }
   }
 
+  // 添加所有未跟踪的文件；intentToAdd 时只登记路径，内容留到之后暂存
+  const stageUntrackedFiles = async (intentToAdd: boolean) => {
+    if (!repoInfo || !workspaceStatus?.untracked_files?.length) return
+
+    try {
+      setLoading(true)
+      setError(null)
+
+      const { invoke } = await import('@tauri-apps/api/tauri')
+      await invoke('stage_paths', {
+        repoPath: repoInfo.path,
+        request: {
+          paths: workspaceStatus.untracked_files,
+          intent_to_add: intentToAdd,
+        },
+      })
+
+      // 刷新工作区状态
+      await fetchWorkspaceStatus()
+    } catch (err) {
+      setError(err instanceof Error ? err.message : '添加未跟踪文件失败')
+    } finally {
+      setLoading(false)
+    }
+  }
+
   // 取消暂存文件
   const unstageFile = async (filePath: string) => {
     if (!repoInfo) return
@@ -337,13 +391,10 @@ export function WorkspaceStatus({  repoInfo,  onRefresh,
       
       const { invoke } = await import('@tauri-apps/api/tauri')
       
-      // 批量取消暂存所有文件
-      for (const file of workspaceStatus.staged_files) {
-        await invoke('unstage_file', {
-          repoPath: repoInfo.path,
-          filePath: file.path,
-        })
-      }
+      // 一次写入索引，取消所有暂存
+      await invoke('unstage_all', {
+        repoPath: repoInfo.path,
+      })
       
       // 刷新工作区状态
       await fetchWorkspaceStatus()
@@ -387,9 +438,10 @@ export function WorkspaceStatus({  repoInfo,  onRefresh,
       setError(null)
       
       const { invoke } = await import('@tauri-apps/api/tauri')
-      await invoke('push_changes', {
+      const summary = await invoke<PushSummary>('push_changes', {
         repoPath: repoInfo.path,
       })
+      setError(describeRejectedRefs(summary))
       
       onRefresh() // 刷新提交列表
     } catch (err) {
@@ -446,9 +498,10 @@ export function WorkspaceStatus({  repoInfo,  onRefresh,
       })
       
       if (updatedRepoInfo.ahead > 0) {
-        await invoke('push_changes', {
+        const summary = await invoke<PushSummary>('push_changes', {
           repoPath: repoInfo.path,
         })
+        setError(describeRejectedRefs(summary))
       }
       
       // 刷新所有状态
@@ -490,11 +543,34 @@ export function WorkspaceStatus({  repoInfo,  onRefresh,
         return '删除(已恢复)'
       case 'renamed':
         return '重命名'
+      case 'copied':
+        return '复制'
+      case 'typechange':
+        return '类型变更'
       default:
         return status
     }
   }
 
+  const getConflictText = (kind: ConflictKind) => {
+    switch (kind) {
+      case 'both-modified':
+        return '双方修改'
+      case 'both-added':
+        return '双方新增'
+      case 'both-deleted':
+        return '双方删除'
+      case 'added-by-us':
+        return '我方新增'
+      case 'added-by-them':
+        return '对方新增'
+      case 'deleted-by-us':
+        return '我方删除'
+      case 'deleted-by-them':
+        return '对方删除'
+    }
+  }
+
   // 查看文件差异
   const viewFileDiff = (filePath: string, type: 'staged' | 'unstaged' | 'untracked') => {
     setSelectedFile({ path: filePath, type })
@@ -536,10 +612,14 @@ export function WorkspaceStatus({  repoInfo,  onRefresh,
     )
   }
 
+  const isLfsFile = (path: string) => workspaceStatus?.lfs_files?.includes(path) ?? false
+
   const hasChanges = workspaceStatus && (
     workspaceStatus.staged_files.length > 0 ||
     workspaceStatus.unstaged_files.length > 0 ||
-    workspaceStatus.untracked_files.length > 0
+    workspaceStatus.untracked_files.length > 0 ||
+    (workspaceStatus.conflicted_files?.length ?? 0) > 0 ||
+    (workspaceStatus.submodules?.length ?? 0) > 0
   )
 
   return (
@@ -806,6 +886,41 @@ export function WorkspaceStatus({  repoInfo,  onRefresh,
         </DialogContent>
       </Dialog>
 
+      {/* 冲突文件：暂存即标记为已解决 */}
+      {workspaceStatus?.conflicted_files && workspaceStatus.conflicted_files.length > 0 && (
+        <Card className="border-l-4 border-l-red-500 dark:border-l-red-400">
+          <CardHeader className="bg-red-50/50 dark:bg-red-900/10">
+            <CardTitle className="text-lg flex items-center gap-2 text-red-700 dark:text-red-300">
+              <AlertCircle className="h-5 w-5" />
+              冲突的文件
+            </CardTitle>
+          </CardHeader>
+          <CardContent>
+            <div className="space-y-2">
+              {workspaceStatus.conflicted_files.map((file) => (
+                <div key={file.path} className="flex items-start gap-2 p-2 rounded bg-red-50/30 dark:bg-red-900/5">
+                  <Badge variant="destructive" className="flex-shrink-0">
+                    {getConflictText(file.kind)}
+                  </Badge>
+                  <div className="flex-1 min-w-0">
+                    <div className="text-sm font-mono truncate" title={file.path}>{shortenPathMiddle(file.path, 56)}</div>
+                  </div>
+                  <div className="flex items-center gap-2 flex-shrink-0">
+                    <Button
+                      size="sm"
+                      onClick={() => stageFile(file.path)}
+                      disabled={loading}
+                    >
+                      标记已解决
+                    </Button>
+                  </div>
+                </div>
+              ))}
+            </div>
+          </CardContent>
+        </Card>
+      )}
+
       {/* 暂存的文件 */}
       {workspaceStatus?.staged_files && workspaceStatus.staged_files.length > 0 && (
         <Card className="border-l-4 border-l-green-500 dark:border-l-green-400">
@@ -833,8 +948,14 @@ export function WorkspaceStatus({  repoInfo,  onRefresh,
                   <Badge variant={getStatusBadgeVariant(file.status)} className="flex-shrink-0">
                     {getStatusText(file.status)}
                   </Badge>
+                  {isLfsFile(file.path) && <Badge variant="secondary" className="flex-shrink-0" title="Git LFS 管理的文件">LFS</Badge>}
                   <div className="flex-1 min-w-0">
                     <div className="text-sm font-mono truncate" title={file.path}>{shortenPathMiddle(file.path, 56)}</div>
+                    {file.old_path && (
+                      <div className="text-xs text-muted-foreground font-mono truncate" title={file.old_path}>
+                        原路径 {shortenPathMiddle(file.old_path, 52)}
+                      </div>
+                    )}
                   </div>
                   <div className="flex items-center gap-2 flex-shrink-0">
                     <Button
@@ -888,8 +1009,14 @@ export function WorkspaceStatus({  repoInfo,  onRefresh,
                   <Badge variant={getStatusBadgeVariant(file.status)} className="flex-shrink-0">
                     {getStatusText(file.status)}
                   </Badge>
+                  {isLfsFile(file.path) && <Badge variant="secondary" className="flex-shrink-0" title="Git LFS 管理的文件">LFS</Badge>}
                   <div className="flex-1 min-w-0">
                     <div className="text-sm font-mono truncate" title={file.path}>{shortenPathMiddle(file.path, 56)}</div>
+                    {file.old_path && (
+                      <div className="text-xs text-muted-foreground font-mono truncate" title={file.old_path}>
+                        原路径 {shortenPathMiddle(file.old_path, 52)}
+                      </div>
+                    )}
                   </div>
                   <div className="flex items-center gap-2 flex-shrink-0">
                     <Button
@@ -920,7 +1047,28 @@ export function WorkspaceStatus({  repoInfo,  onRefresh,
       {workspaceStatus?.untracked_files && workspaceStatus.untracked_files.length > 0 && (
         <Card>
           <CardHeader>
-            <CardTitle className="text-lg">未跟踪的文件</CardTitle>
+            <div className="flex items-center justify-between">
+              <CardTitle className="text-lg">未跟踪的文件</CardTitle>
+              <div className="flex items-center gap-2">
+                <Button
+                  size="sm"
+                  variant="outline"
+                  onClick={() => stageUntrackedFiles(true)}
+                  disabled={loading}
+                  title="只登记文件路径（git add -N），内容不暂存"
+                >
+                  意图添加
+                </Button>
+                <Button
+                  size="sm"
+                  variant="outline"
+                  onClick={() => stageUntrackedFiles(false)}
+                  disabled={loading}
+                >
+                  全部添加
+                </Button>
+              </div>
+            </div>
           </CardHeader>
           <CardContent>
             <div className="space-y-2">
@@ -930,6 +1078,7 @@ export function WorkspaceStatus({  repoInfo,  onRefresh,
                 <div key={index} className="flex items-start gap-2 p-2 border rounded">
                   
                   <Badge variant="outline" className="flex-shrink-0">未跟踪</Badge>
+                  {isLfsFile(file) && <Badge variant="secondary" className="flex-shrink-0" title="Git LFS 管理的文件">LFS</Badge>}
                   <div className="flex-1 min-w-0">
                     <div className="text-sm font-mono truncate" title={file}>{shortenPathMiddle(file, 56)}</div>
                   </div>
@@ -958,6 +1107,42 @@ export function WorkspaceStatus({  repoInfo,  onRefresh,
         </Card>
       )}
 
+      {/* 子模块变更 */}
+      {workspaceStatus?.submodules && workspaceStatus.submodules.length > 0 && (
+        <Card>
+          <CardHeader>
+            <CardTitle className="text-lg">子模块</CardTitle>
+          </CardHeader>
+          <CardContent>
+            <div className="space-y-2">
+              {workspaceStatus.submodules.map((sub) => (
+                <div key={sub.path} className="flex items-start gap-2 p-2 border rounded">
+                  <div className="flex flex-wrap gap-1 flex-shrink-0">
+                    {sub.staged && (
+                      <Badge variant="default">
+                        {sub.index_change === 'added' ? '已暂存新增' : sub.index_change === 'deleted' ? '已暂存删除' : '已暂存'}
+                      </Badge>
+                    )}
+                    {sub.workdir_change === 'deleted' && <Badge variant="destructive">目录缺失</Badge>}
+                    {sub.new_commits && <Badge variant="secondary">新提交</Badge>}
+                    {sub.modified_content && <Badge variant="outline">内容已修改</Badge>}
+                    {sub.untracked_content && <Badge variant="outline">含未跟踪文件</Badge>}
+                  </div>
+                  <div className="flex-1 min-w-0">
+                    <div className="text-sm font-mono truncate" title={sub.path}>{shortenPathMiddle(sub.path, 56)}</div>
+                    {sub.new_commits && (
+                      <div className="text-xs text-muted-foreground font-mono">
+                        {sub.index_id?.slice(0, 7) ?? '-'} → {sub.workdir_id?.slice(0, 7) ?? '-'}
+                      </div>
+                    )}
+                  </div>
+                </div>
+              ))}
+            </div>
+          </CardContent>
+        </Card>
+      )}
+
       {/* 无更改状态 */}
       {!hasChanges && (
         <Card>
@@ -0,0 +1,205 @@
+import { useState, useEffect } from 'react'
+import { Dialog, DialogContent, DialogHeader, DialogTitle } from './ui/dialog'
+import { Button } from './ui/button'
+import { Input } from './ui/input'
+import { Switch } from './ui/switch'
+import { Label } from './ui/label'
+import { Badge } from './ui/badge'
+import { WorktreeInfo, WorktreeAddRequest } from '../types/git'
+import { open } from '@tauri-apps/api/dialog'
+import { FolderOpen, Lock, Unlock, RefreshCw, Trash2 } from 'lucide-react'
+import { shortenPathMiddle } from '../lib/utils'
+
+interface WorktreeModalProps {
+  isOpen: boolean
+  onClose: () => void
+  getWorktrees: () => Promise<WorktreeInfo[]>
+  onAdd: (path: string, options: WorktreeAddRequest) => Promise<unknown>
+  onLock: (name: string, reason?: string) => Promise<void>
+  onUnlock: (name: string) => Promise<void>
+  onPrune: () => Promise<string[]>
+  onOpen: (name?: string) => Promise<void>
+}
+
+export function WorktreeModal({ isOpen, onClose, getWorktrees, onAdd, onLock, onUnlock, onPrune, onOpen }: WorktreeModalProps) {
+  const [worktrees, setWorktrees] = useState<WorktreeInfo[]>([])
+  const [loading, setLoading] = useState(false)
+  const [path, setPath] = useState('')
+  const [branch, setBranch] = useState('')
+  const [newBranch, setNewBranch] = useState(false)
+  const [lock, setLock] = useState(false)
+  const [submitting, setSubmitting] = useState(false)
+  const [submitError, setSubmitError] = useState<string | null>(null)
+  const [message, setMessage] = useState<string | null>(null)
+
+  const loadWorktrees = async () => {
+    setLoading(true)
+    try {
+      setWorktrees(await getWorktrees())
+    } catch (error) {
+      setSubmitError(error instanceof Error ? error.message : String(error))
+    } finally {
+      setLoading(false)
+    }
+  }
+
+  useEffect(() => {
+    if (isOpen) {
+      setSubmitError(null)
+      setMessage(null)
+      loadWorktrees()
+    }
+  }, [isOpen])
+
+  const choosePath = async () => {
+    const selected = await open({ directory: true, title: '选择工作树目录' })
+    if (selected && typeof selected === 'string') {
+      setPath(selected)
+    }
+  }
+
+  // 执行操作并刷新列表，错误在弹窗内显示
+  const run = async (action: () => Promise<unknown>) => {
+    setSubmitting(true)
+    setSubmitError(null)
+    setMessage(null)
+    try {
+      await action()
+      await loadWorktrees()
+    } catch (error) {
+      setSubmitError(error instanceof Error ? error.message : String(error))
+    } finally {
+      setSubmitting(false)
+    }
+  }
+
+  const handleAdd = () => run(async () => {
+    await onAdd(path.trim(), {
+      branch: branch.trim() || undefined,
+      new_branch: newBranch && !!branch.trim(),
+      lock,
+    })
+    setPath('')
+    setBranch('')
+  })
+
+  const handlePrune = () => run(async () => {
+    const pruned = await onPrune()
+    setMessage(pruned.length > 0 ? `已清理: ${pruned.join(', ')}` : '没有需要清理的工作树')
+  })
+
+  const handleOpen = (worktree: WorktreeInfo) => run(async () => {
+    await onOpen(worktree.name)
+    onClose()
+  })
+
+  return (
+    <Dialog open={isOpen} onOpenChange={onClose}>
+      <DialogContent className="max-w-2xl max-h-[90vh] overflow-y-auto">
+        <DialogHeader>
+          <DialogTitle>工作树</DialogTitle>
+        </DialogHeader>
+
+        <div className="space-y-4">
+          <div className="flex items-center justify-between">
+            <div className="text-sm text-muted-foreground">同一仓库的多个工作目录，每个工作树检出不同的分支</div>
+            <div className="flex gap-2">
+              <Button variant="ghost" size="sm" onClick={loadWorktrees} disabled={loading} className="h-8">
+                <RefreshCw className={`h-4 w-4 mr-2 ${loading ? 'animate-spin' : ''}`} />
+                刷新
+              </Button>
+              <Button variant="outline" size="sm" onClick={handlePrune} disabled={submitting} className="h-8">
+                <Trash2 className="h-4 w-4 mr-2" />
+                清理
+              </Button>
+            </div>
+          </div>
+
+          <div className="space-y-2">
+            {worktrees.map(worktree => (
+              <div key={worktree.path} className="flex items-center gap-2 p-2 border rounded">
+                <div className="flex-1 min-w-0">
+                  <div className="flex items-center gap-2">
+                    <span className="text-sm font-medium">{worktree.is_main ? '主工作树' : worktree.name}</span>
+                    {worktree.is_current && <Badge variant="default">当前</Badge>}
+                    {worktree.locked && <Badge variant="secondary" title={worktree.lock_reason}>已锁定</Badge>}
+                    {worktree.prunable && <Badge variant="destructive">目录已丢失</Badge>}
+                  </div>
+                  <div className="text-xs font-mono text-muted-foreground truncate" title={worktree.path}>
+                    {shortenPathMiddle(worktree.path, 64)}
+                  </div>
+                  <div className="text-xs text-muted-foreground">
+                    {worktree.branch ?? `分离头指针 ${worktree.head?.slice(0, 7) ?? ''}`}
+                  </div>
+                </div>
+                <div className="flex items-center gap-2 flex-shrink-0">
+                  {!worktree.is_main && worktree.name && (
+                    worktree.locked ? (
+                      <Button size="sm" variant="outline" onClick={() => run(() => onUnlock(worktree.name!))} disabled={submitting} title="解锁">
+                        <Unlock className="h-3 w-3" />
+                      </Button>
+                    ) : (
+                      <Button size="sm" variant="outline" onClick={() => run(() => onLock(worktree.name!))} disabled={submitting} title="锁定，防止被清理">
+                        <Lock className="h-3 w-3" />
+                      </Button>
+                    )
+                  )}
+                  <Button
+                    size="sm"
+                    onClick={() => handleOpen(worktree)}
+                    disabled={submitting || worktree.is_current || worktree.prunable}
+                  >
+                    打开
+                  </Button>
+                </div>
+              </div>
+            ))}
+          </div>
+
+          <div className="space-y-3 border-t pt-4">
+            <div className="text-sm font-medium">添加工作树</div>
+            <div className="flex gap-2">
+              <Input
+                value={path}
+                onChange={(e) => setPath(e.target.value)}
+                placeholder="工作树目录（不存在会自动创建）"
+              />
+              <Button type="button" variant="outline" onClick={choosePath} title="选择目录">
+                <FolderOpen className="h-4 w-4" />
+              </Button>
+            </div>
+            <Input
+              value={branch}
+              onChange={(e) => setBranch(e.target.value)}
+              placeholder="分支（留空则按目录名新建分支）"
+            />
+            <div className="flex items-center gap-6">
+              <div className="flex items-center space-x-2">
+                <Switch id="worktree-new-branch" checked={newBranch} onCheckedChange={(checked: boolean) => setNewBranch(checked)} />
+                <Label htmlFor="worktree-new-branch" className="text-sm">新建分支</Label>
+              </div>
+              <div className="flex items-center space-x-2">
+                <Switch id="worktree-lock" checked={lock} onCheckedChange={(checked: boolean) => setLock(checked)} />
+                <Label htmlFor="worktree-lock" className="text-sm">锁定</Label>
+              </div>
+            </div>
+          </div>
+
+          {submitError && (
+            <div className="text-sm text-destructive break-all">{submitError}</div>
+          )}
+          {message && (
+            <div className="text-sm text-muted-foreground">{message}</div>
+          )}
+
+          <div className="flex justify-end space-x-2">
+            <Button variant="outline" onClick={onClose}>关闭</Button>
+            <Button onClick={handleAdd} disabled={!path.trim() || submitting}>
+              {submitting ? '处理中...' : '添加'}
+            </Button>
+          </div>
+        </div>
+      </DialogContent>
+    </Dialog>
+  )
+}
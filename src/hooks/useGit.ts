@@ -1,9 +1,15 @@
 import { useState, useCallback, useEffect } from 'react'
 import { invoke } from '@tauri-apps/api/tauri'
+import { listen } from '@tauri-apps/api/event'
 import { open } from '@tauri-apps/api/dialog'
-import { RepoInfo, CommitInfo, FileChange, RecentRepo, WorkspaceStatus } from '../types/git'
+import { RepoInfo, CommitInfo, FileChange, RecentRepo, WorkspaceStatus, FetchRequest, FetchSummary, PushRequest, PushSummary, CloneRequest, CloneResult, InitRequest, InitResult, SubmoduleInfo, WorktreeInfo, WorktreeAddRequest, RepoChangeEvent, WorkspaceStatusOptions, StageRequest } from '../types/git'
 import { formatTauriInvokeError } from '../utils/tauriError'
 
+// 为可取消的长时间操作生成 id，命令返回前可用它调用 cancel_operation
+export function createOperationId(kind: string) {
+  return `${kind}-${Date.now()}-${Math.random().toString(36).slice(2, 8)}`
+}
+
 export function useGit() {
   const [repoInfo, setRepoInfo] = useState<RepoInfo | null>(null)
   const [loading, setLoading] = useState(false)
@@ -54,6 +60,48 @@ export function useGit() {
     }
   }, [])
 
+  // 克隆远程仓库；非 bare 克隆完成后直接打开
+  const cloneRepository = useCallback(async (url: string, dest: string, options?: CloneRequest, operationId?: string) => {
+    try {
+      const result = await invoke<CloneResult>('clone_repository', {
+        url,
+        dest,
+        options,
+        operationId,
+      })
+      if (!result.bare) {
+        const repoInfo: RepoInfo = await invoke('open_repository', {
+          path: result.path,
+        })
+        setRepoInfo(repoInfo)
+        loadRecentRepos()
+      }
+      return result
+    } catch (err) {
+      throw new Error(formatTauriInvokeError(err, '克隆失败'))
+    }
+  }, [])
+
+  // 初始化新仓库；非 bare 仓库创建后直接打开
+  const initRepository = useCallback(async (path: string, options?: InitRequest) => {
+    try {
+      const result = await invoke<InitResult>('init_repository', {
+        path,
+        options,
+      })
+      if (!result.bare) {
+        const repoInfo: RepoInfo = await invoke('open_repository', {
+          path: result.path,
+        })
+        setRepoInfo(repoInfo)
+        loadRecentRepos()
+      }
+      return result
+    } catch (err) {
+      throw new Error(formatTauriInvokeError(err, '初始化仓库失败'))
+    }
+  }, [])
+
   const loadRecentRepos = useCallback(async () => {
     try {
       const repos: RecentRepo[] = await invoke('get_recent_repos')
@@ -75,6 +123,33 @@ export function useGit() {
     }
   }, [recentRepos, repoInfo, openRepositoryByPath, autoOpenEnabled])
 
+  // 监听当前仓库的文件变化：引用或 HEAD 变化时静默刷新仓库信息，工作区变化由 WorkspaceStatus 处理
+  useEffect(() => {
+    const repoPath = repoInfo?.path
+    if (!repoPath) return
+
+    invoke('watch_repository', { repoPath })
+      .catch(error => console.error('Failed to watch repository:', error))
+
+    const unlisteners = ['refs-changed', 'head-changed'].map(event =>
+      listen<RepoChangeEvent>(event, async ({ payload }) => {
+        if (payload.repo_path !== repoPath) return
+        try {
+          const info: RepoInfo = await invoke('open_repository', { path: repoPath })
+          setRepoInfo(info)
+        } catch (error) {
+          console.error('Failed to refresh repository:', error)
+        }
+      })
+    )
+
+    return () => {
+      unlisteners.forEach(unlisten => unlisten.then(fn => fn()))
+      invoke('unwatch_repository', { repoPath })
+        .catch(error => console.error('Failed to unwatch repository:', error))
+    }
+  }, [repoInfo?.path])
+
   const checkoutBranch = useCallback(async (branchName: string) => {
     if (!repoInfo) return
     
@@ -142,13 +217,14 @@ export function useGit() {
     }
   }, [repoInfo])
 
-  const getCommitsPaginated = useCallback(async (limit?: number, offset?: number) => {
+  const getCommitsPaginated = useCallback(async (limit?: number, offset?: number, operationId?: string) => {
     try {
       if (!repoInfo) throw new Error('No repository selected')
       return await invoke<CommitInfo[]>('get_commits_paginated', { 
         repoPath: repoInfo.path, 
         limit, 
-        offset 
+        offset,
+        operationId,
       })
     } catch (error) {
       console.error('Failed to get paginated commits:', error)
@@ -156,13 +232,14 @@ export function useGit() {
     }
   }, [repoInfo])
 
-  const searchCommits = useCallback(async (query: string, limit?: number) => {
+  const searchCommits = useCallback(async (query: string, limit?: number, operationId?: string) => {
     try {
       if (!repoInfo) throw new Error('No repository selected')
       return await invoke<CommitInfo[]>('search_commits', {
         repoPath: repoInfo.path,
         query: query.trim(),
         limit: limit ?? 500,
+        operationId,
       })
     } catch (error) {
       console.error('Failed to search commits:', error)
@@ -170,12 +247,13 @@ export function useGit() {
     }
   }, [repoInfo])
 
-  const getWorkspaceStatus = useCallback(async (): Promise<WorkspaceStatus> => {
+  const getWorkspaceStatus = useCallback(async (options?: WorkspaceStatusOptions): Promise<WorkspaceStatus> => {
     if (!repoInfo) throw new Error('No repository open')
     
     try {
       const status: WorkspaceStatus = await invoke('get_workspace_status', {
         repoPath: repoInfo.path,
+        options,
       })
       return status
     } catch (err) {
@@ -209,6 +287,47 @@ export function useGit() {
     }
   }, [repoInfo])
 
+  // 批量暂存 / 取消暂存，返回实际变更的路径
+  const stagePaths = useCallback(async (request: StageRequest): Promise<string[]> => {
+    if (!repoInfo) throw new Error('No repository open')
+
+    try {
+      return await invoke<string[]>('stage_paths', { repoPath: repoInfo.path, request })
+    } catch (err) {
+      throw new Error(formatTauriInvokeError(err, '批量暂存失败'))
+    }
+  }, [repoInfo])
+
+  const unstagePaths = useCallback(async (paths: string[]): Promise<string[]> => {
+    if (!repoInfo) throw new Error('No repository open')
+
+    try {
+      return await invoke<string[]>('unstage_paths', { repoPath: repoInfo.path, paths })
+    } catch (err) {
+      throw new Error(formatTauriInvokeError(err, '批量取消暂存失败'))
+    }
+  }, [repoInfo])
+
+  const stageAll = useCallback(async (trackedOnly = false): Promise<string[]> => {
+    if (!repoInfo) throw new Error('No repository open')
+
+    try {
+      return await invoke<string[]>('stage_all', { repoPath: repoInfo.path, trackedOnly })
+    } catch (err) {
+      throw new Error(formatTauriInvokeError(err, '暂存全部失败'))
+    }
+  }, [repoInfo])
+
+  const unstageAll = useCallback(async (): Promise<string[]> => {
+    if (!repoInfo) throw new Error('No repository open')
+
+    try {
+      return await invoke<string[]>('unstage_all', { repoPath: repoInfo.path })
+    } catch (err) {
+      throw new Error(formatTauriInvokeError(err, '取消所有暂存失败'))
+    }
+  }, [repoInfo])
+
   const commitChanges = useCallback(async (message: string) => {
     if (!repoInfo) throw new Error('No repository open')
     
@@ -222,12 +341,15 @@ export function useGit() {
     }
   }, [repoInfo])
 
-  const pushChanges = useCallback(async () => {
+  const pushChanges = useCallback(async (remote?: string, options?: PushRequest, operationId?: string) => {
     if (!repoInfo) throw new Error('No repository open')
     
     try {
-      await invoke('push_changes', {
+      return await invoke<PushSummary>('push_changes', {
         repoPath: repoInfo.path,
+        remote,
+        options,
+        operationId,
       })
     } catch (err) {
       throw new Error(formatTauriInvokeError(err, '推送失败'))
@@ -274,12 +396,15 @@ export function useGit() {
     }
   }, [repoInfo])
 
-  const fetchChangesWithLogs = useCallback(async () => {
+  const fetchChangesWithLogs = useCallback(async (remote?: string, options?: FetchRequest, operationId?: string) => {
     if (!repoInfo) throw new Error('No repository open')
     
     try {
-      const logs: Array<[string, string, string]> = await invoke('fetch_changes_with_logs', {
+      const summary: FetchSummary = await invoke('fetch_changes_with_logs', {
         repoPath: repoInfo.path,
+        remote,
+        options,
+        operationId,
       })
       
       // 获取成功后，重新获取仓库信息以更新状态
@@ -288,18 +413,19 @@ export function useGit() {
       })
       setRepoInfo(updatedRepoInfo)
       
-      return logs
+      return summary
     } catch (err) {
       throw new Error(formatTauriInvokeError(err, '获取失败'))
     }
   }, [repoInfo])
 
-  const pushChangesWithLogs = useCallback(async () => {
+  const pushChangesWithLogs = useCallback(async (operationId?: string) => {
     if (!repoInfo) throw new Error('No repository open')
     
     try {
       const logs: Array<[string, string, string]> = await invoke('push_changes_with_logs', {
         repoPath: repoInfo.path,
+        operationId,
       })
       
       // 推送成功后，重新获取仓库信息以更新状态
@@ -314,12 +440,13 @@ export function useGit() {
     }
   }, [repoInfo])
 
-  const pushChangesWithRealtimeLogs = useCallback(async () => {
+  const pushChangesWithRealtimeLogs = useCallback(async (operationId?: string) => {
     if (!repoInfo) throw new Error('No repository open')
     
     try {
       await invoke('push_changes_with_realtime_logs', {
         repoPath: repoInfo.path,
+        operationId,
       })
       
       // 推送成功后，重新获取仓库信息以更新状态
@@ -334,12 +461,13 @@ export function useGit() {
     }
   }, [repoInfo])
 
-  const pullChangesWithLogs = useCallback(async () => {
+  const pullChangesWithLogs = useCallback(async (operationId?: string) => {
     if (!repoInfo) throw new Error('No repository open')
     
     try {
       const logs: Array<[string, string, string]> = await invoke('pull_changes_with_logs', {
         repoPath: repoInfo.path,
+        operationId,
       })
       
       // 拉取成功后，重新获取仓库信息以更新状态
@@ -354,6 +482,136 @@ export function useGit() {
     }
   }, [repoInfo])
 
+  const getSubmodules = useCallback(async (repoPath: string) => {
+    try {
+      return await invoke<SubmoduleInfo[]>('get_submodules', { repoPath })
+    } catch (err) {
+      throw new Error(formatTauriInvokeError(err, '获取子模块失败'))
+    }
+  }, [])
+
+  const initSubmodules = useCallback(async (repoPath: string, paths?: string[]) => {
+    try {
+      return await invoke<string[]>('init_submodules', { repoPath, paths })
+    } catch (err) {
+      throw new Error(formatTauriInvokeError(err, '初始化子模块失败'))
+    }
+  }, [])
+
+  // 更新子模块到父仓库记录的提交
+  const updateSubmodules = useCallback(async (
+    repoPath: string,
+    options?: { paths?: string[]; init?: boolean; recursive?: boolean },
+    operationId?: string,
+  ) => {
+    try {
+      return await invoke<string[]>('update_submodules_cmd', {
+        repoPath,
+        paths: options?.paths,
+        init: options?.init,
+        recursive: options?.recursive,
+        operationId,
+      })
+    } catch (err) {
+      throw new Error(formatTauriInvokeError(err, '更新子模块失败'))
+    }
+  }, [])
+
+  const syncSubmodules = useCallback(async (repoPath: string, paths?: string[], recursive?: boolean) => {
+    try {
+      return await invoke<string[]>('sync_submodules', { repoPath, paths, recursive })
+    } catch (err) {
+      throw new Error(formatTauriInvokeError(err, '同步子模块地址失败'))
+    }
+  }, [])
+
+  const addSubmodule = useCallback(async (repoPath: string, url: string, path: string, branch?: string, operationId?: string) => {
+    try {
+      return await invoke<SubmoduleInfo>('add_submodule', { repoPath, url, path, branch, operationId })
+    } catch (err) {
+      throw new Error(formatTauriInvokeError(err, '添加子模块失败'))
+    }
+  }, [])
+
+  const removeSubmodule = useCallback(async (repoPath: string, path: string, force?: boolean) => {
+    try {
+      return await invoke<string>('remove_submodule', { repoPath, path, force })
+    } catch (err) {
+      throw new Error(formatTauriInvokeError(err, '删除子模块失败'))
+    }
+  }, [])
+
+  const getWorktrees = useCallback(async () => {
+    if (!repoInfo) return []
+    try {
+      return await invoke<WorktreeInfo[]>('get_worktrees', { repoPath: repoInfo.path })
+    } catch (err) {
+      throw new Error(formatTauriInvokeError(err, '获取工作树失败'))
+    }
+  }, [repoInfo])
+
+  const addWorktree = useCallback(async (path: string, options?: WorktreeAddRequest) => {
+    if (!repoInfo) throw new Error('No repository open')
+    try {
+      const worktree = await invoke<WorktreeInfo>('add_worktree', { repoPath: repoInfo.path, path, options })
+      const updatedRepoInfo: RepoInfo = await invoke('open_repository', { path: repoInfo.path })
+      setRepoInfo(updatedRepoInfo)
+      return worktree
+    } catch (err) {
+      throw new Error(formatTauriInvokeError(err, '添加工作树失败'))
+    }
+  }, [repoInfo])
+
+  const lockWorktree = useCallback(async (name: string, reason?: string) => {
+    if (!repoInfo) return
+    try {
+      await invoke('lock_worktree', { repoPath: repoInfo.path, name, reason })
+    } catch (err) {
+      throw new Error(formatTauriInvokeError(err, '锁定工作树失败'))
+    }
+  }, [repoInfo])
+
+  const unlockWorktree = useCallback(async (name: string) => {
+    if (!repoInfo) return
+    try {
+      await invoke('unlock_worktree', { repoPath: repoInfo.path, name })
+    } catch (err) {
+      throw new Error(formatTauriInvokeError(err, '解锁工作树失败'))
+    }
+  }, [repoInfo])
+
+  const pruneWorktrees = useCallback(async () => {
+    if (!repoInfo) return []
+    try {
+      const pruned = await invoke<string[]>('prune_worktrees', { repoPath: repoInfo.path })
+      const updatedRepoInfo: RepoInfo = await invoke('open_repository', { path: repoInfo.path })
+      setRepoInfo(updatedRepoInfo)
+      return pruned
+    } catch (err) {
+      throw new Error(formatTauriInvokeError(err, '清理工作树失败'))
+    }
+  }, [repoInfo])
+
+  // 切换到指定工作树（name 为空时为主工作树）
+  const openWorktree = useCallback(async (name?: string) => {
+    if (!repoInfo) return
+    try {
+      const updatedRepoInfo: RepoInfo = await invoke('open_worktree', { repoPath: repoInfo.path, name })
+      setRepoInfo(updatedRepoInfo)
+      loadRecentRepos()
+    } catch (err) {
+      throw new Error(formatTauriInvokeError(err, '打开工作树失败'))
+    }
+  }, [repoInfo])
+
+  const cancelOperation = useCallback(async (operationId: string) => {
+    try {
+      return await invoke<boolean>('cancel_operation', { operationId })
+    } catch (err) {
+      throw new Error(formatTauriInvokeError(err, '取消失败'))
+    }
+  }, [])
+
   return {
     repoInfo,
     loading,
@@ -363,6 +621,20 @@ export function useGit() {
     setAutoOpenEnabled,
     openRepository,
     openRepositoryByPath,
+    cloneRepository,
+    initRepository,
+    getSubmodules,
+    initSubmodules,
+    updateSubmodules,
+    syncSubmodules,
+    addSubmodule,
+    removeSubmodule,
+    getWorktrees,
+    addWorktree,
+    lockWorktree,
+    unlockWorktree,
+    pruneWorktrees,
+    openWorktree,
     checkoutBranch,
     getFileDiff,
     getCommitFiles,
@@ -372,6 +644,10 @@ export function useGit() {
     getWorkspaceStatus,
     stageFile,
     unstageFile,
+    stagePaths,
+    unstagePaths,
+    stageAll,
+    unstageAll,
     commitChanges,
     pushChanges,
     pullChanges,
@@ -380,5 +656,6 @@ export function useGit() {
     pushChangesWithLogs,
     pushChangesWithRealtimeLogs,
     pullChangesWithLogs,
+    cancelOperation,
   }
 }
@@ -11,15 +11,26 @@ export interface BranchInfo {
   name: string
   is_current: boolean
   is_remote: boolean
+  worktree?: string // 已在其他工作树中检出时为该工作树路径
 }
 
+export type FileStatus = 'added' | 'modified' | 'deleted' | 'renamed' | 'copied' | 'typechange' | 'unknown'
+
 export interface FileChange {
   path: string
-  status: string // "added", "modified", "deleted", "renamed"
+  old_path?: string // 重命名 / 复制前的路径
+  status: FileStatus
   additions: number
   deletions: number
 }
 
+export type ConflictKind = 'both-modified' | 'both-added' | 'both-deleted' | 'added-by-us' | 'added-by-them' | 'deleted-by-us' | 'deleted-by-them'
+
+export interface ConflictedFile {
+  path: string
+  kind: ConflictKind
+}
+
 export interface RecentRepo {
   path: string
   name: string
@@ -30,6 +41,39 @@ export interface WorkspaceStatus {
   staged_files: FileChange[]
   unstaged_files: FileChange[]
   untracked_files: string[]
+  conflicted_files: ConflictedFile[]
+  ignored_files: string[] // 仅在 include_ignored 时返回
+  submodules: SubmoduleChange[]
+  lfs_files: string[] // 由 Git LFS 管理的变更文件
+}
+
+// get_workspace_status 的可选参数
+export interface WorkspaceStatusOptions {
+  pathspecs?: string[] // 只统计匹配的路径
+  collapse_untracked_dirs?: boolean // 未跟踪目录只报告目录本身（以 / 结尾）
+  include_ignored?: boolean
+  detect_workdir_renames?: boolean // 工作区重命名检测，大仓库中较慢
+  scan_submodule_content?: boolean // 检查子模块内部的修改与未跟踪文件
+}
+
+// stage_paths 的参数
+export interface StageRequest {
+  paths?: string[] // 文件、目录或 glob，为空时作用于整个工作区
+  tracked_only?: boolean // 只暂存已跟踪文件（git add -u）
+  intent_to_add?: boolean // 未跟踪文件只登记意图添加（git add -N）
+}
+
+// 工作区中的子模块变更（与普通文件分开）
+export interface SubmoduleChange {
+  path: string
+  staged: boolean
+  new_commits: boolean
+  modified_content: boolean
+  untracked_content: boolean
+  index_change?: FileStatus // 父仓库索引相对 HEAD 的变化
+  workdir_change?: FileStatus // 工作区相对索引的变化
+  index_id?: string
+  workdir_id?: string
 }
 
 export interface RepoInfo {
@@ -39,7 +83,19 @@ export interface RepoInfo {
   commits: CommitInfo[]
   ahead: number // 本地比远端超前（待推送）
   behind: number // 本地比远端落后（待拉取）
-  remote_url?: string // 远程仓库URL
+  remote_url?: string // 远程仓库URL（当前分支使用的远程）
+  remote_name?: string
+  remotes: RemoteInfo[]
+  is_worktree: boolean // 当前路径是否为关联工作树
+  worktrees: WorktreeInfo[]
+}
+
+export interface RemoteInfo {
+  name: string
+  url?: string
+  push_url?: string // 单独配置的推送地址
+  fetch_refspecs: string[]
+  push_refspecs: string[]
 }
 
 export interface ProxyConfig {
@@ -50,3 +106,165 @@ export interface ProxyConfig {
   password?: string
   protocol: string // "http", "socks5" (不支持 "https")
 }
+
+export interface FetchRequest {
+  all_remotes?: boolean
+  prune?: boolean // 删除远端已不存在的远程跟踪分支
+  tags?: 'auto' | 'all' | 'none'
+  depth?: number // 浅获取深度
+  unshallow?: boolean
+}
+
+export interface RefUpdate {
+  remote: string
+  refname: string
+  old_id?: string
+  new_id?: string
+  kind: 'new' | 'updated' | 'forced' | 'pruned'
+}
+
+export interface FetchSummary {
+  remotes: string[]
+  failed_remotes: string[]
+  updated: RefUpdate[]
+  logs: Array<[string, string, string]>
+}
+
+export interface PushRequest {
+  branches?: string[] // 为空时推送当前分支
+  remote_branch?: string // 推送到不同名的远程分支
+  refspecs?: string[]
+  tags?: boolean
+  delete?: string[] // 要删除的远程分支
+  force_with_lease?: boolean
+  expected?: string // force-with-lease 的预期提交
+  set_upstream?: boolean
+}
+
+export interface PushRefResult {
+  refname: string
+  status: 'ok' | 'rejected'
+  message?: string
+}
+
+export interface PushSummary {
+  remote: string
+  refs: PushRefResult[]
+  message: string
+}
+
+export interface CredentialRequest {
+  id: string
+  kind: 'userpass' | 'passphrase' // 用户名 + 密码 / 令牌，或 SSH 私钥口令
+  url: string
+  username?: string
+  key_path?: string
+  attempt: number
+}
+
+export interface CredentialResponse {
+  username?: string
+  password?: string
+  save?: boolean // 认证成功后保存到 git 凭据助手
+  cancel?: boolean
+}
+
+export interface GitProgress {
+  operation: string // fetch / pull / push / clone
+  remote: string
+  phase: 'receiving' | 'resolving' | 'packing' | 'pushing' | 'checkout' | 'remote'
+  current: number
+  total: number
+  received_objects: number
+  indexed_objects: number
+  total_objects: number
+  indexed_deltas: number
+  total_deltas: number
+  bytes: number
+  message?: string // 服务端消息
+}
+
+export interface CloneRequest {
+  branch?: string // 默认为远程 HEAD
+  depth?: number // 浅克隆深度
+  recurse_submodules?: boolean
+  bare?: boolean
+  mirror?: boolean // 镜像克隆，隐含 bare
+}
+
+export interface CloneResult {
+  path: string
+  bare: boolean
+  head?: string
+  submodules: string[]
+  message: string
+}
+
+export interface InitRequest {
+  initial_branch?: string // 默认取 init.defaultBranch，未配置时为 main
+  bare?: boolean
+  gitignore_template?: string
+  readme?: boolean
+  initial_commit?: boolean
+}
+
+export interface InitResult {
+  path: string
+  bare: boolean
+  branch: string
+  commit?: string
+  files: string[]
+  message: string
+}
+
+export interface SubmoduleInfo {
+  name: string
+  path: string
+  url?: string
+  branch?: string
+  head_id?: string
+  index_id?: string
+  workdir_id?: string
+  initialized: boolean
+  cloned: boolean
+  out_of_sync: boolean
+  dirty: boolean
+  status: 'uninitialized' | 'not-cloned' | 'out-of-sync' | 'dirty' | 'clean'
+}
+
+export interface WorktreeInfo {
+  name?: string // 主工作树为空
+  path: string
+  branch?: string
+  head?: string
+  is_main: boolean
+  is_current: boolean
+  locked: boolean
+  lock_reason?: string
+  prunable: boolean // 工作树目录已不存在
+}
+
+export interface WorktreeAddRequest {
+  branch?: string
+  new_branch?: boolean
+  start_point?: string
+  lock?: boolean
+}
+
+export interface LfsPointer {
+  oid: string
+  size: number
+}
+
+export interface LfsFileInfo {
+  path: string
+  tracked: boolean
+  pointer?: LfsPointer
+  available: boolean // 对象已在 .git/lfs/objects 中
+  object_path?: string
+}
+
+// 仓库监听事件 workspace-changed / refs-changed / head-changed 的负载
+export interface RepoChangeEvent {
+  repo_path: string
+}